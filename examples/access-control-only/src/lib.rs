@@ -0,0 +1,78 @@
+#![no_std]
+
+//! A registry of numbered entries that `writer`s set and `manager`s staff.
+//!
+//! It implements `AccessControl`, with its role enumeration, and no
+//! `Ownable`, so it only publishes the access control events and the indexer
+//! classifies it as `ACCESS_CONTROL`. The playground covers
+//! `ACCESS_CONTROL_OWNABLE`, and `ownable-only` covers `OWNABLE`.
+
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+use stellar_access::access_control::{self as access_control, AccessControl};
+use stellar_macros::default_impl;
+
+/// Writer: Can set entries
+pub const WRITER_ROLE: Symbol = symbol_short!("writer");
+
+/// Manager: Can grant and revoke WRITER
+pub const MANAGER_ROLE: Symbol = symbol_short!("manager");
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Entry(Symbol),
+}
+
+#[contract]
+pub struct AccessControlRegistry;
+
+#[contractimpl]
+impl AccessControlRegistry {
+    /// Initialize the registry with its admin and initial role assignments.
+    ///
+    /// - Sets the AccessControl admin.
+    /// - Makes MANAGER_ROLE the admin role of WRITER_ROLE (emits
+    ///   `RoleAdminChanged`).
+    /// - Grants each `(role, account)` in `initial_roles` (emits `RoleGranted`
+    ///   with the admin as caller).
+    pub fn __constructor(e: &Env, admin: Address, initial_roles: Vec<(Symbol, Address)>) {
+        access_control::set_admin(e, &admin);
+        access_control::set_role_admin_no_auth(e, &WRITER_ROLE, &MANAGER_ROLE);
+        for (role, account) in initial_roles.iter() {
+            access_control::grant_role_no_auth(e, &admin, &account, &role);
+        }
+    }
+
+    /// Set the entry `key` to `value` (requires WRITER_ROLE).
+    ///
+    /// Checks the role as `#[only_role]` would, which only takes it as a
+    /// string literal.
+    pub fn set_entry(e: &Env, caller: Address, key: Symbol, value: u32) {
+        access_control::ensure_role(e, &caller, &WRITER_ROLE);
+        caller.require_auth();
+        e.storage().persistent().set(&DataKey::Entry(key), &value);
+    }
+
+    pub fn get_entry(e: &Env, key: Symbol) -> Option<u32> {
+        e.storage().persistent().get(&DataKey::Entry(key))
+    }
+
+    /// Every member of `role`, from the library's role enumeration.
+    pub fn list_role_members(e: &Env, role: Symbol) -> Vec<Address> {
+        let mut members = Vec::new(e);
+        for index in 0..access_control::get_role_member_count(e, &role) {
+            members.push_back(access_control::get_role_member(e, &role, index));
+        }
+        members
+    }
+}
+
+/// The library's `AccessControl`, including `get_role_member` and
+/// `get_role_member_count`.
+#[default_impl]
+#[contractimpl]
+impl AccessControl for AccessControlRegistry {}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,178 @@
+#![cfg(test)]
+
+extern crate std;
+
+use rbac_test_support::{assert_authorized_by, assert_last_events};
+use soroban_sdk::{symbol_short, testutils::Address as _, vec, Address, Env, IntoVal, Symbol};
+
+use stellar_access::access_control::{
+    AdminTransferCompleted, AdminTransferInitiated, RoleAdminChanged, RoleGranted, RoleRevoked,
+};
+
+use crate::{AccessControlRegistry, AccessControlRegistryClient, MANAGER_ROLE, WRITER_ROLE};
+
+struct Setup<'a> {
+    env: Env,
+    contract_id: Address,
+    admin: Address,
+    manager: Address,
+    writer: Address,
+    client: AccessControlRegistryClient<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let writer = Address::generate(&env);
+    let contract_id = env.register(
+        AccessControlRegistry,
+        (
+            &admin,
+            vec![
+                &env,
+                (MANAGER_ROLE, manager.clone()),
+                (WRITER_ROLE, writer.clone()),
+            ],
+        ),
+    );
+    let client = AccessControlRegistryClient::new(&env, &contract_id);
+
+    Setup {
+        env,
+        contract_id,
+        admin,
+        manager,
+        writer,
+        client,
+    }
+}
+
+#[test]
+fn constructor_sets_hierarchy_and_grants_initial_roles() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let contract_id = env.register(
+        AccessControlRegistry,
+        (&admin, vec![&env, (MANAGER_ROLE, manager.clone())]),
+    );
+
+    assert_last_events(
+        &env,
+        &contract_id,
+        &[
+            &RoleAdminChanged {
+                role: WRITER_ROLE,
+                previous_admin_role: Symbol::new(&env, ""),
+                new_admin_role: MANAGER_ROLE,
+            },
+            &RoleGranted {
+                role: MANAGER_ROLE,
+                account: manager.clone(),
+                caller: admin.clone(),
+            },
+        ],
+    );
+    let client = AccessControlRegistryClient::new(&env, &contract_id);
+    assert_eq!(client.get_admin(), Some(admin));
+    assert_eq!(client.get_role_admin(&WRITER_ROLE), Some(MANAGER_ROLE));
+    assert_eq!(client.list_role_members(&MANAGER_ROLE), vec![&env, manager]);
+}
+
+#[test]
+fn writers_set_entries() {
+    let s = setup();
+    let key = symbol_short!("limit");
+
+    s.client.set_entry(&s.writer, &key, &42);
+    assert_authorized_by(
+        &s.env,
+        &s.contract_id,
+        &s.writer,
+        "set_entry",
+        (s.writer.clone(), key.clone(), 42_u32).into_val(&s.env),
+    );
+    assert_eq!(s.client.get_entry(&key), Some(42));
+    assert_eq!(s.client.get_entry(&symbol_short!("other")), None);
+
+    assert!(s.client.try_set_entry(&s.manager, &key, &7).is_err());
+    assert_eq!(s.client.get_entry(&key), Some(42));
+}
+
+#[test]
+fn managers_grant_and_revoke_writers() {
+    let s = setup();
+    let second = Address::generate(&s.env);
+
+    s.client.grant_role(&s.manager, &second, &WRITER_ROLE);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleGranted {
+            role: WRITER_ROLE,
+            account: second.clone(),
+            caller: s.manager.clone(),
+        }],
+    );
+    assert_eq!(
+        s.client.list_role_members(&WRITER_ROLE),
+        vec![&s.env, s.writer.clone(), second.clone()]
+    );
+    s.client.set_entry(&second, &symbol_short!("limit"), &1);
+
+    s.client.revoke_role(&s.manager, &s.writer, &WRITER_ROLE);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleRevoked {
+            role: WRITER_ROLE,
+            account: s.writer.clone(),
+            caller: s.manager.clone(),
+        }],
+    );
+    // Enumeration moves the last member into the freed slot.
+    assert_eq!(s.client.get_role_member_count(&WRITER_ROLE), 1);
+    assert_eq!(s.client.get_role_member(&WRITER_ROLE, &0), second);
+    assert!(s
+        .client
+        .try_set_entry(&s.writer, &symbol_short!("limit"), &2)
+        .is_err());
+
+    // Writers can't grant their own role.
+    assert!(s
+        .client
+        .try_grant_role(&second, &s.writer, &WRITER_ROLE)
+        .is_err());
+}
+
+#[test]
+fn admin_transfer_takes_two_steps() {
+    let s = setup();
+    let new_admin = Address::generate(&s.env);
+
+    s.client.transfer_admin_role(&new_admin, &1000);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&AdminTransferInitiated {
+            current_admin: s.admin.clone(),
+            new_admin: new_admin.clone(),
+            live_until_ledger: 1000,
+        }],
+    );
+    assert_eq!(s.client.get_admin(), Some(s.admin.clone()));
+
+    s.client.accept_admin_transfer();
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&AdminTransferCompleted {
+            new_admin: new_admin.clone(),
+            previous_admin: s.admin.clone(),
+        }],
+    );
+    assert_eq!(s.client.get_admin(), Some(new_admin));
+}
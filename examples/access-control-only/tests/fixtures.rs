@@ -0,0 +1,95 @@
+//! Exports the events of a scripted run as a fixture for contract-type
+//! detection: one JSON `LedgerEvent` per line, as `rbac-state-reconstructor`
+//! reads them. Only access control events appear, so the contract is
+//! `ACCESS_CONTROL`.
+//!
+//! After an intended change, rewrite `fixtures/events.jsonl` with
+//! `UPDATE_FIXTURES=1 cargo test -p rbac-access-control-only --test fixtures`.
+
+use rbac_access_control_only::{
+    AccessControlRegistry, AccessControlRegistryClient, MANAGER_ROLE, WRITER_ROLE,
+};
+use rbac_state_reconstructor::{read_events, ContractType, State};
+use rbac_test_support::{account, assert_fixture_is_current, sandbox, strkey, EventLog};
+use soroban_sdk::{symbol_short, vec, Address, Env};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/events.jsonl");
+
+/// Deploys a registry with a manager and a writer, swaps the writer for
+/// another one, has the new writer renounce and the admin hand over.
+fn scripted_run() -> (Env, EventLog, Address) {
+    let env = sandbox();
+    let mut log = EventLog::new();
+    let admin = account(&env);
+    let manager = account(&env);
+    let writer = account(&env);
+    let second = account(&env);
+    let new_admin = account(&env);
+    let contract_id = env.register(
+        AccessControlRegistry,
+        (
+            &admin,
+            vec![
+                &env,
+                (MANAGER_ROLE, manager.clone()),
+                (WRITER_ROLE, writer.clone()),
+            ],
+        ),
+    );
+    log.record(&env);
+    let client = AccessControlRegistryClient::new(&env, &contract_id);
+
+    client.set_entry(&writer, &symbol_short!("limit"), &10);
+    log.record(&env);
+    client.grant_role(&manager, &second, &WRITER_ROLE);
+    log.record(&env);
+    client.revoke_role(&manager, &writer, &WRITER_ROLE);
+    log.record(&env);
+    client.grant_role(&admin, &writer, &WRITER_ROLE);
+    log.record(&env);
+    client.renounce_role(&second, &WRITER_ROLE);
+    log.record(&env);
+    client.transfer_admin_role(&new_admin, &(env.ledger().sequence() + 100));
+    log.record(&env);
+    client.accept_admin_transfer();
+    log.record(&env);
+    (env, log, contract_id)
+}
+
+#[test]
+fn fixture_is_current() {
+    let (_, log, _) = scripted_run();
+    assert_fixture_is_current(FIXTURE, &log.json_lines());
+}
+
+#[test]
+fn fixture_replays_as_an_access_control_contract() {
+    let (env, _, contract_id) = scripted_run();
+    let events = read_events(std::io::BufReader::new(
+        std::fs::File::open(FIXTURE).unwrap(),
+    ))
+    .unwrap();
+    let state = State::replay(&events);
+    let contract = strkey(&contract_id);
+
+    assert_eq!(state.contracts.len(), 1);
+    assert_eq!(
+        state.contracts[&contract].contract_type,
+        ContractType::AccessControl
+    );
+    let client = AccessControlRegistryClient::new(&env, &contract_id);
+    for role in [MANAGER_ROLE, WRITER_ROLE] {
+        let mut on_chain: std::vec::Vec<String> = client
+            .list_role_members(&role)
+            .iter()
+            .map(|member| strkey(&member))
+            .collect();
+        on_chain.sort();
+        assert_eq!(state.role_members(&contract, &role.to_string()), on_chain);
+    }
+    assert_eq!(
+        state.admin(&contract).map(str::to_string),
+        client.get_admin().map(|admin| strkey(&admin))
+    );
+    assert_eq!(state.owner(&contract), None);
+}
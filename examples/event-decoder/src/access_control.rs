@@ -0,0 +1,110 @@
+//! The `AccessControlEvent` entity of `schema.graphql`, as built by the
+//! SubQuery handlers in `src/mappings/mappingHandlers.ts`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Event;
+use crate::DecodedEvent;
+
+/// `EventType` in `schema.graphql`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    RoleGranted,
+    RoleRevoked,
+    AdminTransferInitiated,
+    AdminTransferCompleted,
+    AdminRenounced,
+    RoleAdminChanged,
+    OwnershipTransferStarted,
+    OwnershipTransferCompleted,
+    OwnershipRenounced,
+}
+
+/// The event-derived fields of an `AccessControlEvent`.
+///
+/// Ledger context (`id`, `blockHeight`, `timestamp`, `txHash`, `ledger`) is
+/// not part of the event itself and is left to the caller. Absent fields
+/// serialize as `null`, so every JSON object has the same keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessControlEvent {
+    pub contract: String,
+    pub role: Option<String>,
+    pub account: Option<String>,
+    pub admin: Option<String>,
+    pub previous_admin_role: Option<String>,
+    pub new_admin_role: Option<String>,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub live_until_ledger: Option<u32>,
+}
+
+impl AccessControlEvent {
+    /// Maps a decoded event the way the SubQuery handlers do, or returns
+    /// `None` for events they do not index.
+    pub fn from_decoded(decoded: &DecodedEvent) -> Option<Self> {
+        let mut entity = AccessControlEvent {
+            contract: decoded.contract.clone(),
+            role: None,
+            account: None,
+            admin: None,
+            previous_admin_role: None,
+            new_admin_role: None,
+            event_type: EventType::RoleGranted,
+            live_until_ledger: None,
+        };
+
+        match &decoded.event {
+            Event::RoleGranted(event) => {
+                entity.role = Some(event.role.to_string());
+                entity.account = Some(event.account.to_string());
+                entity.admin = Some(event.caller.to_string());
+            }
+            Event::RoleRevoked(event) => {
+                entity.event_type = EventType::RoleRevoked;
+                entity.role = Some(event.role.to_string());
+                entity.account = Some(event.account.to_string());
+                entity.admin = Some(event.caller.to_string());
+            }
+            Event::RoleAdminChanged(event) => {
+                entity.event_type = EventType::RoleAdminChanged;
+                entity.role = Some(event.role.to_string());
+                entity.previous_admin_role = Some(event.previous_admin_role.to_string());
+                entity.new_admin_role = Some(event.new_admin_role.to_string());
+            }
+            Event::AdminTransferInitiated(event) => {
+                entity.event_type = EventType::AdminTransferInitiated;
+                entity.account = Some(event.new_admin.to_string());
+                entity.admin = Some(event.current_admin.to_string());
+                entity.live_until_ledger = Some(event.live_until_ledger);
+            }
+            Event::AdminTransferCompleted(event) => {
+                entity.event_type = EventType::AdminTransferCompleted;
+                entity.account = Some(event.new_admin.to_string());
+                entity.admin = Some(event.previous_admin.to_string());
+            }
+            Event::AdminRenounced(event) => {
+                entity.event_type = EventType::AdminRenounced;
+                entity.account = Some(event.admin.to_string());
+            }
+            Event::OwnershipTransfer(event) => {
+                entity.event_type = EventType::OwnershipTransferStarted;
+                entity.account = Some(event.new_owner.to_string());
+                entity.admin = Some(event.old_owner.to_string());
+                entity.live_until_ledger = Some(event.live_until_ledger);
+            }
+            Event::OwnershipTransferCompleted(event) => {
+                entity.event_type = EventType::OwnershipTransferCompleted;
+                entity.account = Some(event.new_owner.to_string());
+            }
+            Event::OwnershipRenounced(event) => {
+                entity.event_type = EventType::OwnershipRenounced;
+                entity.account = Some(event.old_owner.to_string());
+            }
+            _ => return None,
+        }
+
+        Some(entity)
+    }
+}
@@ -0,0 +1,307 @@
+//! Typed structs for every event the playground can emit: its own events and
+//! those of the `stellar-access` and `stellar-contract-utils` modules it uses.
+//!
+//! Each struct lists its topic fields (after the event name) first, then its
+//! data fields, with the same names and order as the contract.
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::ScVal;
+
+use crate::scval::{data_entries, Address, Bytes32, FromScVal, Symbol};
+use crate::DecodeError;
+
+macro_rules! events {
+    ($(
+        $(#[$doc:meta])*
+        $name:ident = $event:literal {
+            topics { $($topic:ident: $topic_ty:ty),* $(,)? }
+            data { $($(#[$attr:meta])* $field:ident: $field_ty:ty),* $(,)? }
+        }
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+            pub struct $name {
+                $(pub $topic: $topic_ty,)*
+                $($(#[$attr])* pub $field: $field_ty,)*
+            }
+
+            impl $name {
+                /// The event name, published as the first topic.
+                pub const NAME: &'static str = $event;
+
+                #[allow(unused_mut, unused_variables)]
+                fn decode(topics: &[ScVal], data: &ScVal) -> Result<Self, DecodeError> {
+                    let topic_names: &[&str] = &[$(stringify!($topic)),*];
+                    if topics.len() != topic_names.len() {
+                        return Err(DecodeError::TopicCount {
+                            event: $event,
+                            expected: topic_names.len(),
+                            found: topics.len(),
+                        });
+                    }
+                    let field_names: &[&str] = &[$(stringify!($field)),*];
+                    let entries = data_entries(data)
+                        .filter(|entries| entries.len() == field_names.len())
+                        .ok_or(DecodeError::DataShape { event: $event })?;
+
+                    let mut topics = topics.iter();
+                    Ok(Self {
+                        $($topic: FromScVal::from_scval(topics.next().unwrap()).ok_or(
+                            DecodeError::InvalidField {
+                                event: $event,
+                                field: stringify!($topic),
+                            },
+                        )?,)*
+                        $($field: {
+                            let field = stringify!($field);
+                            let (_, val) = entries
+                                .iter()
+                                .find(|(key, _)| key == field)
+                                .ok_or(DecodeError::MissingField { event: $event, field })?;
+                            FromScVal::from_scval(val)
+                                .ok_or(DecodeError::InvalidField { event: $event, field })?
+                        },)*
+                    })
+                }
+            }
+        )*
+
+        /// Any event the playground can emit. In JSON, the event name is in the
+        /// `event` field next to the event's own fields.
+        #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(tag = "event")]
+        pub enum Event {
+            $(
+                #[serde(rename = $event)]
+                $name($name),
+            )*
+        }
+
+        impl Event {
+            /// Names of all known events.
+            pub const NAMES: &'static [&'static str] = &[$($event),*];
+
+            /// The event name, published as the first topic.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$name(_) => $event,)*
+                }
+            }
+
+            /// Decodes an event from its name and the remaining topics and data.
+            pub(crate) fn decode(
+                name: &str,
+                topics: &[ScVal],
+                data: &ScVal,
+            ) -> Result<Self, DecodeError> {
+                match name {
+                    $($event => $name::decode(topics, data).map(Event::$name),)*
+                    _ => Err(DecodeError::UnknownEvent(name.to_string())),
+                }
+            }
+        }
+    };
+}
+
+events! {
+    // ------------------------------------------------------------------------
+    // stellar-access: AccessControl
+    // ------------------------------------------------------------------------
+
+    /// A role was granted to `account`.
+    RoleGranted = "role_granted" {
+        topics { role: Symbol, account: Address }
+        data { caller: Address }
+    }
+
+    /// A role was revoked from, or renounced by, `account`.
+    RoleRevoked = "role_revoked" {
+        topics { role: Symbol, account: Address }
+        data { caller: Address }
+    }
+
+    /// The admin role of `role` changed. An unset admin role is the empty
+    /// symbol.
+    RoleAdminChanged = "role_admin_changed" {
+        topics { role: Symbol }
+        data { previous_admin_role: Symbol, new_admin_role: Symbol }
+    }
+
+    /// The admin started a two-step admin transfer.
+    AdminTransferInitiated = "admin_transfer_initiated" {
+        topics { current_admin: Address }
+        data { new_admin: Address, live_until_ledger: u32 }
+    }
+
+    /// The new admin accepted an admin transfer.
+    AdminTransferCompleted = "admin_transfer_completed" {
+        topics { new_admin: Address }
+        data { previous_admin: Address }
+    }
+
+    /// The admin renounced the admin role.
+    AdminRenounced = "admin_renounced" {
+        topics { admin: Address }
+        data {}
+    }
+
+    // ------------------------------------------------------------------------
+    // stellar-access: Ownable
+    // ------------------------------------------------------------------------
+
+    /// The owner started a two-step ownership transfer.
+    OwnershipTransfer = "ownership_transfer" {
+        topics {}
+        data { old_owner: Address, new_owner: Address, live_until_ledger: u32 }
+    }
+
+    /// The new owner accepted an ownership transfer.
+    OwnershipTransferCompleted = "ownership_transfer_completed" {
+        topics {}
+        data { new_owner: Address }
+    }
+
+    /// The owner renounced ownership.
+    OwnershipRenounced = "ownership_renounced" {
+        topics {}
+        data { old_owner: Address }
+    }
+
+    // ------------------------------------------------------------------------
+    // stellar-contract-utils: Pausable
+    // ------------------------------------------------------------------------
+
+    /// The contract was paused.
+    Paused = "paused" {
+        topics {}
+        data {}
+    }
+
+    /// The contract was unpaused.
+    Unpaused = "unpaused" {
+        topics {}
+        data {}
+    }
+
+    // ------------------------------------------------------------------------
+    // RbacPlayground
+    // ------------------------------------------------------------------------
+
+    /// Published at the end of the constructor.
+    Initialized = "initialized" {
+        topics {}
+        data {
+            admin: Address,
+            owner: Address,
+            initial_roles: Vec<(Symbol, Address)>,
+            version: (u32, u32, u32),
+            capabilities: Vec<Symbol>,
+        }
+    }
+
+    /// Tokens were minted to `to`.
+    Minted = "minted" {
+        topics { to: Address, caller: Address }
+        data { #[serde(with = "crate::scval::i128_string")] amount: i128 }
+    }
+
+    /// Tokens were burned from `from`.
+    Burned = "burned" {
+        topics { from: Address, caller: Address }
+        data { #[serde(with = "crate::scval::i128_string")] amount: i128 }
+    }
+
+    /// Follows `paused` with who paused the contract, why and until when.
+    PauseDetails = "pause_details" {
+        topics { caller: Address }
+        data { reason: Symbol, until_ledger: Option<u32> }
+    }
+
+    /// Follows `unpaused` when the owner lifts a pause.
+    EmergencyUnpaused = "emergency_unpaused" {
+        topics { caller: Address }
+        data {}
+    }
+
+    /// A transfer agent moved tokens.
+    TransferExecuted = "transfer_executed" {
+        topics { from: Address, to: Address, caller: Address }
+        data { #[serde(with = "crate::scval::i128_string")] amount: i128 }
+    }
+
+    /// A batch mint/burn or batch role grant/revoke.
+    BatchOperation = "batch_operation" {
+        topics { operation: Symbol, caller: Address }
+        data { count: u32 }
+    }
+
+    /// A multi-sig transfer was proposed.
+    TransferProposed = "transfer_proposed" {
+        topics { id: u64, from: Address, to: Address }
+        data {
+            #[serde(with = "crate::scval::i128_string")] amount: i128,
+            proposer: Address,
+        }
+    }
+
+    /// A pending transfer was approved for `approver`, by `delegate` if set.
+    TransferApproved = "transfer_approved" {
+        topics { id: u64, approver: Address }
+        data { current_approvals: u32, required_approvals: u32, delegate: Option<Address> }
+    }
+
+    /// A pending transfer reached its approvals and was executed.
+    TransferFinalized = "transfer_finalized" {
+        topics { id: u64, from: Address, to: Address }
+        data { #[serde(with = "crate::scval::i128_string")] amount: i128 }
+    }
+
+    /// The admin set or removed a role's member cap.
+    RoleCapSet = "role_cap_set" {
+        topics { role: Symbol }
+        data { max_members: Option<u32>, caller: Address }
+    }
+
+    /// A role was offered to `account`, pending `accept_role`.
+    RoleOffered = "role_offered" {
+        topics { role: Symbol, account: Address }
+        data { caller: Address, live_until_ledger: u32 }
+    }
+
+    /// A pending role offer was withdrawn.
+    RoleOfferCancelled = "role_offer_cancelled" {
+        topics { role: Symbol, account: Address }
+        data { caller: Address }
+    }
+
+    /// A role holder delegated `role` to `delegate`.
+    DelegationCreated = "delegation_created" {
+        topics { delegator: Address, delegate: Address, role: Symbol }
+        data { until_ledger: u32 }
+    }
+
+    /// A delegation was revoked or replaced.
+    DelegationRevoked = "delegation_revoked" {
+        topics { delegator: Address, delegate: Address, role: Symbol }
+        data {}
+    }
+
+    /// `account` was stripped of its roles by `revoke_all_roles`.
+    AccountCompromised = "account_compromised" {
+        topics { account: Address }
+        data { caller: Address, revoked_roles: Vec<Symbol>, cancelled_transfers: Vec<u64> }
+    }
+
+    /// The owner replaced the contract code.
+    Upgraded = "upgraded" {
+        topics { caller: Address }
+        data { new_wasm_hash: Bytes32 }
+    }
+
+    /// A viewer read sensitive data.
+    SensitiveDataAccessed = "sensitive_data_accessed" {
+        topics { viewer: Address }
+        data { data_type: Symbol }
+    }
+}
@@ -0,0 +1,152 @@
+//! Decoder for the events of the RBAC playground contract.
+//!
+//! Parses raw XDR `ContractEvent`/`DiagnosticEvent` bytes into the typed
+//! structs of [`events`], covering the playground's own events and those of
+//! the `stellar-access` and `stellar-contract-utils` modules it uses. Decoded
+//! events serialize to JSON with serde; [`AccessControlEvent`] gives the shape
+//! of `schema.graphql`'s entity for the events the indexer stores.
+//!
+//! ```ignore
+//! let decoded = rbac_event_decoder::decode_contract_event(&xdr_bytes)?;
+//! println!("{}", serde_json::to_string(&decoded)?);
+//! if let Some(entity) = decoded.access_control_event() {
+//!     println!("{}", serde_json::to_string(&entity)?);
+//! }
+//! ```
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{
+    ContractEvent, ContractEventBody, ContractEventType, DiagnosticEvent, Limits, ReadXdr, ScVal,
+};
+
+mod access_control;
+pub mod events;
+mod scval;
+
+pub use access_control::{AccessControlEvent, EventType};
+pub use events::Event;
+pub use scval::{Address, Bytes32, Symbol};
+
+/// A decoded event and the contract that published it.
+///
+/// Serializes as `{"contract": "C...", "event": "<name>", ...fields}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecodedEvent {
+    pub contract: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl DecodedEvent {
+    /// The `AccessControlEvent` the indexer stores for this event, if any.
+    pub fn access_control_event(&self) -> Option<AccessControlEvent> {
+        AccessControlEvent::from_decoded(self)
+    }
+}
+
+/// Errors raised while decoding an event.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The bytes are not a valid XDR value of the expected type.
+    Xdr(stellar_xdr::curr::Error),
+    /// The event is a system or diagnostic event, not a contract event.
+    NotContractEvent,
+    /// The event has no contract id.
+    MissingContract,
+    /// The first topic is missing or not a symbol.
+    MissingName,
+    /// The event name is not one the playground emits.
+    UnknownEvent(String),
+    /// The event has the wrong number of topics after its name.
+    TopicCount {
+        event: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// The event data is not a map with the expected number of fields.
+    DataShape { event: &'static str },
+    /// A data field is missing.
+    MissingField {
+        event: &'static str,
+        field: &'static str,
+    },
+    /// A topic or data field has the wrong type.
+    InvalidField {
+        event: &'static str,
+        field: &'static str,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Xdr(error) => write!(f, "invalid XDR: {error}"),
+            DecodeError::NotContractEvent => f.write_str("not a contract event"),
+            DecodeError::MissingContract => f.write_str("event has no contract id"),
+            DecodeError::MissingName => f.write_str("event has no name topic"),
+            DecodeError::UnknownEvent(name) => write!(f, "unknown event `{name}`"),
+            DecodeError::TopicCount {
+                event,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{event}` has {found} topics after its name, expected {expected}"
+            ),
+            DecodeError::DataShape { event } => write!(f, "`{event}` has unexpected data"),
+            DecodeError::MissingField { event, field } => {
+                write!(f, "`{event}` is missing field `{field}`")
+            }
+            DecodeError::InvalidField { event, field } => {
+                write!(f, "`{event}` has an invalid `{field}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<stellar_xdr::curr::Error> for DecodeError {
+    fn from(error: stellar_xdr::curr::Error) -> Self {
+        DecodeError::Xdr(error)
+    }
+}
+
+/// Decodes XDR-encoded `ContractEvent` bytes.
+pub fn decode_contract_event(bytes: &[u8]) -> Result<DecodedEvent, DecodeError> {
+    decode(&ContractEvent::from_xdr(bytes, Limits::none())?)
+}
+
+/// Decodes the contract event inside XDR-encoded `DiagnosticEvent` bytes.
+pub fn decode_diagnostic_event(bytes: &[u8]) -> Result<DecodedEvent, DecodeError> {
+    decode(&DiagnosticEvent::from_xdr(bytes, Limits::none())?.event)
+}
+
+/// Decodes a parsed `ContractEvent`.
+pub fn decode(event: &ContractEvent) -> Result<DecodedEvent, DecodeError> {
+    if event.type_ != ContractEventType::Contract {
+        return Err(DecodeError::NotContractEvent);
+    }
+    let contract = event
+        .contract_id
+        .as_ref()
+        .ok_or(DecodeError::MissingContract)?
+        .to_string();
+
+    let ContractEventBody::V0(body) = &event.body;
+    let (name, topics) = body.topics.split_first().ok_or(DecodeError::MissingName)?;
+    let ScVal::Symbol(name) = name else {
+        return Err(DecodeError::MissingName);
+    };
+    let name = name
+        .0
+        .to_utf8_string()
+        .map_err(|_| DecodeError::MissingName)?;
+
+    Ok(DecodedEvent {
+        contract,
+        event: Event::decode(&name, topics, &body.data)?,
+    })
+}
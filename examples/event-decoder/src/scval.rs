@@ -0,0 +1,175 @@
+//! Conversion of event topics and data from `ScVal` into Rust field types.
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{Int128Parts, ScMap, ScVal, ScVec};
+
+/// A Stellar account (`G...`) or contract (`C...`) address in strkey form.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Address(pub String);
+
+/// A Soroban symbol, such as a role name.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Symbol(pub String);
+
+/// A 32-byte value such as a wasm hash, as lowercase hex.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bytes32(pub String);
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Conversion from the `ScVal` a contract type is encoded as.
+pub(crate) trait FromScVal: Sized {
+    fn from_scval(val: &ScVal) -> Option<Self>;
+}
+
+impl FromScVal for Address {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Address(address) => Some(Address(address.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for Symbol {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Symbol(symbol) => symbol.0.to_utf8_string().ok().map(Symbol),
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for Bytes32 {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Bytes(bytes) if bytes.len() == 32 => {
+                Some(Bytes32(bytes.iter().map(|b| format!("{b:02x}")).collect()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for bool {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for u32 {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::U32(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for u64 {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::U64(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromScVal for i128 {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::I128(Int128Parts { hi, lo }) => Some((i128::from(*hi) << 64) | i128::from(*lo)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromScVal> FromScVal for Option<T> {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Void => Some(None),
+            val => T::from_scval(val).map(Some),
+        }
+    }
+}
+
+impl<T: FromScVal> FromScVal for Vec<T> {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        vec_items(val)?.iter().map(T::from_scval).collect()
+    }
+}
+
+impl<A: FromScVal, B: FromScVal> FromScVal for (A, B) {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match vec_items(val)? {
+            [a, b] => Some((A::from_scval(a)?, B::from_scval(b)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<A: FromScVal, B: FromScVal, C: FromScVal> FromScVal for (A, B, C) {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        match vec_items(val)? {
+            [a, b, c] => Some((A::from_scval(a)?, B::from_scval(b)?, C::from_scval(c)?)),
+            _ => None,
+        }
+    }
+}
+
+fn vec_items(val: &ScVal) -> Option<&[ScVal]> {
+    match val {
+        ScVal::Vec(Some(ScVec(items))) => Some(items.as_slice()),
+        _ => None,
+    }
+}
+
+/// The data payload of an event: a map keyed by field name. Events without
+/// data fields may publish an empty map or `Void`.
+pub(crate) fn data_entries(data: &ScVal) -> Option<Vec<(String, &ScVal)>> {
+    match data {
+        ScVal::Void => Some(Vec::new()),
+        ScVal::Map(Some(ScMap(entries))) => entries
+            .iter()
+            .map(|entry| match &entry.key {
+                ScVal::Symbol(key) => key.0.to_utf8_string().ok().map(|key| (key, &entry.val)),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Serializes `i128` amounts as decimal strings, which JSON consumers can read
+/// without losing precision.
+pub(crate) mod i128_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,274 @@
+//! Decodes every event the playground emits in the sandbox and round-trips it
+//! through JSON.
+
+use std::collections::BTreeSet;
+
+use rbac_event_decoder::{
+    decode_contract_event, decode_diagnostic_event, events, AccessControlEvent, DecodedEvent,
+    Event, EventType,
+};
+use rbac_playground::{RbacPlayground, RbacPlaygroundClient};
+use rbac_test_support::{contract_events, strkey};
+use serde_json::json;
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger as _},
+    vec,
+    xdr::{ContractEventType, DiagnosticEvent, Limits, ReadXdr, WriteXdr},
+    Address, Env, Symbol, Vec,
+};
+
+fn decode_all(recorded: &[std::vec::Vec<u8>]) -> std::vec::Vec<DecodedEvent> {
+    recorded
+        .iter()
+        .map(|bytes| decode_contract_event(bytes).unwrap())
+        .collect()
+}
+
+/// Runs a scenario that makes the playground emit every event it can, and
+/// returns the XDR of each event in order.
+fn record_all_events(env: &Env, contract_id: &Address) -> std::vec::Vec<std::vec::Vec<u8>> {
+    // The constructor's events, before any view call replaces them.
+    let mut recorded = contract_events(env);
+    let client = RbacPlaygroundClient::new(env, contract_id);
+    let admin = client.get_admin().unwrap();
+    let alice = Address::generate(env);
+    let bob = Address::generate(env);
+    let approver = Address::generate(env);
+    let new_admin = Address::generate(env);
+    let new_owner = Address::generate(env);
+    let pauser = symbol_short!("pauser");
+    let viewer = symbol_short!("viewer");
+    let record = |recorded: &mut std::vec::Vec<_>| recorded.extend(contract_events(env));
+
+    client.grant_role(&admin, &alice, &symbol_short!("transfer"));
+    record(&mut recorded);
+    client.grant_role(&admin, &approver, &symbol_short!("approver"));
+    record(&mut recorded);
+    client.grant_role(&admin, &approver, &symbol_short!("burner"));
+    record(&mut recorded);
+    client.grant_role(&admin, &alice, &viewer);
+    record(&mut recorded);
+    client.grant_role(&admin, &alice, &pauser);
+    record(&mut recorded);
+    client.mint(&alice, &1_000, &admin);
+    record(&mut recorded);
+    client.batch_mint(&vec![env, bob.clone()], &vec![env, 50i128], &admin);
+    record(&mut recorded);
+    client.execute_transfer(&alice, &bob, &100, &alice);
+    record(&mut recorded);
+    let id = client.propose_transfer(&alice, &bob, &10, &1, &alice);
+    record(&mut recorded);
+    client.approve_transfer(&id, &approver);
+    record(&mut recorded);
+    client.view_sensitive_stats(&alice);
+    record(&mut recorded);
+    client.set_role_cap(&viewer, &Some(5));
+    record(&mut recorded);
+    client.offer_role(&bob, &viewer, &100, &admin);
+    record(&mut recorded);
+    client.offer_role(&bob, &viewer, &0, &admin);
+    record(&mut recorded);
+    client.delegate_role(&alice, &viewer, &bob, &100);
+    record(&mut recorded);
+    client.revoke_delegation(&alice, &viewer);
+    record(&mut recorded);
+    client.pause(&alice, &symbol_short!("incident"), &Some(100));
+    record(&mut recorded);
+    client.emergency_unpause();
+    record(&mut recorded);
+    client.revoke_role(&admin, &alice, &viewer);
+    record(&mut recorded);
+    client.revoke_all_roles(&alice);
+    record(&mut recorded);
+    client.burn(&bob, &5, &approver);
+    record(&mut recorded);
+
+    client.transfer_admin_role(&new_admin, &100);
+    record(&mut recorded);
+    client.accept_admin_transfer();
+    record(&mut recorded);
+    client.renounce_admin();
+    record(&mut recorded);
+
+    client.transfer_ownership(&new_owner, &100);
+    record(&mut recorded);
+    client.accept_ownership();
+    record(&mut recorded);
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(rbac_test_support::wasm("rbac-playground"));
+    client.upgrade(&wasm_hash);
+    record(&mut recorded);
+    client.renounce_ownership();
+    record(&mut recorded);
+
+    recorded
+}
+
+fn setup() -> (Env, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_sequence_number(10);
+
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            vec![&env, (symbol_short!("burner"), carol.clone())],
+            Some(vec![&env, (carol, 500i128)]),
+        ),
+    );
+    (env, contract_id)
+}
+
+#[test]
+fn decodes_every_event_the_contract_emits() {
+    let (env, contract_id) = setup();
+    let recorded = record_all_events(&env, &contract_id);
+
+    let decoded = decode_all(&recorded);
+
+    let seen: BTreeSet<&str> = decoded.iter().map(|d| d.event.name()).collect();
+    let all: BTreeSet<&str> = Event::NAMES.iter().copied().collect();
+    assert_eq!(seen, all);
+    assert!(decoded.iter().all(|d| d.contract == strkey(&contract_id)));
+}
+
+#[test]
+fn decoded_events_round_trip_through_json() {
+    let (env, contract_id) = setup();
+    let recorded = record_all_events(&env, &contract_id);
+
+    for (bytes, decoded) in recorded.iter().zip(decode_all(&recorded)) {
+        let json = serde_json::to_string(&decoded).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DecodedEvent>(&json).unwrap(),
+            decoded
+        );
+
+        if let Some(entity) = decoded.access_control_event() {
+            let json = serde_json::to_string(&entity).unwrap();
+            assert_eq!(
+                serde_json::from_str::<AccessControlEvent>(&json).unwrap(),
+                entity
+            );
+        }
+
+        // The same event inside a diagnostic event decodes identically.
+        let event = soroban_sdk::xdr::ContractEvent::from_xdr(bytes, Limits::none()).unwrap();
+        let diagnostic = DiagnosticEvent {
+            in_successful_contract_call: true,
+            event,
+        };
+        let diagnostic = diagnostic.to_xdr(Limits::none()).unwrap();
+        assert_eq!(decode_diagnostic_event(&diagnostic).unwrap(), decoded);
+    }
+}
+
+#[test]
+fn json_shapes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            Vec::<(Symbol, Address)>::new(&env),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+    let contract = strkey(&contract_id);
+
+    client.mint(&alice, &(i128::MAX), &admin);
+    let minted = decode_all(&contract_events(&env)).remove(0);
+    assert_eq!(
+        minted.event,
+        Event::Minted(events::Minted {
+            to: rbac_event_decoder::Address(strkey(&alice)),
+            caller: rbac_event_decoder::Address(strkey(&admin)),
+            amount: i128::MAX,
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&minted).unwrap(),
+        json!({
+            "contract": contract,
+            "event": "minted",
+            "to": strkey(&alice),
+            "caller": strkey(&admin),
+            "amount": i128::MAX.to_string(),
+        })
+    );
+    assert_eq!(minted.access_control_event(), None);
+
+    client.grant_role(&admin, &alice, &symbol_short!("viewer"));
+    let granted = decode_all(&contract_events(&env)).remove(0);
+    let entity = granted.access_control_event().unwrap();
+    assert_eq!(entity.event_type, EventType::RoleGranted);
+    assert_eq!(
+        serde_json::to_value(&entity).unwrap(),
+        json!({
+            "contract": contract,
+            "role": "viewer",
+            "account": strkey(&alice),
+            "admin": strkey(&admin),
+            "previousAdminRole": null,
+            "newAdminRole": null,
+            "type": "ROLE_GRANTED",
+            "liveUntilLedger": null,
+        })
+    );
+}
+
+#[test]
+fn rejects_unknown_and_malformed_events() {
+    use soroban_sdk::xdr::{
+        ContractEvent, ContractEventBody, ContractEventV0, ContractId, ExtensionPoint, Hash,
+        ScSymbol, ScVal,
+    };
+
+    let event = |name: &str, topics: std::vec::Vec<ScVal>| {
+        let mut all = std::vec![ScVal::Symbol(ScSymbol(name.try_into().unwrap()))];
+        all.extend(topics);
+        ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: Some(ContractId(Hash([1; 32]))),
+            type_: ContractEventType::Contract,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: all.try_into().unwrap(),
+                data: ScVal::Void,
+            }),
+        }
+        .to_xdr(Limits::none())
+        .unwrap()
+    };
+
+    assert!(matches!(
+        decode_contract_event(&event("unknown", std::vec![])),
+        Err(rbac_event_decoder::DecodeError::UnknownEvent(name)) if name == "unknown"
+    ));
+    assert!(matches!(
+        decode_contract_event(&event("role_granted", std::vec![])),
+        Err(rbac_event_decoder::DecodeError::TopicCount {
+            expected: 2,
+            found: 0,
+            ..
+        })
+    ));
+    assert!(matches!(
+        decode_contract_event(&event("admin_renounced", std::vec![ScVal::U32(1)])),
+        Err(rbac_event_decoder::DecodeError::InvalidField { field: "admin", .. })
+    ));
+    assert!(decode_contract_event(&event("paused", std::vec![])).is_ok());
+    assert!(decode_contract_event(&[0, 1, 2]).is_err());
+}
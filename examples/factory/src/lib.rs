@@ -0,0 +1,229 @@
+#![no_std]
+
+//! Deploys instances of the playground from its uploaded wasm.
+//!
+//! Each instance's address follows from the factory's address, the deployer
+//! and the salt it was deployed with, so a script can deploy many of them and
+//! know their addresses beforehand (`deployed_address`), and nobody else can
+//! take those addresses first. Every deployment is recorded in a registry,
+//! listed a page at a time by `deployments`, and announced with a
+//! `PlaygroundDeployed` event.
+
+use soroban_sdk::{
+    contract, contractevent, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    Symbol, Vec,
+};
+
+use stellar_access::ownable::{self as ownable, Ownable};
+use stellar_macros::{default_impl, only_owner};
+
+/// Most deployments `deployments` returns at once.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+const DAY_IN_LEDGERS: u32 = 17280;
+
+/// Ledgers the registry entries and the factory live for after each deploy
+/// or read.
+pub const DEPLOYMENT_EXTEND_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Entries are extended once they have fewer ledgers than this left.
+pub const DEPLOYMENT_TTL_THRESHOLD: u32 = DEPLOYMENT_EXTEND_AMOUNT - DAY_IN_LEDGERS;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    /// Hash of the playground wasm new instances run.
+    WasmHash,
+    DeploymentCount,
+    /// Index -> Deployment (persistent storage).
+    Deployment(u32),
+}
+
+/// A deployment in the registry.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deployment {
+    pub index: u32,
+    pub contract: Address,
+    pub deployer: Address,
+    pub salt: BytesN<32>,
+    pub wasm_hash: BytesN<32>,
+    pub admin: Address,
+    pub owner: Address,
+    pub ledger: u32,
+}
+
+/// Event emitted by `deploy`, after the new instance's constructor events.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlaygroundDeployed {
+    #[topic]
+    pub contract: Address,
+    #[topic]
+    pub deployer: Address,
+    pub index: u32,
+    pub salt: BytesN<32>,
+    pub wasm_hash: BytesN<32>,
+}
+
+/// Event emitted when the owner changes the wasm new instances run.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WasmHashSet {
+    pub wasm_hash: BytesN<32>,
+    #[topic]
+    pub caller: Address,
+}
+
+#[contract]
+pub struct PlaygroundFactory;
+
+#[contractimpl]
+impl PlaygroundFactory {
+    /// Sets the owner and the hash of the uploaded playground wasm.
+    pub fn __constructor(e: &Env, owner: Address, wasm_hash: BytesN<32>) {
+        ownable::set_owner(e, &owner);
+        e.storage().instance().set(&DataKey::WasmHash, &wasm_hash);
+    }
+
+    /// Hash of the playground wasm new instances run.
+    pub fn wasm_hash(e: &Env) -> BytesN<32> {
+        e.storage().instance().get(&DataKey::WasmHash).unwrap()
+    }
+
+    /// Run `wasm_hash` in instances deployed from now on (owner only).
+    /// Existing instances keep their code.
+    #[only_owner]
+    pub fn set_wasm_hash(e: &Env, wasm_hash: BytesN<32>) {
+        e.storage().instance().set(&DataKey::WasmHash, &wasm_hash);
+        WasmHashSet {
+            wasm_hash,
+            caller: ownable::get_owner(e).unwrap(),
+        }
+        .publish(e);
+    }
+
+    /// Deploy a playground at the address `deployer` and `salt` give,
+    /// running its constructor with the remaining arguments, and return the
+    /// address.
+    ///
+    /// Anyone may deploy; `deployer` authorizes it and is recorded. Each
+    /// deployer can use a salt once.
+    pub fn deploy(
+        e: &Env,
+        deployer: Address,
+        salt: BytesN<32>,
+        admin: Address,
+        owner: Address,
+        initial_roles: Vec<(Symbol, Address)>,
+        initial_balances: Option<Vec<(Address, i128)>>,
+    ) -> Address {
+        deployer.require_auth();
+
+        let wasm_hash = Self::wasm_hash(e);
+        let contract = e
+            .deployer()
+            .with_current_contract(deployment_salt(e, &deployer, &salt))
+            .deploy_v2(
+                wasm_hash.clone(),
+                (
+                    admin.clone(),
+                    owner.clone(),
+                    initial_roles,
+                    initial_balances,
+                ),
+            );
+
+        let index = Self::deployment_count(e);
+        let deployment = Deployment {
+            index,
+            contract: contract.clone(),
+            deployer: deployer.clone(),
+            salt: salt.clone(),
+            wasm_hash: wasm_hash.clone(),
+            admin,
+            owner,
+            ledger: e.ledger().sequence(),
+        };
+        let key = DataKey::Deployment(index);
+        e.storage().persistent().set(&key, &deployment);
+        e.storage().persistent().extend_ttl(
+            &key,
+            DEPLOYMENT_TTL_THRESHOLD,
+            DEPLOYMENT_EXTEND_AMOUNT,
+        );
+        e.storage()
+            .instance()
+            .set(&DataKey::DeploymentCount, &(index + 1));
+        e.storage()
+            .instance()
+            .extend_ttl(DEPLOYMENT_TTL_THRESHOLD, DEPLOYMENT_EXTEND_AMOUNT);
+
+        PlaygroundDeployed {
+            contract: contract.clone(),
+            deployer,
+            index,
+            salt,
+            wasm_hash,
+        }
+        .publish(e);
+        contract
+    }
+
+    /// The address `deploy` would give an instance `deployer` deploys with
+    /// `salt`.
+    pub fn deployed_address(e: &Env, deployer: Address, salt: BytesN<32>) -> Address {
+        e.deployer()
+            .with_current_contract(deployment_salt(e, &deployer, &salt))
+            .deployed_address()
+    }
+
+    /// Number of deployments in the registry, and the index of the next one.
+    pub fn deployment_count(e: &Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::DeploymentCount)
+            .unwrap_or(0)
+    }
+
+    /// Up to `limit` deployments from index `start` on, oldest first.
+    /// `limit` is capped at `MAX_PAGE_SIZE`; a short page is the last one.
+    /// Listed entries and the factory live on for `DEPLOYMENT_EXTEND_AMOUNT`
+    /// ledgers.
+    pub fn deployments(e: &Env, start: u32, limit: u32) -> Vec<Deployment> {
+        let end = Self::deployment_count(e).min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        let mut page = Vec::new(e);
+        for index in start..end {
+            let key = DataKey::Deployment(index);
+            let deployment = e
+                .storage()
+                .persistent()
+                .get(&key)
+                .expect("deployment entry is missing");
+            e.storage().persistent().extend_ttl(
+                &key,
+                DEPLOYMENT_TTL_THRESHOLD,
+                DEPLOYMENT_EXTEND_AMOUNT,
+            );
+            page.push_back(deployment);
+        }
+        e.storage()
+            .instance()
+            .extend_ttl(DEPLOYMENT_TTL_THRESHOLD, DEPLOYMENT_EXTEND_AMOUNT);
+        page
+    }
+}
+
+/// The salt `deploy_v2` gets: sha256 of the deployer's XDR and `salt`, so
+/// one deployer cannot deploy at an address another one predicted.
+fn deployment_salt(e: &Env, deployer: &Address, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage: Bytes = deployer.clone().to_xdr(e);
+    preimage.append(&salt.clone().into());
+    e.crypto().sha256(&preimage).into()
+}
+
+/// `transfer_ownership`, `accept_ownership`, `renounce_ownership` and
+/// `get_owner`, as the library implements them.
+#[default_impl]
+#[contractimpl]
+impl Ownable for PlaygroundFactory {}
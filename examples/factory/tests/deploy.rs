@@ -0,0 +1,266 @@
+//! Deploys playgrounds from the built wasm through the factory, and checks
+//! that the indexer would attribute each instance's events to it.
+
+use rbac_playground::RbacPlaygroundClient;
+use rbac_playground_factory::{
+    DataKey, Deployment, PlaygroundDeployed, PlaygroundFactory, PlaygroundFactoryClient,
+    DEPLOYMENT_EXTEND_AMOUNT,
+};
+use rbac_state_reconstructor::{ContractType, ReplayOptions, State};
+use rbac_test_support::{sandbox, strkey, EventLog};
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events as _, Ledger as _,
+    },
+    vec, Address, Bytes, BytesN, Env, Event as _, Symbol, Vec,
+};
+
+fn wasm() -> &'static [u8] {
+    rbac_test_support::wasm("rbac-playground")
+}
+
+struct Setup {
+    env: Env,
+    factory: PlaygroundFactoryClient<'static>,
+    wasm_hash: BytesN<32>,
+}
+
+/// A factory holding the uploaded playground wasm, with every auth mocked.
+fn setup() -> Setup {
+    let env = sandbox();
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(Bytes::from_slice(&env, wasm()));
+    let owner = Address::generate(&env);
+    let factory_id = env.register(PlaygroundFactory, (&owner, &wasm_hash));
+    let factory = PlaygroundFactoryClient::new(&env, &factory_id);
+    Setup {
+        env,
+        factory,
+        wasm_hash,
+    }
+}
+
+fn salt(env: &Env, n: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[n; 32])
+}
+
+impl Setup {
+    /// Deploys an instance with salt `n`, a fresh admin and owner and
+    /// `initial_roles`.
+    fn deploy(&self, deployer: &Address, n: u8, initial_roles: &[(&str, &Address)]) -> Address {
+        let mut roles = Vec::new(&self.env);
+        for (role, account) in initial_roles {
+            roles.push_back((Symbol::new(&self.env, role), (*account).clone()));
+        }
+        self.factory.deploy(
+            deployer,
+            &salt(&self.env, n),
+            &Address::generate(&self.env),
+            &Address::generate(&self.env),
+            &roles,
+            &None,
+        )
+    }
+}
+
+#[test]
+fn deploys_at_the_address_the_deployer_and_salt_give() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    let minter = Address::generate(&s.env);
+    let predicted = s.factory.deployed_address(&deployer, &salt(&s.env, 1));
+
+    let contract = s.deploy(&deployer, 1, &[("minter", &minter)]);
+    assert_eq!(contract, predicted);
+    let events = s.env.events().all();
+    let expected = PlaygroundDeployed {
+        contract: contract.clone(),
+        deployer: deployer.clone(),
+        index: 0,
+        salt: salt(&s.env, 1),
+        wasm_hash: s.wasm_hash.clone(),
+    };
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &s.env,
+            (
+                s.factory.address.clone(),
+                expected.topics(&s.env),
+                expected.data(&s.env)
+            )
+        ]
+    );
+
+    let deployment = s.factory.deployments(&0, &10).get(0).unwrap();
+    let playground = RbacPlaygroundClient::new(&s.env, &contract);
+    assert_eq!(
+        deployment,
+        Deployment {
+            index: 0,
+            contract: contract.clone(),
+            deployer,
+            salt: salt(&s.env, 1),
+            wasm_hash: s.wasm_hash.clone(),
+            admin: playground.get_admin().unwrap(),
+            owner: playground.get_owner().unwrap(),
+            ledger: 100,
+        }
+    );
+    assert!(playground.list_minters().contains(&minter));
+}
+
+#[test]
+fn deploy_needs_the_deployers_auth() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    s.deploy(&deployer, 1, &[]);
+
+    let auths = s.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, deployer);
+}
+
+#[test]
+fn salts_are_single_use() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    s.deploy(&deployer, 1, &[]);
+
+    let reused = s.factory.try_deploy(
+        &deployer,
+        &salt(&s.env, 1),
+        &Address::generate(&s.env),
+        &Address::generate(&s.env),
+        &Vec::new(&s.env),
+        &None,
+    );
+    assert!(reused.is_err());
+    assert_eq!(s.factory.deployment_count(), 1);
+}
+
+#[test]
+fn deployers_cannot_take_each_others_addresses() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    let other = Address::generate(&s.env);
+    let predicted = s.factory.deployed_address(&deployer, &salt(&s.env, 1));
+
+    // Another deployer with the same salt lands elsewhere.
+    let theirs = s.deploy(&other, 1, &[]);
+    assert_ne!(theirs, predicted);
+    assert_eq!(s.deploy(&deployer, 1, &[]), predicted);
+    assert_eq!(s.factory.deployment_count(), 2);
+}
+
+#[test]
+fn registry_entries_outlive_their_minimum_ttl() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    let contract = s.deploy(&deployer, 1, &[]);
+    let ttls = || {
+        s.env.as_contract(&s.factory.address, || {
+            (
+                s.env
+                    .storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Deployment(0)),
+                s.env.storage().instance().get_ttl(),
+            )
+        })
+    };
+    assert_eq!(ttls(), (DEPLOYMENT_EXTEND_AMOUNT, DEPLOYMENT_EXTEND_AMOUNT));
+
+    // Well past the default persistent TTL, listing the entry extends it and
+    // the factory, so a factory that is only read stays live.
+    s.env
+        .ledger()
+        .with_mut(|ledger| ledger.sequence_number += DEPLOYMENT_EXTEND_AMOUNT - 10);
+    assert_eq!(
+        s.factory.deployments(&0, &1).get(0).unwrap().contract,
+        contract
+    );
+    assert_eq!(ttls(), (DEPLOYMENT_EXTEND_AMOUNT, DEPLOYMENT_EXTEND_AMOUNT));
+}
+
+#[test]
+fn registry_pages_in_deployment_order() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    let contracts: std::vec::Vec<Address> = (1..=5).map(|n| s.deploy(&deployer, n, &[])).collect();
+
+    assert_eq!(s.factory.deployment_count(), 5);
+    let mut listed = std::vec::Vec::new();
+    let mut start = 0;
+    loop {
+        let page = s.factory.deployments(&start, &2);
+        listed.extend(page.iter().map(|deployment| deployment.contract));
+        if page.len() < 2 {
+            break;
+        }
+        start += 2;
+    }
+    assert_eq!(listed, contracts);
+    assert!(s.factory.deployments(&5, &2).is_empty());
+    assert!(s.factory.deployments(&u32::MAX, &u32::MAX).is_empty());
+}
+
+#[test]
+fn instance_events_are_attributed_to_their_contract() {
+    let s = setup();
+    let deployer = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+
+    // The decoder doesn't know the factory's own events.
+    let mut log = EventLog::new().ignoring(&s.factory.address);
+    let first = s.deploy(&deployer, 1, &[("minter", &alice)]);
+    log.record(&s.env);
+    let second = s.deploy(&deployer, 2, &[("burner", &bob)]);
+    log.record(&s.env);
+    let playground = RbacPlaygroundClient::new(&s.env, &first);
+    playground.grant_role(
+        &playground.get_admin().unwrap(),
+        &bob,
+        &Symbol::new(&s.env, "viewer"),
+    );
+    log.record(&s.env);
+
+    let (first, second) = (strkey(&first), strkey(&second));
+    assert!(log
+        .events
+        .iter()
+        .all(|event| event.decoded.contract == first || event.decoded.contract == second));
+    let state = State::replay_with(ReplayOptions::EXTENDED, &log.events);
+    assert_eq!(state.contracts.len(), 2);
+    for contract in [&first, &second] {
+        assert_eq!(
+            state.contracts[contract].contract_type,
+            ContractType::AccessControlOwnable
+        );
+    }
+    let (alice, bob) = (strkey(&alice), strkey(&bob));
+    assert!(state.has_role(&first, "minter", &alice));
+    assert!(state.has_role(&first, "viewer", &bob));
+    assert!(!state.has_role(&first, "burner", &bob));
+    assert!(state.has_role(&second, "burner", &bob));
+    assert!(!state.has_role(&second, "minter", &alice));
+    assert!(!state.has_role(&second, "viewer", &bob));
+    assert_ne!(state.owner(&first), state.owner(&second));
+}
+
+#[test]
+fn only_the_owner_sets_the_wasm_hash() {
+    let s = setup();
+    let new_hash = salt(&s.env, 9);
+    s.factory.set_wasm_hash(&new_hash);
+    let auths = s.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, s.factory.get_owner().unwrap());
+    assert_eq!(s.factory.wasm_hash(), new_hash);
+
+    s.env.set_auths(&[]);
+    assert!(s.factory.try_set_wasm_hash(&s.wasm_hash).is_err());
+}
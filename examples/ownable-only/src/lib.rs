@@ -0,0 +1,57 @@
+#![no_std]
+
+//! A counter that only its owner can change.
+//!
+//! It implements `Ownable` and no `AccessControl`, so it only publishes the
+//! ownership events and the indexer classifies it as `OWNABLE`. The
+//! playground covers `ACCESS_CONTROL_OWNABLE`, and `access-control-only`
+//! covers `ACCESS_CONTROL`.
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env};
+
+use stellar_access::ownable::{self as ownable, Ownable};
+use stellar_macros::{default_impl, only_owner};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Count,
+}
+
+#[contract]
+pub struct OwnableCounter;
+
+#[contractimpl]
+impl OwnableCounter {
+    /// Sets the owner. Emits nothing, like `ownable::set_owner`.
+    pub fn __constructor(e: &Env, owner: Address) {
+        ownable::set_owner(e, &owner);
+    }
+
+    pub fn count(e: &Env) -> u32 {
+        e.storage().instance().get(&DataKey::Count).unwrap_or(0)
+    }
+
+    /// Add one to the count and return it (owner only).
+    #[only_owner]
+    pub fn increment(e: &Env) -> u32 {
+        let count = Self::count(e) + 1;
+        e.storage().instance().set(&DataKey::Count, &count);
+        count
+    }
+
+    /// Set the count back to zero (owner only).
+    #[only_owner]
+    pub fn reset(e: &Env) {
+        e.storage().instance().remove(&DataKey::Count);
+    }
+}
+
+/// `transfer_ownership`, `accept_ownership`, `renounce_ownership` and
+/// `get_owner`, as the library implements them.
+#[default_impl]
+#[contractimpl]
+impl Ownable for OwnableCounter {}
+
+#[cfg(test)]
+mod test;
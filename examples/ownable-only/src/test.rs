@@ -0,0 +1,137 @@
+#![cfg(test)]
+
+extern crate std;
+
+use rbac_test_support::{assert_authorized_by, assert_last_events};
+use soroban_sdk::{
+    testutils::{Address as _, Events as _},
+    Address, Env, IntoVal,
+};
+
+use stellar_access::ownable::{OwnershipRenounced, OwnershipTransfer, OwnershipTransferCompleted};
+
+use crate::{OwnableCounter, OwnableCounterClient};
+
+struct Setup<'a> {
+    env: Env,
+    contract_id: Address,
+    owner: Address,
+    client: OwnableCounterClient<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let contract_id = env.register(OwnableCounter, (&owner,));
+    let client = OwnableCounterClient::new(&env, &contract_id);
+
+    Setup {
+        env,
+        contract_id,
+        owner,
+        client,
+    }
+}
+
+#[test]
+fn constructor_sets_the_owner_without_events() {
+    let s = setup();
+
+    assert_eq!(s.client.get_owner(), Some(s.owner.clone()));
+    assert_eq!(s.client.count(), 0);
+    assert!(s.env.events().all().is_empty());
+}
+
+#[test]
+fn owner_increments_and_resets() {
+    let s = setup();
+
+    assert_eq!(s.client.increment(), 1);
+    assert_authorized_by(
+        &s.env,
+        &s.contract_id,
+        &s.owner,
+        "increment",
+        ().into_val(&s.env),
+    );
+    assert_eq!(s.client.increment(), 2);
+    assert_eq!(s.client.count(), 2);
+
+    s.client.reset();
+    assert_authorized_by(
+        &s.env,
+        &s.contract_id,
+        &s.owner,
+        "reset",
+        ().into_val(&s.env),
+    );
+    assert_eq!(s.client.count(), 0);
+}
+
+#[test]
+fn increment_needs_the_owners_auth() {
+    let s = setup();
+    s.env.set_auths(&[]);
+
+    assert!(s.client.try_increment().is_err());
+    assert!(s.client.try_reset().is_err());
+}
+
+#[test]
+fn new_owner_takes_over_after_accepting() {
+    let s = setup();
+    let new_owner = Address::generate(&s.env);
+    let live_until_ledger = s.env.ledger().sequence() + 100;
+
+    s.client.transfer_ownership(&new_owner, &live_until_ledger);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&OwnershipTransfer {
+            old_owner: s.owner.clone(),
+            new_owner: new_owner.clone(),
+            live_until_ledger,
+        }],
+    );
+    assert_eq!(s.client.get_owner(), Some(s.owner.clone()));
+
+    s.client.accept_ownership();
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&OwnershipTransferCompleted {
+            new_owner: new_owner.clone(),
+        }],
+    );
+    assert_eq!(s.client.get_owner(), Some(new_owner.clone()));
+
+    s.client.increment();
+    assert_authorized_by(
+        &s.env,
+        &s.contract_id,
+        &new_owner,
+        "increment",
+        ().into_val(&s.env),
+    );
+}
+
+#[test]
+fn renounced_counter_is_frozen() {
+    let s = setup();
+    s.client.increment();
+
+    s.client.renounce_ownership();
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&OwnershipRenounced {
+            old_owner: s.owner.clone(),
+        }],
+    );
+    assert_eq!(s.client.get_owner(), None);
+    assert!(s.client.try_increment().is_err());
+    assert!(s.client.try_reset().is_err());
+    assert_eq!(s.client.count(), 1);
+}
@@ -0,0 +1,70 @@
+//! Exports the events of a scripted run as a fixture for contract-type
+//! detection: one JSON `LedgerEvent` per line, as `rbac-state-reconstructor`
+//! reads them. Only ownership events appear, so the contract is `OWNABLE`.
+//!
+//! After an intended change, rewrite `fixtures/events.jsonl` with
+//! `UPDATE_FIXTURES=1 cargo test -p rbac-ownable-only --test fixtures`.
+
+use rbac_ownable_only::{OwnableCounter, OwnableCounterClient};
+use rbac_state_reconstructor::{read_events, ContractType, State};
+use rbac_test_support::{account, assert_fixture_is_current, sandbox, strkey, EventLog};
+use soroban_sdk::{Address, Env};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/events.jsonl");
+
+/// Deploys a counter, counts, and hands it from its first owner to alice,
+/// then offers it to bob.
+fn scripted_run() -> (Env, EventLog, Address) {
+    let env = sandbox();
+    let mut log = EventLog::new();
+    let owner = account(&env);
+    let alice = account(&env);
+    let bob = account(&env);
+    let contract_id = env.register(OwnableCounter, (&owner,));
+    log.record(&env);
+    let client = OwnableCounterClient::new(&env, &contract_id);
+
+    client.increment();
+    log.record(&env);
+    client.transfer_ownership(&alice, &(env.ledger().sequence() + 100));
+    log.record(&env);
+    client.accept_ownership();
+    log.record(&env);
+    client.increment();
+    log.record(&env);
+    client.transfer_ownership(&bob, &(env.ledger().sequence() + 100));
+    log.record(&env);
+    (env, log, contract_id)
+}
+
+#[test]
+fn fixture_is_current() {
+    let (_, log, _) = scripted_run();
+    assert_fixture_is_current(FIXTURE, &log.json_lines());
+}
+
+#[test]
+fn fixture_replays_as_an_ownable_contract() {
+    let (env, _, contract_id) = scripted_run();
+    let events = read_events(std::io::BufReader::new(
+        std::fs::File::open(FIXTURE).unwrap(),
+    ))
+    .unwrap();
+    let state = State::replay(&events);
+    let contract = strkey(&contract_id);
+
+    assert_eq!(state.contracts.len(), 1);
+    assert_eq!(
+        state.contracts[&contract].contract_type,
+        ContractType::Ownable
+    );
+    let client = OwnableCounterClient::new(&env, &contract_id);
+    assert_eq!(
+        state.owner(&contract).map(str::to_string),
+        client.get_owner().map(|owner| strkey(&owner))
+    );
+    assert!(state
+        .pending_ownership_transfer(&contract, env.ledger().sequence())
+        .is_some());
+    assert!(state.role_memberships.is_empty());
+}
@@ -0,0 +1,143 @@
+#![no_std]
+
+//! The RBAC playground as deployed before `migrate` existed (storage
+//! version 1), cut down to what the upgrade tests exercise.
+//!
+//! Its storage keys and `PendingTransfer` layout match the original contract,
+//! and it writes no `StorageVersion`. `upgrade` is the one addition: it lets
+//! the tests replace this build with the current wasm and then call the new
+//! build's `migrate`. The original contract has no `upgrade` entrypoint, so
+//! its deployments cannot take this path; they have to be redeployed. The
+//! tests exercise the version-1 migration code, not an upgrade of a real
+//! deployment.
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Symbol,
+};
+
+use stellar_access::access_control::{self as access_control, AccessControl};
+use stellar_access::ownable::{self as ownable, Ownable};
+use stellar_contract_utils::upgradeable;
+use stellar_macros::{default_impl, only_owner, only_role};
+
+const OPERATOR_ROLE: Symbol = symbol_short!("operator");
+const MINTER_ROLE: Symbol = symbol_short!("minter");
+
+/// Storage keys of version 1.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Balance(Address),
+    Paused,
+    TotalSupply,
+    PendingTransfer(u64),
+    PendingTransferCounter,
+    TransferApproval(u64, Address),
+}
+
+/// A pending transfer as stored by version 1: no `proposer` or `cancelled`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingTransfer {
+    pub id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub approvals: u32,
+    pub required_approvals: u32,
+    pub executed: bool,
+}
+
+#[contract]
+pub struct RbacPlaygroundV1;
+
+#[contractimpl]
+impl RbacPlaygroundV1 {
+    /// Sets the admin and owner and gives the admin the minter and operator
+    /// roles, as version 1 did.
+    pub fn __constructor(e: &Env, admin: Address, owner: Address) {
+        access_control::set_admin(e, &admin);
+        ownable::set_owner(e, &owner);
+
+        e.storage().instance().set(&DataKey::Paused, &false);
+        e.storage().instance().set(&DataKey::TotalSupply, &0i128);
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingTransferCounter, &0u64);
+
+        access_control::grant_role_no_auth(e, &admin, &admin, &MINTER_ROLE);
+        access_control::grant_role_no_auth(e, &admin, &admin, &OPERATOR_ROLE);
+    }
+
+    pub fn get_balance(e: &Env, account: Address) -> i128 {
+        e.storage()
+            .instance()
+            .get(&DataKey::Balance(account))
+            .unwrap_or(0)
+    }
+
+    #[only_role(caller, "minter")]
+    pub fn mint(e: &Env, to: Address, amount: i128, caller: Address) {
+        let key = DataKey::Balance(to);
+        let balance: i128 = e.storage().instance().get(&key).unwrap_or(0);
+        e.storage().instance().set(&key, &(balance + amount));
+
+        let total: i128 = e
+            .storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0);
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total + amount));
+    }
+
+    #[only_role(proposer, "transfer")]
+    pub fn propose_transfer(
+        e: &Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+        required_approvals: u32,
+        proposer: Address,
+    ) -> u64 {
+        let id: u64 = e
+            .storage()
+            .instance()
+            .get(&DataKey::PendingTransferCounter)
+            .unwrap_or(0);
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingTransferCounter, &(id + 1));
+
+        let pending = PendingTransfer {
+            id,
+            from,
+            to,
+            amount,
+            approvals: 0,
+            required_approvals,
+            executed: false,
+        };
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingTransfer(id), &pending);
+
+        id
+    }
+
+    /// Replaces this contract's code and allows the new code's `migrate`.
+    #[only_owner]
+    pub fn upgrade(e: &Env, new_wasm_hash: BytesN<32>) {
+        upgradeable::enable_migration(e);
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+}
+
+#[default_impl]
+#[contractimpl]
+impl AccessControl for RbacPlaygroundV1 {}
+
+#[default_impl]
+#[contractimpl]
+impl Ownable for RbacPlaygroundV1 {}
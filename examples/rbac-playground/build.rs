@@ -0,0 +1,39 @@
+//! Exposes `[package.metadata.playground] capabilities` from Cargo.toml to the
+//! contract as the comma-separated `PLAYGROUND_CAPABILITIES` env var.
+//!
+//! Fails the build when the table or key is missing or malformed, rather than
+//! building a contract that reports no capabilities.
+
+use std::fs;
+
+use toml::Table;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.toml");
+
+    let manifest: Table = fs::read_to_string("Cargo.toml")
+        .expect("read Cargo.toml")
+        .parse()
+        .expect("parse Cargo.toml");
+    let playground = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("playground"))
+        .expect("Cargo.toml has no [package.metadata.playground] table");
+    let capabilities: Vec<&str> = playground
+        .get("capabilities")
+        .expect("[package.metadata.playground] has no `capabilities` key")
+        .as_array()
+        .expect("`capabilities` must be an array of strings")
+        .iter()
+        .map(|c| {
+            c.as_str()
+                .expect("`capabilities` must be an array of strings")
+        })
+        .collect();
+
+    println!(
+        "cargo:rustc-env=PLAYGROUND_CAPABILITIES={}",
+        capabilities.join(",")
+    );
+}
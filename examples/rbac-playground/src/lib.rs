@@ -1,13 +1,15 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractevent, contractimpl, contracttype, symbol_short, Address, Env, Symbol,
-    Vec,
+    contract, contractevent, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    BytesN, Env, IntoVal, Symbol, Val, Vec,
 };
 
-use stellar_access::access_control::{self as access_control, AccessControl};
+use stellar_access::access_control::{self as access_control, AccessControl, AccessControlError};
 use stellar_access::ownable::{self as ownable, Ownable};
-use stellar_macros::{default_impl, only_admin, only_owner, only_role};
+use stellar_contract_utils::pausable::{self, PausableError};
+use stellar_contract_utils::upgradeable;
+use stellar_macros::{default_impl, only_admin, only_owner, when_paused};
 
 // ============================================================================
 // Role Definitions
@@ -36,18 +38,299 @@ const TRANSFER_ROLE: Symbol = symbol_short!("transfer");
 /// Approver: Can approve or reject pending operations
 const APPROVER_ROLE: Symbol = symbol_short!("approver");
 
+/// Every role defined by this contract.
+const ROLES: [Symbol; 7] = [
+    OPERATOR_ROLE,
+    MINTER_ROLE,
+    BURNER_ROLE,
+    PAUSER_ROLE,
+    VIEWER_ROLE,
+    TRANSFER_ROLE,
+    APPROVER_ROLE,
+];
+
+/// Role hierarchy as `(role, admin role)` pairs. Holders of the admin role can
+/// grant and revoke the role without being the top-level admin.
+/// - OPERATOR administers MINTER and BURNER
+/// - APPROVER administers TRANSFER
+const ROLE_HIERARCHY: [(Symbol, Symbol); 3] = [
+    (MINTER_ROLE, OPERATOR_ROLE),
+    (BURNER_ROLE, OPERATOR_ROLE),
+    (TRANSFER_ROLE, APPROVER_ROLE),
+];
+
+/// Version of the storage layout written by this build, see `migrate`.
+/// - 1: original layout; `PendingTransfer` without `proposer` and `cancelled`
+/// - 2: current layout
+pub const STORAGE_VERSION: u32 = 2;
+
+/// Most pending transfers one `migrate` call converts.
+pub const MIGRATION_BATCH_SIZE: u64 = 50;
+
+/// Most entries accepted by `batch_mint`, `batch_burn`, `batch_grant_roles`
+/// and `batch_revoke_roles`. Each granted role writes two ledger entries, so a
+/// grant batch of 20 stays within Mainnet's 50 write entries per transaction;
+/// token batches of 20 write one balance entry per account and emit about
+/// 4 KiB of the 16 KiB event limit. See `tests/budget.rs` for the
+/// measurements.
+pub const MAX_BATCH_SIZE: u32 = 20;
+
+/// Most open transfers an account can have proposed or approved at once, and
+/// most approvals a transfer can require (a transfer executes on its last
+/// one). `revoke_all_roles` cancels every open transfer of the account in the
+/// same call, so these bound its cost: at most 46 of Mainnet's 50 write
+/// entries for an account holding every role. See `tests/budget.rs`.
+pub const MAX_OPEN_TRANSFERS: u32 = 5;
+pub const MAX_REQUIRED_APPROVALS: u32 = 4;
+
+/// Most members a role can have, whatever its cap (see `set_role_cap`).
+/// `list_*` reads one ledger entry per member plus three, so a role of 97 is
+/// the most Mainnet's 100 read entries per transaction can list; 90 leaves
+/// room. See `tests/budget.rs`.
+pub const MAX_ROLE_MEMBERS: u32 = 90;
+
+const DAY_IN_LEDGERS: u32 = 17280;
+
+/// Ledgers the `OpenTransfers` and `TransferApprovers` entries live for after
+/// each write.
+pub const INDEX_EXTEND_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Index entries are extended once they have fewer ledgers than this left.
+pub const INDEX_TTL_THRESHOLD: u32 = INDEX_EXTEND_AMOUNT - DAY_IN_LEDGERS;
+
+/// Ledgers a `Balance` entry lives for after each write.
+pub const BALANCE_EXTEND_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Balance entries are extended once they have fewer ledgers than this left.
+pub const BALANCE_TTL_THRESHOLD: u32 = BALANCE_EXTEND_AMOUNT - DAY_IN_LEDGERS;
+
+/// Crate version from Cargo.toml, reported by `version()`.
+const VERSION: (u32, u32, u32) = (
+    parse_u32(env!("CARGO_PKG_VERSION_MAJOR")),
+    parse_u32(env!("CARGO_PKG_VERSION_MINOR")),
+    parse_u32(env!("CARGO_PKG_VERSION_PATCH")),
+);
+
+/// Comma-separated `[package.metadata.playground] capabilities` from
+/// Cargo.toml (see build.rs), reported by `capabilities()`.
+const CAPABILITIES: &str = env!("PLAYGROUND_CAPABILITIES");
+
+const fn parse_u32(digits: &str) -> u32 {
+    let bytes = digits.as_bytes();
+    let mut value = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        value = value * 10 + (bytes[i] - b'0') as u32;
+        i += 1;
+    }
+    value
+}
+
+// ============================================================================
+// Permission Matrix
+// ============================================================================
+// Reported by `required_role` and `can`, and enforced from the same entries.
+// `#[only_role]` takes the role as a string literal and knows nothing of
+// delegations, and `#[when_not_paused]` / `#[when_paused]` read the raw pause
+// flag without its `until_ledger`, so role-gated functions pass their entry to
+// `require_permission` and owner/admin-only ones with a pause gate to
+// `PauseGate::enforce`. Admin and owner checks are the `#[only_admin]` /
+// `#[only_owner]` attributes (or the library's own). Entries are constants in
+// `permissions`, so a function can only name an entry that exists; the
+// `permission_matrix_matches_behaviour` test calls every entry to confirm them.
+
+/// Pause state a function requires, enforced by `PauseGate::enforce`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PauseGate {
+    Any,
+    NotPaused,
+    Paused,
+}
+
+impl PauseGate {
+    fn allows(self, paused: bool) -> bool {
+        match self {
+            PauseGate::Any => true,
+            PauseGate::NotPaused => !paused,
+            PauseGate::Paused => paused,
+        }
+    }
+
+    fn enforce(self, e: &Env) {
+        match self {
+            PauseGate::Any => {}
+            PauseGate::NotPaused => RbacPlayground::when_not_paused(e),
+            PauseGate::Paused => RbacPlayground::when_paused(e),
+        }
+    }
+}
+
+/// A function gated by a role (or a delegation of it).
+struct RoleGated {
+    function: &'static str,
+    role: Symbol,
+    pause: PauseGate,
+}
+
+/// A function restricted to the AccessControl admin or the Ownable owner.
+struct AccountGated {
+    function: &'static str,
+    pause: PauseGate,
+}
+
+/// Permission matrix entries, named after their function.
+mod permissions {
+    use super::*;
+
+    pub(crate) const MINT: RoleGated = RoleGated {
+        function: "mint",
+        role: MINTER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const BURN: RoleGated = RoleGated {
+        function: "burn",
+        role: BURNER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const PAUSE: RoleGated = RoleGated {
+        function: "pause",
+        role: PAUSER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const UNPAUSE: RoleGated = RoleGated {
+        function: "unpause",
+        role: PAUSER_ROLE,
+        pause: PauseGate::Paused,
+    };
+    pub(crate) const VIEW_SENSITIVE_STATS: RoleGated = RoleGated {
+        function: "view_sensitive_stats",
+        role: VIEWER_ROLE,
+        pause: PauseGate::Any,
+    };
+    pub(crate) const VIEW_PENDING_TRANSFER: RoleGated = RoleGated {
+        function: "view_pending_transfer",
+        role: VIEWER_ROLE,
+        pause: PauseGate::Any,
+    };
+    pub(crate) const EXECUTE_TRANSFER: RoleGated = RoleGated {
+        function: "execute_transfer",
+        role: TRANSFER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const BATCH_MINT: RoleGated = RoleGated {
+        function: "batch_mint",
+        role: OPERATOR_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const BATCH_BURN: RoleGated = RoleGated {
+        function: "batch_burn",
+        role: OPERATOR_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const PROPOSE_TRANSFER: RoleGated = RoleGated {
+        function: "propose_transfer",
+        role: TRANSFER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+    pub(crate) const APPROVE_TRANSFER: RoleGated = RoleGated {
+        function: "approve_transfer",
+        role: APPROVER_ROLE,
+        pause: PauseGate::NotPaused,
+    };
+
+    pub(crate) const EMERGENCY_UNPAUSE: AccountGated = AccountGated {
+        function: "emergency_unpause",
+        pause: PauseGate::Paused,
+    };
+
+    /// Admin- or owner-only function without a pause gate.
+    const fn any_time(function: &'static str) -> AccountGated {
+        AccountGated {
+            function,
+            pause: PauseGate::Any,
+        }
+    }
+
+    /// Functions gated by a role (or a delegation of it).
+    pub(crate) const ROLE_GATED: [RoleGated; 11] = [
+        MINT,
+        BURN,
+        PAUSE,
+        UNPAUSE,
+        VIEW_SENSITIVE_STATS,
+        VIEW_PENDING_TRANSFER,
+        EXECUTE_TRANSFER,
+        BATCH_MINT,
+        BATCH_BURN,
+        PROPOSE_TRANSFER,
+        APPROVE_TRANSFER,
+    ];
+
+    /// Functions restricted to the AccessControl admin.
+    pub(crate) const ADMIN_GATED: [AccountGated; 7] = [
+        any_time("set_role_cap"),
+        any_time("revoke_all_roles"),
+        any_time("admin_ping"),
+        any_time("configure_hierarchy"),
+        any_time("transfer_admin_role"),
+        any_time("set_role_admin"),
+        any_time("renounce_admin"),
+    ];
+
+    /// Functions restricted to the Ownable owner.
+    pub(crate) const OWNER_GATED: [AccountGated; 8] = [
+        any_time("owner_revoke_all_roles"),
+        any_time("owner_ping"),
+        any_time("emergency_pause"),
+        EMERGENCY_UNPAUSE,
+        any_time("upgrade"),
+        any_time("migrate"),
+        any_time("transfer_ownership"),
+        any_time("renounce_ownership"),
+    ];
+}
+
+use permissions::{ADMIN_GATED, OWNER_GATED, ROLE_GATED};
+
 // ============================================================================
 // Storage Keys
 // ============================================================================
 
+/// Contract storage keys.
+///
+/// The pause flag is owned by the `pausable` module. The former
+/// `DataKey::Paused` variant and `PausableStorageKey::Paused` both encode to the
+/// ledger key `["Paused"]`, so a flag stored before the switch carries over
+/// without a data migration.
 #[contracttype]
 pub enum DataKey {
+    /// account -> balance (persistent storage). Version 1 deployments kept
+    /// balances in instance storage; those are read until the account's
+    /// balance next changes, which moves it.
     Balance(Address),
-    Paused,
     TotalSupply,
     PendingTransfer(u64),
     PendingTransferCounter,
     TransferApproval(u64, Address),
+    PauseInfo,
+    /// Maximum number of members of a role, see `set_role_cap`.
+    RoleCap(Symbol),
+    /// Pending two-step grant of a role to an account (temporary storage).
+    RoleOffer(Address, Symbol),
+    /// (delegator, role) -> Delegation (temporary storage).
+    Delegation(Address, Symbol),
+    /// (delegate, role) -> delegator (temporary storage).
+    DelegatedBy(Address, Symbol),
+    /// Storage layout version; absent before version 2.
+    StorageVersion,
+    /// (next id, end id) of the pending transfers `migrate` has yet to
+    /// convert; present only while a migration is under way.
+    MigrationCursor,
+    /// account -> ids of the open transfers it proposed or approved, in
+    /// ascending order (persistent storage).
+    OpenTransfers(Address),
+    /// id -> accounts that approved the open transfer (persistent storage).
+    TransferApprovers(u64),
 }
 
 #[contracttype]
@@ -60,18 +343,82 @@ pub struct PendingTransfer {
     pub approvals: u32,
     pub required_approvals: u32,
     pub executed: bool,
+    pub proposer: Address,
+    /// Set when the proposer or an approver is revoked via `revoke_all_roles`.
+    pub cancelled: bool,
+}
+
+/// `PendingTransfer` as stored by storage version 1, read by `migrate`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingTransferV1 {
+    pub id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub approvals: u32,
+    pub required_approvals: u32,
+    pub executed: bool,
+}
+
+/// Details of the current pause, recorded by `pause` and `emergency_pause`
+/// next to the `pausable` module's flag.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PauseInfo {
+    pub paused_by: Address,
+    pub paused_at: u32,
+    pub reason: Symbol,
+    /// Last ledger (inclusive) at which the pause is in effect; after it the
+    /// contract behaves as unpaused. `None` means the pause lasts until
+    /// explicitly lifted.
+    pub until_ledger: Option<u32>,
+}
+
+/// A pending role grant created by `offer_role`, awaiting `accept_role`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleOffer {
+    pub offered_by: Address,
+    pub live_until_ledger: u32,
+}
+
+/// A temporary hand-over of a role holder's capability, see `delegate_role`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Delegation {
+    pub delegate: Address,
+    pub until_ledger: u32,
 }
 
 // ============================================================================
 // Events
 // ============================================================================
+// Accounts and transfer ids are `#[topic]` fields so RPC `getEvents` can
+// filter on them (at most 4 topics, including the event name). Role events
+// follow the library's `RoleGranted` layout: role and account as topics,
+// caller in the data. The `event_topic_layout` test pins every layout.
+
+/// Event emitted at the end of `__constructor`, after the library's
+/// `RoleGranted` events, tying the deployment's settings together.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct Initialized {
+    pub admin: Address,
+    pub owner: Address,
+    pub initial_roles: Vec<(Symbol, Address)>,
+    pub version: (u32, u32, u32),
+    pub capabilities: Vec<Symbol>,
+}
 
 /// Event emitted when tokens are minted.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct Minted {
+    #[topic]
     pub to: Address,
     pub amount: i128,
+    #[topic]
     pub caller: Address,
 }
 
@@ -79,22 +426,31 @@ pub struct Minted {
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct Burned {
+    #[topic]
     pub from: Address,
     pub amount: i128,
+    #[topic]
     pub caller: Address,
 }
 
-/// Event emitted when contract is paused.
+/// Event emitted right after the standard `paused` event, recording who
+/// paused the contract, why and until when. `emergency_pause` emits it alone
+/// when it changes a pause already in effect.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
-pub struct Paused {
+pub struct PauseDetails {
+    #[topic]
     pub caller: Address,
+    pub reason: Symbol,
+    pub until_ledger: Option<u32>,
 }
 
-/// Event emitted when contract is unpaused.
+/// Event emitted when the owner lifts a pause via `emergency_unpause`,
+/// right after the standard `unpaused` event.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
-pub struct Unpaused {
+pub struct EmergencyUnpaused {
+    #[topic]
     pub caller: Address,
 }
 
@@ -102,9 +458,12 @@ pub struct Unpaused {
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct TransferExecuted {
+    #[topic]
     pub from: Address,
+    #[topic]
     pub to: Address,
     pub amount: i128,
+    #[topic]
     pub caller: Address,
 }
 
@@ -112,8 +471,10 @@ pub struct TransferExecuted {
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct BatchOperation {
+    #[topic]
     pub operation: Symbol,
     pub count: u32,
+    #[topic]
     pub caller: Address,
 }
 
@@ -121,38 +482,130 @@ pub struct BatchOperation {
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct TransferProposed {
+    #[topic]
     pub id: u64,
+    #[topic]
     pub from: Address,
+    #[topic]
     pub to: Address,
     pub amount: i128,
     pub proposer: Address,
 }
 
-/// Event emitted when a transfer is approved by an approver.
+/// Event emitted when a transfer is approved by an approver. `approver` is
+/// the role holder the approval counts for; `delegate` is set when its
+/// delegate made it.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct TransferApproved {
+    #[topic]
     pub id: u64,
+    #[topic]
     pub approver: Address,
     pub current_approvals: u32,
     pub required_approvals: u32,
+    pub delegate: Option<Address>,
 }
 
 /// Event emitted when a transfer is finalized after enough approvals.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct TransferFinalized {
+    #[topic]
     pub id: u64,
+    #[topic]
     pub from: Address,
+    #[topic]
     pub to: Address,
     pub amount: i128,
 }
 
+/// Event emitted when the member cap of a role is set or removed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct RoleCapSet {
+    #[topic]
+    pub role: Symbol,
+    pub max_members: Option<u32>,
+    pub caller: Address,
+}
+
+/// Event emitted when a role is offered to an account. `RoleGranted` follows
+/// only once the account accepts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct RoleOffered {
+    #[topic]
+    pub role: Symbol,
+    #[topic]
+    pub account: Address,
+    pub caller: Address,
+    pub live_until_ledger: u32,
+}
+
+/// Event emitted when a pending role offer is withdrawn.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct RoleOfferCancelled {
+    #[topic]
+    pub role: Symbol,
+    #[topic]
+    pub account: Address,
+    pub caller: Address,
+}
+
+/// Event emitted when a role holder lets `delegate` act for them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct DelegationCreated {
+    #[topic]
+    pub delegator: Address,
+    #[topic]
+    pub delegate: Address,
+    #[topic]
+    pub role: Symbol,
+    pub until_ledger: u32,
+}
+
+/// Event emitted when a delegation is withdrawn before it expires.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct DelegationRevoked {
+    #[topic]
+    pub delegator: Address,
+    #[topic]
+    pub delegate: Address,
+    #[topic]
+    pub role: Symbol,
+}
+
+/// Event emitted by `revoke_all_roles` after an account has been stripped of
+/// its roles. A `RoleRevoked` event precedes it for each role.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct AccountCompromised {
+    #[topic]
+    pub account: Address,
+    pub caller: Address,
+    pub revoked_roles: Vec<Symbol>,
+    pub cancelled_transfers: Vec<u64>,
+}
+
+/// Event emitted when the owner replaces the contract code via `upgrade`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contractevent]
+pub struct Upgraded {
+    pub new_wasm_hash: BytesN<32>,
+    #[topic]
+    pub caller: Address,
+}
+
 /// Event emitted when sensitive data is viewed.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contractevent]
 pub struct SensitiveDataAccessed {
     pub data_type: Symbol,
+    #[topic]
     pub viewer: Address,
 }
 
@@ -183,15 +636,27 @@ impl RbacPlayground {
     // Initialization
     // ========================================================================
 
-    /// Initialize the contract with admin and owner.
+    /// Initialize the contract with admin, owner and initial role assignments.
     ///
     /// - Sets the top-level AccessControl admin.
     /// - Sets the Ownable owner.
     /// - Grants MINTER_ROLE and OPERATOR_ROLE to the admin.
-    /// - Initializes contract state.
+    /// - Grants each `(role, account)` in `initial_roles` (emits `RoleGranted`
+    ///   with the admin as caller).
+    /// - Sets up the `ROLE_HIERARCHY` (emits `RoleAdminChanged`).
+    /// - Initializes contract state and mints `initial_balances`, if any
+    ///   (emits `Minted` with the admin as caller).
+    /// - Emits `Initialized` with the admin, owner, `initial_roles`,
+    ///   `version()` and `capabilities()`.
     ///
     /// Call this once at deployment time.
-    pub fn __constructor(e: &Env, admin: Address, owner: Address) {
+    pub fn __constructor(
+        e: &Env,
+        admin: Address,
+        owner: Address,
+        initial_roles: Vec<(Symbol, Address)>,
+        initial_balances: Option<Vec<(Address, i128)>>,
+    ) {
         // AccessControl admin (no auth in constructor)
         access_control::set_admin(e, &admin);
 
@@ -199,13 +664,45 @@ impl RbacPlayground {
         ownable::set_owner(e, &owner);
 
         // Initialize state
-        e.storage().instance().set(&DataKey::Paused, &false);
         e.storage().instance().set(&DataKey::TotalSupply, &0i128);
-        e.storage().instance().set(&DataKey::PendingTransferCounter, &0u64);
+        e.storage()
+            .instance()
+            .set(&DataKey::PendingTransferCounter, &0u64);
+        e.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &STORAGE_VERSION);
 
         // Give the admin initial roles (bypasses auth, safe during init).
         access_control::grant_role_no_auth(e, &admin, &admin, &MINTER_ROLE);
         access_control::grant_role_no_auth(e, &admin, &admin, &OPERATOR_ROLE);
+
+        for (role, account) in initial_roles.iter() {
+            access_control::grant_role_no_auth(e, &admin, &account, &role);
+        }
+
+        Self::set_role_hierarchy(e);
+
+        for (to, amount) in initial_balances.unwrap_or_else(|| Vec::new(e)).iter() {
+            Self::ensure_valid_amount(amount);
+            Self::credit(e, &to, amount);
+            Self::adjust_total_supply(e, amount);
+
+            Minted {
+                to,
+                amount,
+                caller: admin.clone(),
+            }
+            .publish(e);
+        }
+
+        Initialized {
+            admin,
+            owner,
+            initial_roles,
+            version: VERSION,
+            capabilities: Self::capabilities(e),
+        }
+        .publish(e);
     }
 
     // ========================================================================
@@ -214,13 +711,24 @@ impl RbacPlayground {
 
     /// Get the balance of an account (public).
     pub fn get_balance(e: &Env, account: Address) -> i128 {
-        let key = DataKey::Balance(account);
-        e.storage().instance().get(&key).unwrap_or(0)
+        Self::balance(e, &account)
     }
 
     /// Check if the contract is paused (public).
+    ///
+    /// A pause whose `until_ledger` has passed is reported as lifted.
     pub fn is_paused(e: &Env) -> bool {
-        e.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+        Self::pause_in_effect(e)
+    }
+
+    /// Get who paused the contract, when, why and until when (public).
+    ///
+    /// Returns `None` when the contract is not paused or the pause has expired.
+    pub fn get_pause_info(e: &Env) -> Option<PauseInfo> {
+        if !Self::pause_in_effect(e) {
+            return None;
+        }
+        e.storage().instance().get(&DataKey::PauseInfo)
     }
 
     /// Get total supply (public).
@@ -282,26 +790,79 @@ impl RbacPlayground {
         result
     }
 
+    // ========================================================================
+    // Permission Introspection (Public)
+    // ========================================================================
+
+    /// The role a function requires, or `None` if it is not role-gated
+    /// (public, owner/admin-only, or checked against its arguments such as
+    /// `grant_role`).
+    pub fn required_role(e: &Env, function: Symbol) -> Option<Symbol> {
+        ROLE_GATED
+            .into_iter()
+            .find(|entry| Symbol::new(e, entry.function) == function)
+            .map(|entry| entry.role)
+    }
+
+    /// Whether `account` may currently call `function`.
+    ///
+    /// Considers the role or an active delegation of it, the owner and admin
+    /// for owner/admin-only functions, and the current pause state. Returns
+    /// `false` for functions without a fixed permission (see `required_role`).
+    pub fn can(e: &Env, account: Address, function: Symbol) -> bool {
+        let paused = Self::pause_in_effect(e);
+
+        for entry in ROLE_GATED {
+            if Symbol::new(e, entry.function) == function {
+                return entry.pause.allows(paused)
+                    && (access_control::has_role(e, &account, &entry.role).is_some()
+                        || Self::active_delegator(e, &account, &entry.role).is_some());
+            }
+        }
+        for entry in ADMIN_GATED {
+            if Symbol::new(e, entry.function) == function {
+                return entry.pause.allows(paused) && access_control::get_admin(e) == Some(account);
+            }
+        }
+        for entry in OWNER_GATED {
+            if Symbol::new(e, entry.function) == function {
+                return entry.pause.allows(paused) && ownable::get_owner(e) == Some(account);
+            }
+        }
+
+        false
+    }
+
+    // ========================================================================
+    // Build Info (Public)
+    // ========================================================================
+
+    /// The `(major, minor, patch)` version of this build, from Cargo.toml.
+    pub fn version(_e: &Env) -> (u32, u32, u32) {
+        VERSION
+    }
+
+    /// Features supported by this build, from Cargo.toml metadata
+    /// (e.g. `multisig`, `pause`, `enumeration`).
+    pub fn capabilities(e: &Env) -> Vec<Symbol> {
+        let mut capabilities = Vec::new(e);
+        for capability in CAPABILITIES.split(',').filter(|c| !c.is_empty()) {
+            capabilities.push_back(Symbol::new(e, capability));
+        }
+        capabilities
+    }
+
     // ========================================================================
     // MINTER Role Functions
     // ========================================================================
 
-    /// Mint tokens to `to` (requires MINTER role).
-    #[only_role(caller, "minter")]
+    /// Mint tokens to `to` (requires MINTER role or delegation).
     pub fn mint(e: &Env, to: Address, amount: i128, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        Self::require_permission(e, &permissions::MINT, &caller);
+        Self::ensure_valid_amount(amount);
 
-        // Update balance
-        let key = DataKey::Balance(to.clone());
-        let mut balance: i128 = e.storage().instance().get(&key).unwrap_or(0);
-        balance += amount;
-        e.storage().instance().set(&key, &balance);
-
-        // Update total supply
-        let mut total: i128 = e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-        total += amount;
-        e.storage().instance().set(&DataKey::TotalSupply, &total);
+        Self::credit(e, &to, amount);
+        Self::adjust_total_supply(e, amount);
 
         Minted {
             to,
@@ -315,21 +876,14 @@ impl RbacPlayground {
     // BURNER Role Functions
     // ========================================================================
 
-    /// Burn tokens from `from` (requires BURNER role).
-    #[only_role(caller, "burner")]
+    /// Burn tokens from `from` (requires BURNER role or delegation).
+    /// Panics if `from` holds less than `amount`.
     pub fn burn(e: &Env, from: Address, amount: i128, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
-
-        let key = DataKey::Balance(from.clone());
-        let mut balance: i128 = e.storage().instance().get(&key).unwrap_or(0);
-        balance -= amount;
-        e.storage().instance().set(&key, &balance);
+        Self::require_permission(e, &permissions::BURN, &caller);
+        Self::ensure_valid_amount(amount);
 
-        // Update total supply
-        let mut total: i128 = e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-        total -= amount;
-        e.storage().instance().set(&DataKey::TotalSupply, &total);
+        Self::debit(e, &from, amount);
+        Self::adjust_total_supply(e, -amount);
 
         Burned {
             from,
@@ -343,34 +897,47 @@ impl RbacPlayground {
     // PAUSER Role Functions
     // ========================================================================
 
-    /// Pause the contract (requires PAUSER role).
-    #[only_role(caller, "pauser")]
-    pub fn pause(e: &Env, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        e.storage().instance().set(&DataKey::Paused, &true);
-        Paused { caller }.publish(e);
+    /// Pause the contract (requires PAUSER role or delegation).
+    ///
+    /// Emits the standard `paused` event followed by `PauseDetails`.
+    /// `reason` is recorded for `get_pause_info`. When `until_ledger` is set,
+    /// the pause is lifted automatically once the ledger passes it.
+    pub fn pause(e: &Env, caller: Address, reason: Symbol, until_ledger: Option<u32>) {
+        Self::require_permission(e, &permissions::PAUSE, &caller);
+        Self::set_paused(e, caller, reason, until_ledger);
     }
 
-    /// Unpause the contract (requires PAUSER role).
-    #[only_role(caller, "pauser")]
+    /// Unpause the contract (requires PAUSER role or delegation).
     pub fn unpause(e: &Env, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        e.storage().instance().set(&DataKey::Paused, &false);
-        Unpaused { caller }.publish(e);
+        Self::require_permission(e, &permissions::UNPAUSE, &caller);
+        Self::clear_paused(e);
+    }
+
+    /// Clear a pause whose `until_ledger` has passed (public).
+    ///
+    /// Optional: an expired pause no longer blocks anything. This removes it
+    /// from storage and emits the standard `unpaused` event, so indexers see
+    /// the pause end without waiting for the next `pause`.
+    #[when_paused]
+    pub fn lift_expired_pause(e: &Env) {
+        let info: Option<PauseInfo> = e.storage().instance().get(&DataKey::PauseInfo);
+        match info.and_then(|info| info.until_ledger) {
+            Some(until_ledger) if e.ledger().sequence() > until_ledger => Self::clear_paused(e),
+            _ => panic!("Pause has not expired"),
+        }
     }
 
     // ========================================================================
     // VIEWER Role Functions
     // ========================================================================
 
-    /// View sensitive contract statistics (requires VIEWER role).
+    /// View sensitive contract statistics (requires VIEWER role or delegation).
     /// This demonstrates access-controlled view functions.
-    #[only_role(caller, "viewer")]
     pub fn view_sensitive_stats(e: &Env, caller: Address) -> (i128, u64, bool) {
-        // Note: #[only_role] macro handles require_auth()
+        Self::require_permission(e, &permissions::VIEW_SENSITIVE_STATS, &caller);
         let total_supply: i128 = e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
         let pending_count: u64 = e.storage().instance().get(&DataKey::PendingTransferCounter).unwrap_or(0);
-        let is_paused: bool = e.storage().instance().get(&DataKey::Paused).unwrap_or(false);
+        let is_paused = Self::pause_in_effect(e);
 
         SensitiveDataAccessed {
             data_type: symbol_short!("stats"),
@@ -381,10 +948,18 @@ impl RbacPlayground {
         (total_supply, pending_count, is_paused)
     }
 
-    /// View a pending transfer details (requires VIEWER role).
-    #[only_role(caller, "viewer")]
+    /// Ids of the open transfers `account` proposed or approved, ascending
+    /// (public). These are what `revoke_all_roles` cancels.
+    pub fn get_open_transfers(e: &Env, account: Address) -> Vec<u64> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::OpenTransfers(account))
+            .unwrap_or_else(|| Vec::new(e))
+    }
+
+    /// View a pending transfer details (requires VIEWER role or delegation).
     pub fn view_pending_transfer(e: &Env, id: u64, caller: Address) -> PendingTransfer {
-        // Note: #[only_role] macro handles require_auth()
+        Self::require_permission(e, &permissions::VIEW_PENDING_TRANSFER, &caller);
         let key = DataKey::PendingTransfer(id);
         let transfer: PendingTransfer = e.storage().instance().get(&key).unwrap();
 
@@ -401,24 +976,15 @@ impl RbacPlayground {
     // TRANSFER Role Functions
     // ========================================================================
 
-    /// Execute a direct transfer between accounts (requires TRANSFER role).
-    /// This is for escrow or administrative transfers.
-    #[only_role(caller, "transfer")]
+    /// Execute a direct transfer between accounts (requires TRANSFER role or delegation).
+    /// This is for escrow or administrative transfers. Panics if `from` holds
+    /// less than `amount`.
     pub fn execute_transfer(e: &Env, from: Address, to: Address, amount: i128, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        Self::require_permission(e, &permissions::EXECUTE_TRANSFER, &caller);
+        Self::ensure_valid_amount(amount);
 
-        // Debit from
-        let from_key = DataKey::Balance(from.clone());
-        let mut from_balance: i128 = e.storage().instance().get(&from_key).unwrap_or(0);
-        from_balance -= amount;
-        e.storage().instance().set(&from_key, &from_balance);
-
-        // Credit to
-        let to_key = DataKey::Balance(to.clone());
-        let mut to_balance: i128 = e.storage().instance().get(&to_key).unwrap_or(0);
-        to_balance += amount;
-        e.storage().instance().set(&to_key, &to_balance);
+        Self::debit(e, &from, amount);
+        Self::credit(e, &to, amount);
 
         TransferExecuted {
             from,
@@ -433,31 +999,25 @@ impl RbacPlayground {
     // OPERATOR Role Functions
     // ========================================================================
 
-    /// Batch mint to multiple addresses (requires OPERATOR role).
-    #[only_role(caller, "operator")]
+    /// Batch mint to multiple addresses (requires OPERATOR role or delegation).
+    /// Takes at most `MAX_BATCH_SIZE` recipients.
     pub fn batch_mint(e: &Env, recipients: Vec<Address>, amounts: Vec<i128>, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        Self::require_permission(e, &permissions::BATCH_MINT, &caller);
 
         let count = recipients.len();
         if count != amounts.len() {
             panic!("recipients and amounts must have the same length");
         }
+        Self::ensure_batch_size(count);
         let mut i: u32 = 0;
 
         while i < count {
             let to = recipients.get(i).unwrap();
             let amount = amounts.get(i).unwrap();
+            Self::ensure_valid_amount(amount);
 
-            let key = DataKey::Balance(to.clone());
-            let mut balance: i128 = e.storage().instance().get(&key).unwrap_or(0);
-            balance += amount;
-            e.storage().instance().set(&key, &balance);
-
-            // Update total supply
-            let mut total: i128 = e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-            total += amount;
-            e.storage().instance().set(&DataKey::TotalSupply, &total);
+            Self::credit(e, &to, amount);
+            Self::adjust_total_supply(e, amount);
 
             Minted {
                 to,
@@ -477,31 +1037,26 @@ impl RbacPlayground {
         .publish(e);
     }
 
-    /// Batch burn from multiple addresses (requires OPERATOR role).
-    #[only_role(caller, "operator")]
+    /// Batch burn from multiple addresses (requires OPERATOR role or delegation).
+    /// Panics, burning nothing, if any account holds less than its amount.
+    /// Takes at most `MAX_BATCH_SIZE` accounts.
     pub fn batch_burn(e: &Env, accounts: Vec<Address>, amounts: Vec<i128>, caller: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        Self::require_permission(e, &permissions::BATCH_BURN, &caller);
 
         let count = accounts.len();
         if count != amounts.len() {
             panic!("accounts and amounts must have the same length");
         }
+        Self::ensure_batch_size(count);
         let mut i: u32 = 0;
 
         while i < count {
             let from = accounts.get(i).unwrap();
             let amount = amounts.get(i).unwrap();
+            Self::ensure_valid_amount(amount);
 
-            let key = DataKey::Balance(from.clone());
-            let mut balance: i128 = e.storage().instance().get(&key).unwrap_or(0);
-            balance -= amount;
-            e.storage().instance().set(&key, &balance);
-
-            // Update total supply
-            let mut total: i128 = e.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-            total -= amount;
-            e.storage().instance().set(&DataKey::TotalSupply, &total);
+            Self::debit(e, &from, amount);
+            Self::adjust_total_supply(e, -amount);
 
             Burned {
                 from,
@@ -526,8 +1081,9 @@ impl RbacPlayground {
     // ========================================================================
 
     /// Propose a transfer that requires multi-sig approval.
-    /// Any TRANSFER role holder can propose, but requires APPROVER approval.
-    #[only_role(proposer, "transfer")]
+    /// Any TRANSFER role holder (or delegate) can propose, but requires APPROVER approval.
+    /// `required_approvals` must be between 1 and `MAX_REQUIRED_APPROVALS`, and
+    /// the proposer may have at most `MAX_OPEN_TRANSFERS` open transfers.
     pub fn propose_transfer(
         e: &Env,
         from: Address,
@@ -536,8 +1092,14 @@ impl RbacPlayground {
         required_approvals: u32,
         proposer: Address,
     ) -> u64 {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        Self::require_permission(e, &permissions::PROPOSE_TRANSFER, &proposer);
+        Self::ensure_valid_amount(amount);
+        if required_approvals == 0 {
+            panic!("required_approvals must be at least 1");
+        }
+        if required_approvals > MAX_REQUIRED_APPROVALS {
+            panic!("required_approvals exceeds MAX_REQUIRED_APPROVALS");
+        }
 
         let id: u64 = e.storage().instance().get(&DataKey::PendingTransferCounter).unwrap_or(0);
         let next_id = id + 1;
@@ -551,9 +1113,12 @@ impl RbacPlayground {
             approvals: 0,
             required_approvals,
             executed: false,
+            proposer: proposer.clone(),
+            cancelled: false,
         };
 
         e.storage().instance().set(&DataKey::PendingTransfer(id), &pending);
+        Self::track_open_transfer(e, &proposer, id);
 
         TransferProposed {
             id,
@@ -567,15 +1132,24 @@ impl RbacPlayground {
         id
     }
 
-    /// Approve a pending transfer (requires APPROVER role).
+    /// Approve a pending transfer (requires APPROVER role or delegation).
     /// When enough approvals are reached, the transfer is automatically executed.
-    #[only_role(approver, "approver")]
+    /// A delegate's approval counts as its delegator's. An approval that leaves
+    /// the transfer open counts towards the approver's `MAX_OPEN_TRANSFERS`.
     pub fn approve_transfer(e: &Env, id: u64, approver: Address) {
-        // Note: #[only_role] macro handles require_auth()
-        Self::require_not_paused(e);
+        let caller = approver;
+        let approver = Self::require_permission(e, &permissions::APPROVE_TRANSFER, &caller);
+        let delegate = if caller == approver {
+            None
+        } else {
+            Some(caller)
+        };
 
         let key = DataKey::PendingTransfer(id);
         let mut transfer: PendingTransfer = e.storage().instance().get(&key).unwrap();
+        if transfer.cancelled {
+            panic!("Transfer has been cancelled");
+        }
 
         // Check if already approved by this approver
         let approval_key = DataKey::TransferApproval(id, approver.clone());
@@ -587,12 +1161,24 @@ impl RbacPlayground {
         // Record this approval
         e.storage().instance().set(&approval_key, &true);
         transfer.approvals += 1;
+        if !transfer.executed {
+            Self::track_open_transfer(e, &approver, id);
+            let approvers_key = DataKey::TransferApprovers(id);
+            let mut approvers: Vec<Address> = e
+                .storage()
+                .persistent()
+                .get(&approvers_key)
+                .unwrap_or_else(|| Vec::new(e));
+            approvers.push_back(approver.clone());
+            Self::set_index_entry(e, &approvers_key, &approvers);
+        }
 
         TransferApproved {
             id,
             approver: approver.clone(),
             current_approvals: transfer.approvals,
             required_approvals: transfer.required_approvals,
+            delegate,
         }
         .publish(e);
 
@@ -600,16 +1186,11 @@ impl RbacPlayground {
         if transfer.approvals >= transfer.required_approvals && !transfer.executed {
             transfer.executed = true;
 
-            // Execute the transfer
-            let from_key = DataKey::Balance(transfer.from.clone());
-            let mut from_balance: i128 = e.storage().instance().get(&from_key).unwrap_or(0);
-            from_balance -= transfer.amount;
-            e.storage().instance().set(&from_key, &from_balance);
-
-            let to_key = DataKey::Balance(transfer.to.clone());
-            let mut to_balance: i128 = e.storage().instance().get(&to_key).unwrap_or(0);
-            to_balance += transfer.amount;
-            e.storage().instance().set(&to_key, &to_balance);
+            // Execute the transfer; the approval fails with it if `from`
+            // cannot cover the amount any more.
+            Self::debit(e, &transfer.from, transfer.amount);
+            Self::credit(e, &transfer.to, transfer.amount);
+            Self::close_transfer(e, &transfer);
 
             TransferFinalized {
                 id,
@@ -623,6 +1204,300 @@ impl RbacPlayground {
         e.storage().instance().set(&key, &transfer);
     }
 
+    // ========================================================================
+    // Role Caps
+    // ========================================================================
+
+    /// Limit `role` to at most `max_members` accounts, or remove the limit
+    /// with `None` (admin only).
+    ///
+    /// The cap applies to `grant_role`, `batch_grant_roles` and `accept_role`;
+    /// it cannot be set below the current member count or above
+    /// `MAX_ROLE_MEMBERS`, which also bounds roles without a cap. Emits
+    /// `RoleCapSet`.
+    #[only_admin]
+    pub fn set_role_cap(e: &Env, role: Symbol, max_members: Option<u32>) {
+        let key = DataKey::RoleCap(role.clone());
+        match max_members {
+            Some(max_members) => {
+                if access_control::get_role_member_count(e, &role) > max_members {
+                    panic!("Role cap below current member count");
+                }
+                if max_members > MAX_ROLE_MEMBERS {
+                    panic!("Role cap above MAX_ROLE_MEMBERS");
+                }
+                e.storage().instance().set(&key, &max_members);
+            }
+            None => e.storage().instance().remove(&key),
+        }
+
+        RoleCapSet {
+            role,
+            max_members,
+            caller: access_control::get_admin(e).unwrap(),
+        }
+        .publish(e);
+    }
+
+    /// Get the member cap of `role`, if any (public).
+    pub fn get_role_cap(e: &Env, role: Symbol) -> Option<u32> {
+        e.storage().instance().get(&DataKey::RoleCap(role))
+    }
+
+    // ========================================================================
+    // Batch Role Management
+    // ========================================================================
+
+    /// Grant several roles in one call.
+    ///
+    /// For each `(account, role)`, `caller` must be the admin or hold the
+    /// role's admin role (see `get_role_admin`). Emits `RoleGranted` per new
+    /// membership, then one `BatchOperation` with operation `grant`. Any
+    /// failure reverts the whole batch, which takes at most `MAX_BATCH_SIZE`
+    /// grants.
+    pub fn batch_grant_roles(e: &Env, grants: Vec<(Address, Symbol)>, caller: Address) {
+        caller.require_auth();
+        Self::ensure_batch_size(grants.len());
+
+        for (account, role) in grants.iter() {
+            access_control::ensure_if_admin_or_admin_role(e, &caller, &role);
+            Self::ensure_role_capacity(e, &account, &role);
+            access_control::grant_role_no_auth(e, &caller, &account, &role);
+        }
+
+        BatchOperation {
+            operation: symbol_short!("grant"),
+            count: grants.len(),
+            caller,
+        }
+        .publish(e);
+    }
+
+    /// Revoke several roles in one call.
+    ///
+    /// Same authorization rules as `batch_grant_roles`. Emits `RoleRevoked`
+    /// per membership, then one `BatchOperation` with operation `revoke`. Any
+    /// failure, such as an account not holding the role, reverts the whole
+    /// batch, which takes at most `MAX_BATCH_SIZE` revocations.
+    pub fn batch_revoke_roles(e: &Env, revocations: Vec<(Address, Symbol)>, caller: Address) {
+        caller.require_auth();
+        Self::ensure_batch_size(revocations.len());
+
+        for (account, role) in revocations.iter() {
+            access_control::ensure_if_admin_or_admin_role(e, &caller, &role);
+            Self::ensure_not_last_pauser(e, &role);
+            access_control::revoke_role_no_auth(e, &caller, &account, &role);
+        }
+
+        BatchOperation {
+            operation: symbol_short!("revoke"),
+            count: revocations.len(),
+            caller,
+        }
+        .publish(e);
+    }
+
+    // ========================================================================
+    // Two-Step Role Grants
+    // ========================================================================
+
+    /// Offer `role` to `account`; it is granted only once `account` calls
+    /// `accept_role` before `live_until_ledger`.
+    ///
+    /// `caller` must be the admin or hold the role's admin role. Like
+    /// `transfer_admin_role`, a `live_until_ledger` of 0 withdraws the pending
+    /// offer (emits `RoleOfferCancelled`); otherwise it replaces any pending
+    /// offer (emits `RoleOffered`).
+    pub fn offer_role(
+        e: &Env,
+        account: Address,
+        role: Symbol,
+        live_until_ledger: u32,
+        caller: Address,
+    ) {
+        caller.require_auth();
+        access_control::ensure_if_admin_or_admin_role(e, &caller, &role);
+
+        let key = DataKey::RoleOffer(account.clone(), role.clone());
+
+        if live_until_ledger == 0 {
+            if !e.storage().temporary().has(&key) {
+                panic!("No pending role offer");
+            }
+            e.storage().temporary().remove(&key);
+
+            RoleOfferCancelled {
+                role,
+                account,
+                caller,
+            }
+            .publish(e);
+            return;
+        }
+
+        let current_ledger = e.ledger().sequence();
+        if live_until_ledger < current_ledger
+            || live_until_ledger > e.ledger().max_live_until_ledger()
+        {
+            panic!("Invalid live_until_ledger");
+        }
+        if access_control::has_role(e, &account, &role).is_some() {
+            panic!("Account already has the role");
+        }
+
+        let offer = RoleOffer {
+            offered_by: caller.clone(),
+            live_until_ledger,
+        };
+        let live_for = live_until_ledger - current_ledger;
+        e.storage().temporary().set(&key, &offer);
+        e.storage().temporary().extend_ttl(&key, live_for, live_for);
+
+        RoleOffered {
+            role,
+            account,
+            caller,
+            live_until_ledger,
+        }
+        .publish(e);
+    }
+
+    /// Accept a pending role offer (requires `account` auth).
+    ///
+    /// The offerer must still be allowed to grant the role. Emits the standard
+    /// `RoleGranted` event with the offerer as caller.
+    pub fn accept_role(e: &Env, account: Address, role: Symbol) {
+        account.require_auth();
+
+        let key = DataKey::RoleOffer(account.clone(), role.clone());
+        let offer: RoleOffer = e
+            .storage()
+            .temporary()
+            .get(&key)
+            .unwrap_or_else(|| panic!("No pending role offer"));
+        // The temporary entry may outlive `live_until_ledger` by the minimum TTL.
+        if e.ledger().sequence() > offer.live_until_ledger {
+            panic!("Role offer has expired");
+        }
+
+        access_control::ensure_if_admin_or_admin_role(e, &offer.offered_by, &role);
+
+        Self::ensure_role_capacity(e, &account, &role);
+
+        e.storage().temporary().remove(&key);
+        access_control::grant_role_no_auth(e, &offer.offered_by, &account, &role);
+    }
+
+    /// Get the pending offer of `role` to `account`, if any (public).
+    pub fn get_role_offer(e: &Env, account: Address, role: Symbol) -> Option<RoleOffer> {
+        e.storage()
+            .temporary()
+            .get(&DataKey::RoleOffer(account, role))
+    }
+
+    // ========================================================================
+    // Delegation
+    // ========================================================================
+
+    /// Let `delegate` use the `role` of `delegator` until `until_ledger`
+    /// (requires `delegator` auth and the role).
+    ///
+    /// Role-gated functions then accept `delegate` as caller. The delegation
+    /// ends when it expires, is revoked, or `delegator` loses the role. A
+    /// holder has at most one delegate per role, and a delegate acts for at
+    /// most one holder per role.
+    pub fn delegate_role(
+        e: &Env,
+        delegator: Address,
+        role: Symbol,
+        delegate: Address,
+        until_ledger: u32,
+    ) {
+        delegator.require_auth();
+        access_control::ensure_role(e, &delegator, &role);
+
+        if delegate == delegator {
+            panic!("Cannot delegate to self");
+        }
+        let current_ledger = e.ledger().sequence();
+        if until_ledger < current_ledger || until_ledger > e.ledger().max_live_until_ledger() {
+            panic!("Invalid until_ledger");
+        }
+        if let Some(other) = Self::active_delegator(e, &delegate, &role) {
+            if other != delegator {
+                panic!("Delegate already acts for another holder");
+            }
+        }
+
+        // Replace any previous delegation of this role.
+        Self::remove_delegation(e, &delegator, &role);
+
+        let key = DataKey::Delegation(delegator.clone(), role.clone());
+        let by_key = DataKey::DelegatedBy(delegate.clone(), role.clone());
+        let delegation = Delegation {
+            delegate: delegate.clone(),
+            until_ledger,
+        };
+        let live_for = until_ledger - current_ledger;
+        e.storage().temporary().set(&key, &delegation);
+        e.storage().temporary().extend_ttl(&key, live_for, live_for);
+        e.storage().temporary().set(&by_key, &delegator);
+        e.storage()
+            .temporary()
+            .extend_ttl(&by_key, live_for, live_for);
+
+        DelegationCreated {
+            delegator,
+            delegate,
+            role,
+            until_ledger,
+        }
+        .publish(e);
+    }
+
+    /// Withdraw the delegation of `role` by `delegator` (requires `delegator` auth).
+    pub fn revoke_delegation(e: &Env, delegator: Address, role: Symbol) {
+        delegator.require_auth();
+
+        if Self::remove_delegation(e, &delegator, &role).is_none() {
+            panic!("No delegation");
+        }
+    }
+
+    /// Get the delegation of `role` by `delegator`, if any (public).
+    ///
+    /// Expired delegations are reported as `None`.
+    pub fn get_delegation(e: &Env, delegator: Address, role: Symbol) -> Option<Delegation> {
+        let key = DataKey::Delegation(delegator, role);
+        let delegation: Delegation = e.storage().temporary().get(&key)?;
+        if e.ledger().sequence() > delegation.until_ledger {
+            return None;
+        }
+        Some(delegation)
+    }
+
+    // ========================================================================
+    // Emergency Revocation
+    // ========================================================================
+
+    /// Revoke every role held by a compromised `account` (requires admin).
+    ///
+    /// Emits `RoleRevoked` for each role, cancels pending transfers that the
+    /// account proposed or approved, then emits `AccountCompromised`. The
+    /// last-pauser guard does not apply; the owner can `emergency_unpause`.
+    #[only_admin]
+    pub fn revoke_all_roles(e: &Env, account: Address) {
+        let admin = access_control::get_admin(e).unwrap();
+        Self::revoke_compromised_account(e, &admin, &account);
+    }
+
+    /// Owner path for `revoke_all_roles`, usable without the admin key.
+    #[only_owner]
+    pub fn owner_revoke_all_roles(e: &Env, account: Address) {
+        let owner = ownable::get_owner(e).unwrap();
+        Self::revoke_compromised_account(e, &owner, &account);
+    }
+
     // ========================================================================
     // Owner & Admin Functions
     // ========================================================================
@@ -639,26 +1514,442 @@ impl RbacPlayground {
         symbol_short!("admin_ok")
     }
 
+    /// (Re)apply the `ROLE_HIERARCHY` (requires admin).
+    ///
+    /// `__constructor` already does this; use it to restore the hierarchy
+    /// after `set_role_admin` changes.
+    #[only_admin]
+    pub fn configure_hierarchy(e: &Env) {
+        Self::set_role_hierarchy(e);
+    }
+
     /// Emergency pause by owner (bypasses PAUSER role).
+    ///
+    /// On a paused contract it replaces the pause's reason and
+    /// `until_ledger`, e.g. to extend it, and keeps the contract paused.
+    #[only_owner]
+    pub fn emergency_pause(e: &Env, reason: Symbol, until_ledger: Option<u32>) {
+        Self::set_paused(e, ownable::get_owner(e).unwrap(), reason, until_ledger);
+    }
+
+    /// Emergency unpause by owner (bypasses PAUSER role).
+    ///
+    /// Lets the owner recover a paused contract even if no PAUSER is left.
     #[only_owner]
-    pub fn emergency_pause(e: &Env) {
-        e.storage().instance().set(&DataKey::Paused, &true);
-        Paused {
+    pub fn emergency_unpause(e: &Env) {
+        permissions::EMERGENCY_UNPAUSE.pause.enforce(e);
+        Self::clear_paused(e);
+        EmergencyUnpaused {
             caller: ownable::get_owner(e).unwrap(),
         }
         .publish(e);
     }
 
+    // ========================================================================
+    // Upgrades
+    // ========================================================================
+
+    /// Replace the contract code with the uploaded wasm `new_wasm_hash`
+    /// (owner only). Emits `Upgraded`.
+    ///
+    /// The new code takes effect after this call; the owner then calls
+    /// `migrate` on it to bring storage up to its layout. The new code's
+    /// constructor is not run.
+    #[only_owner]
+    pub fn upgrade(e: &Env, new_wasm_hash: BytesN<32>) {
+        upgradeable::enable_migration(e);
+
+        Upgraded {
+            new_wasm_hash: new_wasm_hash.clone(),
+            caller: ownable::get_owner(e).unwrap(),
+        }
+        .publish(e);
+
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Migrate storage from the recorded layout version to
+    /// `STORAGE_VERSION` (owner only, after each `upgrade`).
+    ///
+    /// Each call converts at most `MIGRATION_BATCH_SIZE` pending transfers
+    /// and records where it stopped; call it again until it returns `true`,
+    /// once storage is at `STORAGE_VERSION`. Until then, transfers not yet
+    /// converted cannot be viewed or approved.
+    ///
+    /// Pending transfers stored before version 2 have no recorded proposer;
+    /// they get the contract's own address, so `revoke_all_roles` never
+    /// cancels them on a proposer's behalf.
+    #[only_owner]
+    pub fn migrate(e: &Env) -> bool {
+        upgradeable::ensure_can_complete_migration(e);
+
+        if Self::get_storage_version(e) < 2 {
+            let (next, end): (u64, u64) = e
+                .storage()
+                .instance()
+                .get(&DataKey::MigrationCursor)
+                .unwrap_or_else(|| {
+                    let count = e.storage().instance().get(&DataKey::PendingTransferCounter);
+                    (0, count.unwrap_or(0))
+                });
+            let stop = end.min(next + MIGRATION_BATCH_SIZE);
+            Self::migrate_pending_transfers_v1(e, next..stop);
+            if stop < end {
+                e.storage()
+                    .instance()
+                    .set(&DataKey::MigrationCursor, &(stop, end));
+                return false;
+            }
+            e.storage().instance().remove(&DataKey::MigrationCursor);
+        }
+        e.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+
+        upgradeable::complete_migration(e);
+        true
+    }
+
+    /// Get the storage layout version (public).
+    pub fn get_storage_version(e: &Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::StorageVersion)
+            .unwrap_or(1)
+    }
+
     // ========================================================================
     // Internal Helpers
     // ========================================================================
 
-    fn require_not_paused(e: &Env) {
-        let paused: bool = e.storage().instance().get(&DataKey::Paused).unwrap_or(false);
-        if paused {
-            panic!("Contract is paused");
+    fn migrate_pending_transfers_v1(e: &Env, ids: core::ops::Range<u64>) {
+        for id in ids {
+            let key = DataKey::PendingTransfer(id);
+            let Some(old) = e.storage().instance().get::<_, PendingTransferV1>(&key) else {
+                continue;
+            };
+
+            let transfer = PendingTransfer {
+                id: old.id,
+                from: old.from,
+                to: old.to,
+                amount: old.amount,
+                approvals: old.approvals,
+                required_approvals: old.required_approvals,
+                executed: old.executed,
+                proposer: e.current_contract_address(),
+                cancelled: false,
+            };
+            e.storage().instance().set(&key, &transfer);
+        }
+    }
+
+    /// Adds transfer `id` to the open transfers of `account`. Panics if that
+    /// would exceed `MAX_OPEN_TRANSFERS`.
+    fn track_open_transfer(e: &Env, account: &Address, id: u64) {
+        let mut open = Self::get_open_transfers(e, account.clone());
+        if let Err(at) = open.binary_search(id) {
+            if open.len() >= MAX_OPEN_TRANSFERS {
+                panic!("Account has MAX_OPEN_TRANSFERS open transfers");
+            }
+            open.insert(at, id);
+            Self::set_index_entry(e, &DataKey::OpenTransfers(account.clone()), &open);
+        }
+    }
+
+    /// Writes an `OpenTransfers` or `TransferApprovers` entry and extends its
+    /// TTL, so open transfers stay cancellable.
+    fn set_index_entry<V: IntoVal<Env, Val>>(e: &Env, key: &DataKey, value: &V) {
+        e.storage().persistent().set(key, value);
+        e.storage()
+            .persistent()
+            .extend_ttl(key, INDEX_TTL_THRESHOLD, INDEX_EXTEND_AMOUNT);
+    }
+
+    /// Drops a finalized or cancelled transfer from the open transfers of
+    /// its proposer and approvers.
+    fn close_transfer(e: &Env, transfer: &PendingTransfer) {
+        let approvers_key = DataKey::TransferApprovers(transfer.id);
+        let mut participants: Vec<Address> = e
+            .storage()
+            .persistent()
+            .get(&approvers_key)
+            .unwrap_or_else(|| Vec::new(e));
+        e.storage().persistent().remove(&approvers_key);
+        participants.push_back(transfer.proposer.clone());
+
+        for account in participants.iter() {
+            let key = DataKey::OpenTransfers(account.clone());
+            let mut open = Self::get_open_transfers(e, account);
+            if let Ok(at) = open.binary_search(transfer.id) {
+                open.remove(at);
+                if open.is_empty() {
+                    e.storage().persistent().remove(&key);
+                } else {
+                    Self::set_index_entry(e, &key, &open);
+                }
+            }
+        }
+    }
+
+    fn revoke_compromised_account(e: &Env, caller: &Address, account: &Address) {
+        let mut revoked_roles = Vec::new(e);
+        for role in ROLES {
+            if access_control::has_role(e, account, &role).is_some() {
+                access_control::revoke_role_no_auth(e, caller, account, &role);
+                revoked_roles.push_back(role.clone());
+            }
+
+            // Drop delegations the account made or received.
+            Self::remove_delegation(e, account, &role);
+            let by_key = DataKey::DelegatedBy(account.clone(), role.clone());
+            if let Some(delegator) = e.storage().temporary().get::<_, Address>(&by_key) {
+                Self::remove_delegation(e, &delegator, &role);
+            }
+        }
+
+        // Only the account's own open transfers, however many have closed.
+        let cancelled_transfers = Self::get_open_transfers(e, account.clone());
+        for id in cancelled_transfers.iter() {
+            let key = DataKey::PendingTransfer(id);
+            let mut transfer: PendingTransfer = e.storage().instance().get(&key).unwrap();
+            transfer.cancelled = true;
+            e.storage().instance().set(&key, &transfer);
+            Self::close_transfer(e, &transfer);
+        }
+
+        AccountCompromised {
+            account: account.clone(),
+            caller: caller.clone(),
+            revoked_roles,
+            cancelled_transfers,
+        }
+        .publish(e);
+    }
+
+    /// Enforce the role-gated `permission` for `caller`: its pause gate, then
+    /// its role via `require_role_or_delegate`. Returns the account whose role
+    /// is exercised.
+    fn require_permission(e: &Env, permission: &RoleGated, caller: &Address) -> Address {
+        permission.pause.enforce(e);
+        Self::require_role_or_delegate(e, caller, &permission.role)
+    }
+
+    /// Require `caller`'s auth and that it holds `role`, or is the active
+    /// delegate of a holder. Returns the account whose role is exercised.
+    fn require_role_or_delegate(e: &Env, caller: &Address, role: &Symbol) -> Address {
+        let holder = if access_control::has_role(e, caller, role).is_some() {
+            caller.clone()
+        } else if let Some(delegator) = Self::active_delegator(e, caller, role) {
+            delegator
+        } else {
+            panic_with_error!(e, AccessControlError::Unauthorized);
+        };
+
+        caller.require_auth();
+        holder
+    }
+
+    /// The holder `delegate` currently acts for with `role`, if the
+    /// delegation is unexpired and the holder still has the role.
+    fn active_delegator(e: &Env, delegate: &Address, role: &Symbol) -> Option<Address> {
+        let by_key = DataKey::DelegatedBy(delegate.clone(), role.clone());
+        let delegator: Address = e.storage().temporary().get(&by_key)?;
+
+        let key = DataKey::Delegation(delegator.clone(), role.clone());
+        let delegation: Delegation = e.storage().temporary().get(&key)?;
+        if delegation.delegate != *delegate
+            || e.ledger().sequence() > delegation.until_ledger
+            || access_control::has_role(e, &delegator, role).is_none()
+        {
+            return None;
+        }
+
+        Some(delegator)
+    }
+
+    /// Remove the delegation of `role` by `delegator`, emitting
+    /// `DelegationRevoked`. Returns the removed delegation, if any.
+    fn remove_delegation(e: &Env, delegator: &Address, role: &Symbol) -> Option<Delegation> {
+        let key = DataKey::Delegation(delegator.clone(), role.clone());
+        let delegation: Delegation = e.storage().temporary().get(&key)?;
+        e.storage().temporary().remove(&key);
+
+        let by_key = DataKey::DelegatedBy(delegation.delegate.clone(), role.clone());
+        let current: Option<Address> = e.storage().temporary().get(&by_key);
+        if current.as_ref() == Some(delegator) {
+            e.storage().temporary().remove(&by_key);
+        }
+
+        DelegationRevoked {
+            delegator: delegator.clone(),
+            delegate: delegation.delegate.clone(),
+            role: role.clone(),
+        }
+        .publish(e);
+
+        Some(delegation)
+    }
+
+    fn set_role_hierarchy(e: &Env) {
+        for (role, admin_role) in ROLE_HIERARCHY {
+            access_control::set_role_admin_no_auth(e, &role, &admin_role);
+        }
+    }
+
+    /// Panics if granting `role` to `account` would exceed the role's cap,
+    /// or `MAX_ROLE_MEMBERS` if it has none. Re-granting a role the account
+    /// already holds is a no-op and passes.
+    fn ensure_role_capacity(e: &Env, account: &Address, role: &Symbol) {
+        let cap: u32 = e
+            .storage()
+            .instance()
+            .get(&DataKey::RoleCap(role.clone()))
+            .unwrap_or(MAX_ROLE_MEMBERS);
+        if access_control::has_role(e, account, role).is_none()
+            && access_control::get_role_member_count(e, role) >= cap
+        {
+            panic!("Role member cap reached");
+        }
+    }
+
+    /// Prevents the last PAUSER from being removed while the contract is paused,
+    /// which would otherwise leave nobody with the role able to unpause it.
+    fn ensure_not_last_pauser(e: &Env, role: &Symbol) {
+        if *role == PAUSER_ROLE
+            && Self::pause_in_effect(e)
+            && access_control::get_role_member_count(e, role) <= 1
+        {
+            panic!("Cannot remove the last pauser while paused");
+        }
+    }
+
+    fn ensure_batch_size(count: u32) {
+        if count > MAX_BATCH_SIZE {
+            panic!("Batch exceeds MAX_BATCH_SIZE");
         }
     }
+
+    fn ensure_valid_amount(amount: i128) {
+        if amount < 0 {
+            panic!("Amount must not be negative");
+        }
+    }
+
+    fn credit(e: &Env, account: &Address, amount: i128) {
+        let balance = Self::balance(e, account);
+        Self::set_balance(e, account, balance + amount);
+    }
+
+    /// Panics if `account` holds less than `amount`, so balances never go
+    /// negative.
+    fn debit(e: &Env, account: &Address, amount: i128) {
+        let balance = Self::balance(e, account);
+        if balance < amount {
+            panic!("Insufficient balance");
+        }
+        Self::set_balance(e, account, balance - amount);
+    }
+
+    /// The balance of `account`, from persistent storage or, if it has not
+    /// changed since a version 1 deployment, from instance storage.
+    fn balance(e: &Env, account: &Address) -> i128 {
+        let key = DataKey::Balance(account.clone());
+        e.storage()
+            .persistent()
+            .get(&key)
+            .or_else(|| e.storage().instance().get(&key))
+            .unwrap_or(0)
+    }
+
+    /// Writes the balance of `account` to persistent storage, extends its TTL
+    /// and drops any version 1 entry in instance storage.
+    fn set_balance(e: &Env, account: &Address, balance: i128) {
+        let key = DataKey::Balance(account.clone());
+        e.storage().persistent().set(&key, &balance);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_EXTEND_AMOUNT);
+        if e.storage().instance().has(&key) {
+            e.storage().instance().remove(&key);
+        }
+    }
+
+    fn adjust_total_supply(e: &Env, delta: i128) {
+        let total: i128 = e
+            .storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0);
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total + delta));
+    }
+
+    /// Whether the contract is paused and the pause has not yet expired.
+    fn pause_in_effect(e: &Env) -> bool {
+        if !pausable::paused(e) {
+            return false;
+        }
+        let info: Option<PauseInfo> = e.storage().instance().get(&DataKey::PauseInfo);
+        match info.and_then(|info| info.until_ledger) {
+            Some(until_ledger) => e.ledger().sequence() <= until_ledger,
+            None => true,
+        }
+    }
+
+    /// `pausable::when_not_paused`, treating an expired pause as lifted.
+    fn when_not_paused(e: &Env) {
+        if Self::pause_in_effect(e) {
+            panic_with_error!(e, PausableError::EnforcedPause);
+        }
+    }
+
+    /// `pausable::when_paused`, treating an expired pause as lifted.
+    fn when_paused(e: &Env) {
+        if !Self::pause_in_effect(e) {
+            panic_with_error!(e, PausableError::ExpectedPause);
+        }
+    }
+
+    fn set_paused(e: &Env, caller: Address, reason: Symbol, until_ledger: Option<u32>) {
+        if let Some(until_ledger) = until_ledger {
+            if until_ledger < e.ledger().sequence() {
+                panic!("until_ledger must not be in the past");
+            }
+        }
+
+        // A pause in effect only gets new details and keeps its start ledger.
+        // An expired pause is still stored; end it before starting this one.
+        let ongoing: Option<PauseInfo> = if Self::pause_in_effect(e) {
+            e.storage().instance().get(&DataKey::PauseInfo)
+        } else {
+            if pausable::paused(e) {
+                Self::clear_paused(e);
+            }
+            pausable::pause(e);
+            None
+        };
+        let info = PauseInfo {
+            paused_by: caller.clone(),
+            paused_at: ongoing.map_or(e.ledger().sequence(), |info| info.paused_at),
+            reason: reason.clone(),
+            until_ledger,
+        };
+        e.storage().instance().set(&DataKey::PauseInfo, &info);
+
+        PauseDetails {
+            caller,
+            reason,
+            until_ledger,
+        }
+        .publish(e);
+    }
+
+    fn clear_paused(e: &Env) {
+        pausable::unpause(e);
+        e.storage().instance().remove(&DataKey::PauseInfo);
+    }
 }
 
 // ============================================================================
@@ -679,10 +1970,32 @@ impl RbacPlayground {
 ///
 /// These will be exposed as contract entrypoints and are perfect
 /// for driving your SubQuery indexer.
+///
+/// `grant_role` is overridden to enforce role caps (see `set_role_cap`), and
+/// `revoke_role` and `renounce_role` to keep at least one PAUSER while the
+/// contract is paused; they still emit the library events.
 #[default_impl]
 #[contractimpl]
-impl AccessControl for RbacPlayground {}
+impl AccessControl for RbacPlayground {
+    fn grant_role(e: &Env, caller: Address, account: Address, role: Symbol) {
+        Self::ensure_role_capacity(e, &account, &role);
+        access_control::grant_role(e, &caller, &account, &role);
+    }
+
+    fn revoke_role(e: &Env, caller: Address, account: Address, role: Symbol) {
+        Self::ensure_not_last_pauser(e, &role);
+        access_control::revoke_role(e, &caller, &account, &role);
+    }
+
+    fn renounce_role(e: &Env, caller: Address, role: Symbol) {
+        Self::ensure_not_last_pauser(e, &role);
+        access_control::renounce_role(e, &caller, &role);
+    }
+}
 
 #[default_impl]
 #[contractimpl]
-impl Ownable for RbacPlayground {}
\ No newline at end of file
+impl Ownable for RbacPlayground {}
+
+#[cfg(test)]
+mod test;
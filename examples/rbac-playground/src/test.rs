@@ -0,0 +1,1883 @@
+#![cfg(test)]
+
+extern crate std;
+
+use rbac_test_support::{assert_authorized_by, assert_last_events};
+use soroban_sdk::{
+    contracttype, symbol_short,
+    testutils::{
+        storage::Persistent as _, Address as _, Events as _, Ledger as _, MockAuth, MockAuthInvoke,
+    },
+    vec, Address, Env, Error, Event, IntoVal, Symbol, TryFromVal, Val, Vec,
+};
+
+use stellar_access::access_control::{RoleAdminChanged, RoleGranted, RoleRevoked};
+use stellar_contract_utils::pausable::{PausableError, Paused, Unpaused};
+use stellar_contract_utils::upgradeable;
+
+use crate::{
+    AccountCompromised, BatchOperation, Burned, DataKey, DelegationCreated, DelegationRevoked,
+    EmergencyUnpaused, Initialized, Minted, PauseDetails, PauseGate, PauseInfo, PendingTransfer,
+    PendingTransferV1, RbacPlayground, RbacPlaygroundClient, RoleCapSet, RoleOffer,
+    RoleOfferCancelled, RoleOffered, SensitiveDataAccessed, TransferApproved, TransferExecuted,
+    TransferFinalized, TransferProposed, Upgraded, ADMIN_GATED, APPROVER_ROLE, BURNER_ROLE,
+    INDEX_EXTEND_AMOUNT, MAX_OPEN_TRANSFERS, MAX_REQUIRED_APPROVALS, MAX_ROLE_MEMBERS,
+    MIGRATION_BATCH_SIZE, MINTER_ROLE, OPERATOR_ROLE, OWNER_GATED, PAUSER_ROLE, ROLE_GATED,
+    STORAGE_VERSION, TRANSFER_ROLE, VIEWER_ROLE,
+};
+
+struct Setup<'a> {
+    env: Env,
+    contract_id: Address,
+    admin: Address,
+    owner: Address,
+    client: RbacPlaygroundClient<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            Vec::<(Symbol, Address)>::new(&env),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+
+    Setup {
+        env,
+        contract_id,
+        admin,
+        owner,
+        client,
+    }
+}
+
+// ============================================================================
+// Initialization
+// ============================================================================
+
+/// The `Initialized` event of this build.
+fn initialized(
+    env: &Env,
+    admin: &Address,
+    owner: &Address,
+    initial_roles: Vec<(Symbol, Address)>,
+) -> Initialized {
+    Initialized {
+        admin: admin.clone(),
+        owner: owner.clone(),
+        initial_roles,
+        version: (0, 1, 0),
+        capabilities: vec![
+            env,
+            Symbol::new(env, "multisig"),
+            Symbol::new(env, "pause"),
+            Symbol::new(env, "enumeration"),
+            Symbol::new(env, "delegation"),
+            Symbol::new(env, "role_caps"),
+            Symbol::new(env, "upgrade"),
+        ],
+    }
+}
+
+#[test]
+fn version_and_capabilities_come_from_cargo_toml() {
+    let s = setup();
+    let expected = initialized(&s.env, &s.admin, &s.owner, Vec::new(&s.env));
+
+    assert_last_events(&s.env, &s.contract_id, &[&expected]);
+    assert_eq!(s.client.version(), expected.version);
+    assert_eq!(s.client.capabilities(), expected.capabilities);
+}
+
+#[test]
+fn constructor_grants_initial_roles_and_balances() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let initial_roles = vec![
+        &env,
+        (BURNER_ROLE, alice.clone()),
+        (APPROVER_ROLE, alice.clone()),
+        (APPROVER_ROLE, bob.clone()),
+    ];
+
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            initial_roles.clone(),
+            Some(vec![
+                &env,
+                (alice.clone(), 1_000i128),
+                (bob.clone(), 250i128),
+            ]),
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+
+    assert_last_events(
+        &env,
+        &contract_id,
+        &[
+            &Minted {
+                to: alice.clone(),
+                amount: 1_000,
+                caller: admin.clone(),
+            },
+            &Minted {
+                to: bob.clone(),
+                amount: 250,
+                caller: admin.clone(),
+            },
+            &initialized(&env, &admin, &owner, initial_roles),
+        ],
+    );
+    assert_eq!(client.list_burners(), vec![&env, alice.clone()]);
+    assert_eq!(
+        client.list_approvers(),
+        vec![&env, alice.clone(), bob.clone()]
+    );
+    assert_eq!(client.get_balance(&alice), 1_000);
+    assert_eq!(client.get_balance(&bob), 250);
+    assert_eq!(client.get_total_supply(), 1_250);
+}
+
+#[test]
+fn constructor_emits_role_granted_for_initial_roles() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            vec![&env, (VIEWER_ROLE, alice.clone())],
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+
+    // Admin's default roles, then `initial_roles`, then the role hierarchy.
+    let events = env.events().all();
+    let granted = events.slice(0..3);
+    let mut want = Vec::new(&env);
+    for (role, account) in [
+        (MINTER_ROLE, admin.clone()),
+        (OPERATOR_ROLE, admin.clone()),
+        (VIEWER_ROLE, alice),
+    ] {
+        let event = RoleGranted {
+            role,
+            account,
+            caller: admin.clone(),
+        };
+        want.push_back((contract_id.clone(), event.topics(&env), event.data(&env)));
+    }
+    assert_eq!(granted, want);
+}
+
+#[test]
+fn constructor_emits_initialized_after_role_granted() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let initial_roles = vec![&env, (PAUSER_ROLE, alice.clone())];
+
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            initial_roles.clone(),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+
+    assert_last_events(
+        &env,
+        &contract_id,
+        &[&initialized(&env, &admin, &owner, initial_roles)],
+    );
+    // The admin's two default roles and `initial_roles` are granted before it.
+    let events = env.events().all();
+    let last = events.len() - 1;
+    let role_granted: Symbol = Symbol::new(&env, "role_granted");
+    let granted_at: std::vec::Vec<u32> = (0..events.len())
+        .filter(|&i| {
+            let topics = events.get(i).unwrap().1;
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(role_granted.clone())
+        })
+        .collect();
+    assert_eq!(granted_at.len(), 3);
+    assert!(granted_at.iter().all(|&i| i < last));
+}
+
+// ============================================================================
+// Role Hierarchy
+// ============================================================================
+
+#[test]
+fn constructor_sets_role_hierarchy() {
+    let s = setup();
+
+    // `register` runs the constructor, so its events are the latest ones.
+    let empty = Symbol::new(&s.env, "");
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &RoleAdminChanged {
+                role: MINTER_ROLE,
+                previous_admin_role: empty.clone(),
+                new_admin_role: OPERATOR_ROLE,
+            },
+            &RoleAdminChanged {
+                role: BURNER_ROLE,
+                previous_admin_role: empty.clone(),
+                new_admin_role: OPERATOR_ROLE,
+            },
+            &RoleAdminChanged {
+                role: TRANSFER_ROLE,
+                previous_admin_role: empty,
+                new_admin_role: APPROVER_ROLE,
+            },
+            &initialized(&s.env, &s.admin, &s.owner, Vec::new(&s.env)),
+        ],
+    );
+    assert_eq!(s.client.get_role_admin(&MINTER_ROLE), Some(OPERATOR_ROLE));
+    assert_eq!(s.client.get_role_admin(&BURNER_ROLE), Some(OPERATOR_ROLE));
+    assert_eq!(s.client.get_role_admin(&TRANSFER_ROLE), Some(APPROVER_ROLE));
+    assert_eq!(s.client.get_role_admin(&PAUSER_ROLE), None);
+}
+
+#[test]
+fn operator_grants_minter_without_being_admin() {
+    let s = setup();
+    let operator = Address::generate(&s.env);
+    let minter = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &operator, &OPERATOR_ROLE);
+
+    s.client.grant_role(&operator, &minter, &MINTER_ROLE);
+    assert!(s.client.has_role(&minter, &MINTER_ROLE).is_some());
+
+    s.client.revoke_role(&operator, &minter, &MINTER_ROLE);
+    assert!(s.client.has_role(&minter, &MINTER_ROLE).is_none());
+
+    // OPERATOR does not administer roles outside the hierarchy.
+    assert!(s
+        .client
+        .try_grant_role(&operator, &minter, &PAUSER_ROLE)
+        .is_err());
+}
+
+#[test]
+fn approver_grants_transfer() {
+    let s = setup();
+    let approver = Address::generate(&s.env);
+    let agent = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+
+    s.client.grant_role(&approver, &agent, &TRANSFER_ROLE);
+    assert!(s.client.has_role(&agent, &TRANSFER_ROLE).is_some());
+    assert!(s
+        .client
+        .try_grant_role(&approver, &agent, &MINTER_ROLE)
+        .is_err());
+}
+
+#[test]
+fn configure_hierarchy_restores_admin_roles() {
+    let s = setup();
+    s.client.set_role_admin(&MINTER_ROLE, &APPROVER_ROLE);
+
+    s.client.configure_hierarchy();
+
+    assert_eq!(s.client.get_role_admin(&MINTER_ROLE), Some(OPERATOR_ROLE));
+}
+
+// ============================================================================
+// Permission Introspection
+// ============================================================================
+
+#[test]
+fn required_role_reports_matrix() {
+    let s = setup();
+    let role_of = |name: &str| s.client.required_role(&Symbol::new(&s.env, name));
+
+    assert_eq!(role_of("mint"), Some(MINTER_ROLE));
+    assert_eq!(role_of("batch_mint"), Some(OPERATOR_ROLE));
+    assert_eq!(role_of("approve_transfer"), Some(APPROVER_ROLE));
+    assert_eq!(role_of("owner_ping"), None);
+    assert_eq!(role_of("get_balance"), None);
+}
+
+#[test]
+fn can_considers_roles_owner_admin_and_pause() {
+    let s = setup();
+    let pauser = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+    let outsider = Address::generate(&s.env);
+    let can = |account: &Address, name: &str| s.client.can(account, &Symbol::new(&s.env, name));
+    s.client.grant_role(&s.admin, &pauser, &PAUSER_ROLE);
+    s.client
+        .delegate_role(&s.admin, &MINTER_ROLE, &delegate, &100);
+
+    assert!(can(&s.admin, "mint"));
+    assert!(can(&delegate, "mint"));
+    assert!(!can(&outsider, "mint"));
+    assert!(can(&pauser, "pause"));
+    assert!(!can(&pauser, "unpause"));
+    assert!(can(&s.admin, "admin_ping"));
+    assert!(!can(&s.owner, "admin_ping"));
+    assert!(can(&s.owner, "emergency_pause"));
+    assert!(!can(&s.admin, "owner_ping"));
+    assert!(!can(&s.admin, "get_balance"));
+
+    s.client.pause(&pauser, &symbol_short!("incident"), &None);
+
+    assert!(!can(&s.admin, "mint"));
+    assert!(can(&pauser, "unpause"));
+    assert!(can(&s.owner, "emergency_pause"));
+    assert!(can(&s.owner, "emergency_unpause"));
+    assert!(!can(&pauser, "pause"));
+}
+
+/// Arguments that make the matrix entry `name` succeed when `caller` holds
+/// its permission: `funded` has a balance and transfer `0` is pending.
+fn matrix_call_args(s: &Setup, name: &str, caller: &Address, funded: &Address) -> Vec<Val> {
+    let env = &s.env;
+    let other = Address::generate(env);
+    let reason = symbol_short!("incident");
+    match name {
+        "mint" => (other, 1_i128, caller.clone()).into_val(env),
+        "burn" => (funded.clone(), 1_i128, caller.clone()).into_val(env),
+        "pause" => (caller.clone(), reason, None::<u32>).into_val(env),
+        "unpause" | "view_sensitive_stats" => (caller.clone(),).into_val(env),
+        "view_pending_transfer" | "approve_transfer" => (0_u64, caller.clone()).into_val(env),
+        "execute_transfer" => (funded.clone(), other, 1_i128, caller.clone()).into_val(env),
+        "batch_mint" => (vec![env, other], vec![env, 1_i128], caller.clone()).into_val(env),
+        "batch_burn" => {
+            (vec![env, funded.clone()], vec![env, 1_i128], caller.clone()).into_val(env)
+        }
+        "propose_transfer" => (funded.clone(), other, 1_i128, 1_u32, caller.clone()).into_val(env),
+        "set_role_cap" => (MINTER_ROLE, Some(5_u32)).into_val(env),
+        "revoke_all_roles" | "owner_revoke_all_roles" => (other,).into_val(env),
+        "transfer_admin_role" | "transfer_ownership" => (other, 1_000_u32).into_val(env),
+        "set_role_admin" => (MINTER_ROLE, OPERATOR_ROLE).into_val(env),
+        "emergency_pause" => (reason, None::<u32>).into_val(env),
+        _ => Vec::new(env),
+    }
+}
+
+/// Calls `name` with only `signer`'s authorization for it.
+fn try_matrix_call(s: &Setup, name: &str, args: &Vec<Val>, signer: &Address) -> Result<(), Error> {
+    let function = Symbol::new(&s.env, name);
+    s.env.mock_auths(&[MockAuth {
+        address: signer,
+        invoke: &MockAuthInvoke {
+            contract: &s.contract_id,
+            fn_name: name,
+            args: args.clone(),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = s
+        .env
+        .try_invoke_contract::<Val, Error>(&s.contract_id, &function, args.clone());
+    s.env.mock_all_auths();
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(Ok(error)) => Err(error),
+        Err(Err(error)) => panic!("{name} aborted: {error:?}"),
+    }
+}
+
+/// Calls every entry of the permission matrix as an outsider and as the
+/// account it names, in and out of its pause gate. `upgrade` needs a wasm
+/// build and is covered by `tests/upgrade.rs`.
+#[test]
+fn permission_matrix_matches_behaviour() {
+    let entries = ROLE_GATED
+        .into_iter()
+        .map(|entry| (entry.function, Some(entry.role), entry.pause))
+        .chain(
+            ADMIN_GATED
+                .into_iter()
+                .chain(OWNER_GATED)
+                .map(|entry| (entry.function, None, entry.pause)),
+        )
+        .filter(|(name, _, _)| *name != "upgrade");
+
+    for (name, role, gate) in entries {
+        let s = setup();
+        let outsider = Address::generate(&s.env);
+        let funded = Address::generate(&s.env);
+        let minter = Address::generate(&s.env);
+        s.client.grant_role(&s.admin, &minter, &MINTER_ROLE);
+        s.client.grant_role(&s.admin, &minter, &TRANSFER_ROLE);
+        s.client.mint(&funded, &100, &minter);
+        s.client.propose_transfer(&funded, &minter, &1, &2, &minter);
+        s.env
+            .as_contract(&s.contract_id, || upgradeable::enable_migration(&s.env));
+        let holder = match &role {
+            Some(role) => {
+                let holder = Address::generate(&s.env);
+                s.client.grant_role(&s.admin, &holder, role);
+                holder
+            }
+            None if ADMIN_GATED.iter().any(|entry| entry.function == name) => s.admin.clone(),
+            None => s.owner.clone(),
+        };
+        // Role-gated calls name their caller; owner/admin-only calls are
+        // authorized by whoever signs.
+        let caller = |account: &Address| {
+            if role.is_some() {
+                account.clone()
+            } else {
+                holder.clone()
+            }
+        };
+        let set_paused = |paused: bool| {
+            if paused {
+                s.client.emergency_pause(&symbol_short!("test"), &None);
+            } else {
+                s.client.emergency_unpause();
+            }
+        };
+
+        if gate == PauseGate::Paused {
+            set_paused(true);
+        }
+        let args = matrix_call_args(&s, name, &caller(&outsider), &funded);
+        assert!(
+            try_matrix_call(&s, name, &args, &outsider).is_err(),
+            "{name} let an outsider through"
+        );
+        let args = matrix_call_args(&s, name, &caller(&holder), &funded);
+        if let Err(error) = try_matrix_call(&s, name, &args, &holder) {
+            panic!("{name} rejected its matrix entry: {error:?}");
+        }
+
+        if gate != PauseGate::Any {
+            let blocked = gate == PauseGate::NotPaused;
+            if s.client.is_paused() != blocked {
+                set_paused(blocked);
+            }
+            let expected = match gate {
+                PauseGate::NotPaused => PausableError::EnforcedPause,
+                _ => PausableError::ExpectedPause,
+            };
+            let args = matrix_call_args(&s, name, &caller(&holder), &funded);
+            let error = try_matrix_call(&s, name, &args, &holder).expect_err(name);
+            assert_eq!(
+                error,
+                Error::from(expected),
+                "{name} ignored its pause gate"
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Role Caps
+// ============================================================================
+
+#[test]
+fn role_cap_is_enforced_at_boundary() {
+    let s = setup();
+    let a = Address::generate(&s.env);
+    let b = Address::generate(&s.env);
+    let c = Address::generate(&s.env);
+
+    s.client.set_role_cap(&APPROVER_ROLE, &Some(2));
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleCapSet {
+            role: APPROVER_ROLE,
+            max_members: Some(2),
+            caller: s.admin.clone(),
+        }],
+    );
+    assert_eq!(s.client.get_role_cap(&APPROVER_ROLE), Some(2));
+
+    s.client.grant_role(&s.admin, &a, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &b, &APPROVER_ROLE);
+    assert!(s
+        .client
+        .try_grant_role(&s.admin, &c, &APPROVER_ROLE)
+        .is_err());
+
+    // Re-granting to an existing member does not count against the cap.
+    s.client.grant_role(&s.admin, &a, &APPROVER_ROLE);
+    assert_eq!(s.client.get_role_member_count(&APPROVER_ROLE), 2);
+
+    // Other roles are unaffected.
+    s.client.grant_role(&s.admin, &c, &VIEWER_ROLE);
+}
+
+#[test]
+fn renounce_frees_a_capped_slot() {
+    let s = setup();
+    let a = Address::generate(&s.env);
+    let b = Address::generate(&s.env);
+    s.client.set_role_cap(&APPROVER_ROLE, &Some(1));
+    s.client.grant_role(&s.admin, &a, &APPROVER_ROLE);
+    assert!(s
+        .client
+        .try_grant_role(&s.admin, &b, &APPROVER_ROLE)
+        .is_err());
+
+    s.client.renounce_role(&a, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &b, &APPROVER_ROLE);
+
+    assert_eq!(s.client.get_role_member_count(&APPROVER_ROLE), 1);
+}
+
+#[test]
+fn role_cap_applies_to_batch_grants_and_offers() {
+    let s = setup();
+    let a = Address::generate(&s.env);
+    let b = Address::generate(&s.env);
+    s.client.set_role_cap(&APPROVER_ROLE, &Some(1));
+
+    let grants = vec![
+        &s.env,
+        (a.clone(), APPROVER_ROLE),
+        (b.clone(), APPROVER_ROLE),
+    ];
+    assert!(s.client.try_batch_grant_roles(&grants, &s.admin).is_err());
+    assert_eq!(s.client.get_role_member_count(&APPROVER_ROLE), 0);
+
+    s.client.offer_role(&b, &APPROVER_ROLE, &100, &s.admin);
+    s.client.grant_role(&s.admin, &a, &APPROVER_ROLE);
+    assert!(s.client.try_accept_role(&b, &APPROVER_ROLE).is_err());
+}
+
+#[test]
+fn role_cap_cannot_go_below_member_count() {
+    let s = setup();
+    let a = Address::generate(&s.env);
+    let b = Address::generate(&s.env);
+    let c = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &a, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &b, &APPROVER_ROLE);
+
+    assert!(s.client.try_set_role_cap(&APPROVER_ROLE, &Some(1)).is_err());
+
+    s.client.set_role_cap(&APPROVER_ROLE, &Some(2));
+    s.client.set_role_cap(&APPROVER_ROLE, &None);
+
+    assert_eq!(s.client.get_role_cap(&APPROVER_ROLE), None);
+    s.client.grant_role(&s.admin, &c, &APPROVER_ROLE);
+}
+
+#[test]
+fn roles_without_a_cap_stop_at_max_role_members() {
+    let s = setup();
+    assert!(s
+        .client
+        .try_set_role_cap(&VIEWER_ROLE, &Some(MAX_ROLE_MEMBERS + 1))
+        .is_err());
+
+    for _ in 0..MAX_ROLE_MEMBERS {
+        s.client
+            .grant_role(&s.admin, &Address::generate(&s.env), &VIEWER_ROLE);
+    }
+    assert_eq!(s.client.list_viewers().len(), MAX_ROLE_MEMBERS);
+
+    let extra = Address::generate(&s.env);
+    assert!(s
+        .client
+        .try_grant_role(&s.admin, &extra, &VIEWER_ROLE)
+        .is_err());
+    let grants = vec![&s.env, (extra.clone(), VIEWER_ROLE)];
+    assert!(s.client.try_batch_grant_roles(&grants, &s.admin).is_err());
+    s.client.offer_role(&extra, &VIEWER_ROLE, &100, &s.admin);
+    assert!(s.client.try_accept_role(&extra, &VIEWER_ROLE).is_err());
+    assert_eq!(
+        s.client.get_role_member_count(&VIEWER_ROLE),
+        MAX_ROLE_MEMBERS
+    );
+}
+
+// ============================================================================
+// Batch Role Management
+// ============================================================================
+
+#[test]
+fn batch_grant_roles_emits_per_member_events_and_summary() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+
+    s.client.batch_grant_roles(
+        &vec![
+            &s.env,
+            (alice.clone(), VIEWER_ROLE),
+            (bob.clone(), VIEWER_ROLE),
+            (bob.clone(), PAUSER_ROLE),
+        ],
+        &s.admin,
+    );
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &RoleGranted {
+                role: VIEWER_ROLE,
+                account: alice.clone(),
+                caller: s.admin.clone(),
+            },
+            &RoleGranted {
+                role: VIEWER_ROLE,
+                account: bob.clone(),
+                caller: s.admin.clone(),
+            },
+            &RoleGranted {
+                role: PAUSER_ROLE,
+                account: bob.clone(),
+                caller: s.admin.clone(),
+            },
+            &BatchOperation {
+                operation: symbol_short!("grant"),
+                count: 3,
+                caller: s.admin.clone(),
+            },
+        ],
+    );
+    assert_eq!(s.client.list_viewers(), vec![&s.env, alice, bob.clone()]);
+    assert_eq!(s.client.list_pausers(), vec![&s.env, bob]);
+}
+
+#[test]
+fn batch_revoke_roles_emits_per_member_events_and_summary() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &alice, &VIEWER_ROLE);
+    s.client.grant_role(&s.admin, &alice, &BURNER_ROLE);
+
+    s.client.batch_revoke_roles(
+        &vec![
+            &s.env,
+            (alice.clone(), VIEWER_ROLE),
+            (alice.clone(), BURNER_ROLE),
+        ],
+        &s.admin,
+    );
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &RoleRevoked {
+                role: VIEWER_ROLE,
+                account: alice.clone(),
+                caller: s.admin.clone(),
+            },
+            &RoleRevoked {
+                role: BURNER_ROLE,
+                account: alice.clone(),
+                caller: s.admin.clone(),
+            },
+            &BatchOperation {
+                operation: symbol_short!("revoke"),
+                count: 2,
+                caller: s.admin.clone(),
+            },
+        ],
+    );
+    assert!(s.client.has_role(&alice, &VIEWER_ROLE).is_none());
+    assert!(s.client.has_role(&alice, &BURNER_ROLE).is_none());
+}
+
+#[test]
+fn batch_grant_roles_respects_role_admin_delegation() {
+    let s = setup();
+    let operator = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &operator, &OPERATOR_ROLE);
+
+    s.client.batch_grant_roles(
+        &vec![
+            &s.env,
+            (alice.clone(), MINTER_ROLE),
+            (alice.clone(), BURNER_ROLE),
+        ],
+        &operator,
+    );
+    assert!(s.client.has_role(&alice, &MINTER_ROLE).is_some());
+    assert!(s.client.has_role(&alice, &BURNER_ROLE).is_some());
+}
+
+#[test]
+fn batch_grant_roles_is_atomic() {
+    let s = setup();
+    let operator = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &operator, &OPERATOR_ROLE);
+
+    // OPERATOR administers MINTER but not VIEWER, so the whole batch fails.
+    let result = s.client.try_batch_grant_roles(
+        &vec![
+            &s.env,
+            (alice.clone(), MINTER_ROLE),
+            (alice.clone(), VIEWER_ROLE),
+        ],
+        &operator,
+    );
+
+    assert!(result.is_err());
+    assert!(s.client.has_role(&alice, &MINTER_ROLE).is_none());
+}
+
+#[test]
+fn batch_revoke_roles_is_atomic() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &alice, &VIEWER_ROLE);
+
+    // Bob does not hold VIEWER, so Alice keeps it too.
+    let result = s.client.try_batch_revoke_roles(
+        &vec![&s.env, (alice.clone(), VIEWER_ROLE), (bob, VIEWER_ROLE)],
+        &s.admin,
+    );
+
+    assert!(result.is_err());
+    assert!(s.client.has_role(&alice, &VIEWER_ROLE).is_some());
+}
+
+// ============================================================================
+// Two-Step Role Grants
+// ============================================================================
+
+#[test]
+fn offered_role_is_granted_on_accept() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.env.ledger().set_sequence_number(100);
+
+    s.client.offer_role(&alice, &MINTER_ROLE, &200, &s.admin);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleOffered {
+            role: MINTER_ROLE,
+            account: alice.clone(),
+            caller: s.admin.clone(),
+            live_until_ledger: 200,
+        }],
+    );
+    assert!(s.client.has_role(&alice, &MINTER_ROLE).is_none());
+    assert_eq!(
+        s.client.get_role_offer(&alice, &MINTER_ROLE),
+        Some(RoleOffer {
+            offered_by: s.admin.clone(),
+            live_until_ledger: 200,
+        })
+    );
+
+    s.client.accept_role(&alice, &MINTER_ROLE);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleGranted {
+            role: MINTER_ROLE,
+            account: alice.clone(),
+            caller: s.admin.clone(),
+        }],
+    );
+    assert!(s.client.has_role(&alice, &MINTER_ROLE).is_some());
+    assert_eq!(s.client.get_role_offer(&alice, &MINTER_ROLE), None);
+}
+
+#[test]
+fn accept_role_requires_grantee_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let contract_id = env.register(
+        RbacPlayground,
+        (
+            &admin,
+            &owner,
+            Vec::<(Symbol, Address)>::new(&env),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+    client.offer_role(&alice, &MINTER_ROLE, &100, &admin);
+    client.accept_role(&alice, &MINTER_ROLE);
+
+    assert_authorized_by(
+        &env,
+        &contract_id,
+        &alice,
+        "accept_role",
+        (alice.clone(), MINTER_ROLE).into_val(&env),
+    );
+}
+
+#[test]
+fn cancelled_offer_cannot_be_accepted() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.client.offer_role(&alice, &VIEWER_ROLE, &100, &s.admin);
+
+    s.client.offer_role(&alice, &VIEWER_ROLE, &0, &s.admin);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&RoleOfferCancelled {
+            role: VIEWER_ROLE,
+            account: alice.clone(),
+            caller: s.admin.clone(),
+        }],
+    );
+    assert!(s.client.try_accept_role(&alice, &VIEWER_ROLE).is_err());
+    assert!(s.client.has_role(&alice, &VIEWER_ROLE).is_none());
+}
+
+#[test]
+fn expired_offer_cannot_be_accepted() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.env.ledger().set_sequence_number(100);
+    s.client.offer_role(&alice, &VIEWER_ROLE, &110, &s.admin);
+
+    s.env.ledger().set_sequence_number(111);
+
+    assert!(s.client.try_accept_role(&alice, &VIEWER_ROLE).is_err());
+    assert!(s.client.has_role(&alice, &VIEWER_ROLE).is_none());
+}
+
+#[test]
+fn offer_role_respects_role_admin_delegation() {
+    let s = setup();
+    let operator = Address::generate(&s.env);
+    let alice = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &operator, &OPERATOR_ROLE);
+
+    s.client.offer_role(&alice, &BURNER_ROLE, &100, &operator);
+    assert!(s
+        .client
+        .try_offer_role(&alice, &VIEWER_ROLE, &100, &operator)
+        .is_err());
+
+    // Offers from an operator who has since lost the role are void.
+    s.client.revoke_role(&s.admin, &operator, &OPERATOR_ROLE);
+    assert!(s.client.try_accept_role(&alice, &BURNER_ROLE).is_err());
+}
+
+// ============================================================================
+// Delegation
+// ============================================================================
+
+#[test]
+fn delegate_can_mint_until_expiry() {
+    let s = setup();
+    let minter = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &minter, &MINTER_ROLE);
+    s.env.ledger().set_sequence_number(100);
+
+    s.client
+        .delegate_role(&minter, &MINTER_ROLE, &delegate, &150);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&DelegationCreated {
+            delegator: minter.clone(),
+            delegate: delegate.clone(),
+            role: MINTER_ROLE,
+            until_ledger: 150,
+        }],
+    );
+    s.client.mint(&to, &10, &delegate);
+    assert_eq!(s.client.get_balance(&to), 10);
+    assert!(s.client.has_role(&delegate, &MINTER_ROLE).is_none());
+
+    // Delegation is per role.
+    assert!(s.client.try_burn(&to, &1, &delegate).is_err());
+
+    s.env.ledger().set_sequence_number(151);
+    assert!(s.client.try_mint(&to, &10, &delegate).is_err());
+    assert_eq!(s.client.get_delegation(&minter, &MINTER_ROLE), None);
+}
+
+#[test]
+fn revoked_delegation_stops_delegate() {
+    let s = setup();
+    let viewer = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &viewer, &VIEWER_ROLE);
+    s.client
+        .delegate_role(&viewer, &VIEWER_ROLE, &delegate, &100);
+    s.client.view_sensitive_stats(&delegate);
+
+    s.client.revoke_delegation(&viewer, &VIEWER_ROLE);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&DelegationRevoked {
+            delegator: viewer.clone(),
+            delegate: delegate.clone(),
+            role: VIEWER_ROLE,
+        }],
+    );
+    assert!(s.client.try_view_sensitive_stats(&delegate).is_err());
+}
+
+#[test]
+fn delegation_ends_when_delegator_loses_role() {
+    let s = setup();
+    let burner = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &burner, &BURNER_ROLE);
+    s.client
+        .delegate_role(&burner, &BURNER_ROLE, &delegate, &100);
+
+    s.client.revoke_role(&s.admin, &burner, &BURNER_ROLE);
+
+    assert!(s.client.try_burn(&from, &1, &delegate).is_err());
+}
+
+#[test]
+fn only_holders_can_delegate() {
+    let s = setup();
+    let outsider = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+
+    assert!(s
+        .client
+        .try_delegate_role(&outsider, &MINTER_ROLE, &delegate, &100)
+        .is_err());
+}
+
+#[test]
+fn delegate_approval_counts_for_delegator() {
+    let s = setup();
+    let approver = Address::generate(&s.env);
+    let delegate = Address::generate(&s.env);
+    let proposer = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &proposer, &TRANSFER_ROLE);
+    s.client
+        .delegate_role(&approver, &APPROVER_ROLE, &delegate, &100);
+    let id = s.client.propose_transfer(&from, &to, &10, &2, &proposer);
+
+    s.client.approve_transfer(&id, &delegate);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&TransferApproved {
+            id,
+            approver: approver.clone(),
+            current_approvals: 1,
+            required_approvals: 2,
+            delegate: Some(delegate.clone()),
+        }],
+    );
+    // The holder cannot approve a second time through its delegate.
+    assert!(s.client.try_approve_transfer(&id, &approver).is_err());
+}
+
+// ============================================================================
+// Emergency Revocation
+// ============================================================================
+
+#[test]
+fn revoke_all_roles_strips_every_membership() {
+    let s = setup();
+    let mallory = Address::generate(&s.env);
+    let bystander = Address::generate(&s.env);
+    for role in [VIEWER_ROLE, BURNER_ROLE, APPROVER_ROLE] {
+        s.client.grant_role(&s.admin, &mallory, &role);
+    }
+    s.client.grant_role(&s.admin, &bystander, &VIEWER_ROLE);
+
+    s.client.revoke_all_roles(&mallory);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &RoleRevoked {
+                role: BURNER_ROLE,
+                account: mallory.clone(),
+                caller: s.admin.clone(),
+            },
+            &RoleRevoked {
+                role: VIEWER_ROLE,
+                account: mallory.clone(),
+                caller: s.admin.clone(),
+            },
+            &RoleRevoked {
+                role: APPROVER_ROLE,
+                account: mallory.clone(),
+                caller: s.admin.clone(),
+            },
+            &AccountCompromised {
+                account: mallory.clone(),
+                caller: s.admin.clone(),
+                revoked_roles: vec![&s.env, BURNER_ROLE, VIEWER_ROLE, APPROVER_ROLE],
+                cancelled_transfers: Vec::new(&s.env),
+            },
+        ],
+    );
+    assert_eq!(s.client.list_viewers(), vec![&s.env, bystander]);
+    assert_eq!(s.client.list_burners().len(), 0);
+    assert_eq!(s.client.list_approvers().len(), 0);
+}
+
+#[test]
+fn revoke_all_roles_cancels_proposed_and_approved_transfers() {
+    let s = setup();
+    let mallory = Address::generate(&s.env);
+    let proposer = Address::generate(&s.env);
+    let approver = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &mallory, &TRANSFER_ROLE);
+    s.client.grant_role(&s.admin, &mallory, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &proposer, &TRANSFER_ROLE);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+
+    let by_mallory = s.client.propose_transfer(&from, &to, &10, &2, &mallory);
+    let approved_by_mallory = s.client.propose_transfer(&from, &to, &20, &2, &proposer);
+    s.client.approve_transfer(&approved_by_mallory, &mallory);
+    let untouched = s.client.propose_transfer(&from, &to, &30, &2, &proposer);
+
+    s.client.owner_revoke_all_roles(&mallory);
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&AccountCompromised {
+            account: mallory.clone(),
+            caller: s.owner.clone(),
+            revoked_roles: vec![&s.env, TRANSFER_ROLE, APPROVER_ROLE],
+            cancelled_transfers: vec![&s.env, by_mallory, approved_by_mallory],
+        }],
+    );
+    assert!(s
+        .client
+        .try_approve_transfer(&by_mallory, &approver)
+        .is_err());
+    assert!(s
+        .client
+        .try_approve_transfer(&approved_by_mallory, &approver)
+        .is_err());
+    s.client.approve_transfer(&untouched, &approver);
+}
+
+#[test]
+fn closed_transfers_leave_the_open_transfer_index() {
+    let s = setup();
+    let proposer = Address::generate(&s.env);
+    let first = Address::generate(&s.env);
+    let second = Address::generate(&s.env);
+    let mallory = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &proposer, &TRANSFER_ROLE);
+    for approver in [&first, &second, &mallory] {
+        s.client.grant_role(&s.admin, approver, &APPROVER_ROLE);
+    }
+    s.client.mint(&from, &100, &s.admin);
+
+    let finalized = s.client.propose_transfer(&from, &to, &10, &2, &proposer);
+    let cancelled = s.client.propose_transfer(&from, &to, &20, &3, &proposer);
+    s.client.approve_transfer(&finalized, &first);
+    s.client.approve_transfer(&cancelled, &first);
+    s.client.approve_transfer(&cancelled, &mallory);
+    assert_eq!(
+        s.client.get_open_transfers(&proposer),
+        vec![&s.env, finalized, cancelled]
+    );
+    assert_eq!(
+        s.client.get_open_transfers(&first),
+        vec![&s.env, finalized, cancelled]
+    );
+
+    s.client.approve_transfer(&finalized, &second);
+    assert_eq!(
+        s.client.get_open_transfers(&proposer),
+        vec![&s.env, cancelled]
+    );
+    assert_eq!(s.client.get_open_transfers(&first), vec![&s.env, cancelled]);
+    assert!(s.client.get_open_transfers(&second).is_empty());
+
+    // Cancelling for mallory closes the transfer for everyone on it.
+    s.client.revoke_all_roles(&mallory);
+    for account in [&proposer, &first, &mallory] {
+        assert!(s.client.get_open_transfers(account).is_empty());
+    }
+    s.client.revoke_all_roles(&first);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[&AccountCompromised {
+            account: first.clone(),
+            caller: s.admin.clone(),
+            revoked_roles: vec![&s.env, APPROVER_ROLE],
+            cancelled_transfers: Vec::new(&s.env),
+        }],
+    );
+}
+
+#[test]
+fn open_transfers_are_capped_per_account() {
+    let s = setup();
+    let proposer = Address::generate(&s.env);
+    let approver = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &proposer, &TRANSFER_ROLE);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+    s.client.mint(&from, &100, &s.admin);
+
+    let mut ids = std::vec::Vec::new();
+    for _ in 0..MAX_OPEN_TRANSFERS {
+        ids.push(s.client.propose_transfer(&from, &to, &1, &2, &proposer));
+    }
+    assert!(s
+        .client
+        .try_propose_transfer(&from, &to, &1, &2, &proposer)
+        .is_err());
+
+    // Approvals count for the approver too, until the transfer executes.
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+    let extra = s.client.propose_transfer(&from, &to, &1, &2, &s.admin);
+    for id in &ids {
+        s.client.approve_transfer(id, &approver);
+    }
+    assert!(s.client.try_approve_transfer(&extra, &approver).is_err());
+
+    // Executing a transfer frees a slot for its proposer and approvers.
+    s.client.grant_role(&s.admin, &s.admin, &APPROVER_ROLE);
+    s.client.approve_transfer(&ids[0], &s.admin);
+    s.client.approve_transfer(&extra, &approver);
+    s.client.propose_transfer(&from, &to, &1, &2, &proposer);
+}
+
+#[test]
+#[should_panic(expected = "required_approvals exceeds MAX_REQUIRED_APPROVALS")]
+fn proposal_approvals_are_capped() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+    let to = Address::generate(&s.env);
+
+    s.client
+        .propose_transfer(&s.admin, &to, &0, &(MAX_REQUIRED_APPROVALS + 1), &s.admin);
+}
+
+#[test]
+fn open_transfer_index_is_extended_on_write() {
+    let s = setup();
+    let proposer = Address::generate(&s.env);
+    let approver = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &proposer, &TRANSFER_ROLE);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+
+    let id = s.client.propose_transfer(&proposer, &to, &0, &2, &proposer);
+    s.client.approve_transfer(&id, &approver);
+
+    s.env.as_contract(&s.contract_id, || {
+        for key in [
+            DataKey::OpenTransfers(proposer.clone()),
+            DataKey::OpenTransfers(approver.clone()),
+            DataKey::TransferApprovers(id),
+        ] {
+            assert_eq!(
+                s.env.storage().persistent().get_ttl(&key),
+                INDEX_EXTEND_AMOUNT
+            );
+        }
+    });
+}
+
+// ============================================================================
+// Token Balances
+// ============================================================================
+
+#[test]
+fn burns_and_transfers_cannot_overdraw() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    let bob = Address::generate(&s.env);
+    s.client.mint(&alice, &10, &s.admin);
+    s.client.grant_role(&s.admin, &s.admin, &BURNER_ROLE);
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+
+    assert!(s.client.try_burn(&alice, &11, &s.admin).is_err());
+    assert!(s
+        .client
+        .try_execute_transfer(&alice, &bob, &11, &s.admin)
+        .is_err());
+    // The second burn fails, so the first is rolled back with it.
+    assert!(s
+        .client
+        .try_batch_burn(
+            &vec![&s.env, alice.clone(), alice.clone()],
+            &vec![&s.env, 6, 6],
+            &s.admin
+        )
+        .is_err());
+
+    assert_eq!(s.client.get_balance(&alice), 10);
+    assert_eq!(s.client.get_balance(&bob), 0);
+    assert_eq!(s.client.get_total_supply(), 10);
+}
+
+#[test]
+fn negative_amounts_are_rejected() {
+    let s = setup();
+    let alice = Address::generate(&s.env);
+    s.client.mint(&alice, &10, &s.admin);
+    s.client.grant_role(&s.admin, &s.admin, &BURNER_ROLE);
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+
+    assert!(s.client.try_mint(&alice, &-1, &s.admin).is_err());
+    assert!(s.client.try_burn(&alice, &-1, &s.admin).is_err());
+    assert!(s
+        .client
+        .try_batch_mint(&vec![&s.env, alice.clone()], &vec![&s.env, -1], &s.admin)
+        .is_err());
+    assert!(s
+        .client
+        .try_propose_transfer(&alice, &s.admin, &-1, &1, &s.admin)
+        .is_err());
+    assert_eq!(s.client.get_total_supply(), 10);
+}
+
+#[test]
+#[should_panic(expected = "required_approvals must be at least 1")]
+fn proposal_needs_at_least_one_approval() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+    let to = Address::generate(&s.env);
+
+    s.client.propose_transfer(&s.admin, &to, &0, &0, &s.admin);
+}
+
+#[test]
+fn final_approval_waits_for_balance() {
+    let s = setup();
+    let approver = Address::generate(&s.env);
+    let from = Address::generate(&s.env);
+    let to = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &approver, &APPROVER_ROLE);
+    s.client.grant_role(&s.admin, &s.admin, &TRANSFER_ROLE);
+    let id = s.client.propose_transfer(&from, &to, &10, &1, &s.admin);
+
+    assert!(s.client.try_approve_transfer(&id, &approver).is_err());
+
+    s.client.mint(&from, &10, &s.admin);
+    s.client.approve_transfer(&id, &approver);
+    assert_eq!(s.client.get_balance(&from), 0);
+    assert_eq!(s.client.get_balance(&to), 10);
+}
+
+// ============================================================================
+// Pause
+// ============================================================================
+
+#[test]
+fn pause_records_reason_and_expiry() {
+    let s = setup();
+    let pauser = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &pauser, &PAUSER_ROLE);
+    s.env.ledger().set_sequence_number(100);
+
+    s.client
+        .pause(&pauser, &symbol_short!("incident"), &Some(150));
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &Paused {},
+            &PauseDetails {
+                caller: pauser.clone(),
+                reason: symbol_short!("incident"),
+                until_ledger: Some(150),
+            },
+        ],
+    );
+    assert!(s.client.is_paused());
+    assert_eq!(
+        s.client.get_pause_info(),
+        Some(PauseInfo {
+            paused_by: pauser,
+            paused_at: 100,
+            reason: symbol_short!("incident"),
+            until_ledger: Some(150),
+        })
+    );
+}
+
+#[test]
+fn expired_pause_is_lifted_by_anyone() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+    let to = Address::generate(&s.env);
+    s.env.ledger().set_sequence_number(100);
+
+    s.client
+        .pause(&s.admin, &symbol_short!("upgrade"), &Some(120));
+
+    s.env.ledger().set_sequence_number(120);
+    assert!(s.client.try_lift_expired_pause().is_err());
+    assert!(s.client.try_mint(&to, &10, &s.admin).is_err());
+
+    // Lifting the expired pause only clears it from storage.
+    s.env.ledger().set_sequence_number(121);
+    s.client.lift_expired_pause();
+    assert_last_events(&s.env, &s.contract_id, &[&Unpaused {}]);
+    assert!(!s.client.is_paused());
+    assert_eq!(s.client.get_pause_info(), None);
+    s.client.mint(&to, &10, &s.admin);
+    assert_eq!(s.client.get_balance(&to), 10);
+}
+
+#[test]
+fn expired_pause_stops_blocking_without_being_lifted() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+    let to = Address::generate(&s.env);
+    s.env.ledger().set_sequence_number(100);
+    s.client
+        .pause(&s.admin, &symbol_short!("upgrade"), &Some(120));
+
+    s.env.ledger().set_sequence_number(120);
+    assert!(s.client.is_paused());
+    assert!(s.client.try_mint(&to, &10, &s.admin).is_err());
+
+    s.env.ledger().set_sequence_number(121);
+    s.client.mint(&to, &10, &s.admin);
+    assert_eq!(s.client.get_balance(&to), 10);
+    assert!(!s.client.is_paused());
+    assert_eq!(s.client.get_pause_info(), None);
+    assert!(s.client.can(&s.admin, &Symbol::new(&s.env, "mint")));
+    assert!(s.client.try_unpause(&s.admin).is_err());
+
+    // Pausing again ends the stored, expired pause first.
+    s.client.pause(&s.admin, &symbol_short!("again"), &None);
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &Unpaused {},
+            &Paused {},
+            &PauseDetails {
+                caller: s.admin.clone(),
+                reason: symbol_short!("again"),
+                until_ledger: None,
+            },
+        ],
+    );
+    assert!(s.client.is_paused());
+}
+
+#[test]
+#[should_panic(expected = "Pause has not expired")]
+fn pause_without_expiry_cannot_be_lifted_as_expired() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+    s.client.pause(&s.admin, &symbol_short!("audit"), &None);
+    s.env.ledger().set_sequence_number(1_000_000);
+
+    s.client.lift_expired_pause();
+}
+
+#[test]
+fn pause_without_expiry_lasts_until_unpause() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+
+    s.client.pause(&s.admin, &symbol_short!("audit"), &None);
+    s.env.ledger().set_sequence_number(1_000_000);
+    assert!(s.client.is_paused());
+
+    s.client.unpause(&s.admin);
+    assert!(!s.client.is_paused());
+    assert_eq!(s.client.get_pause_info(), None);
+}
+
+#[test]
+#[should_panic(expected = "until_ledger must not be in the past")]
+fn pause_rejects_expiry_in_the_past() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+    s.env.ledger().set_sequence_number(100);
+
+    s.client.pause(&s.admin, &symbol_short!("late"), &Some(99));
+}
+
+#[test]
+fn emergency_pause_records_owner() {
+    let s = setup();
+
+    s.client.emergency_pause(&symbol_short!("exploit"), &None);
+
+    let info = s.client.get_pause_info().unwrap();
+    assert_eq!(info.paused_by, s.owner);
+    assert_eq!(info.reason, symbol_short!("exploit"));
+    assert_eq!(info.until_ledger, None);
+}
+
+#[test]
+fn emergency_pause_blocks_gated_functions() {
+    let s = setup();
+    let to = Address::generate(&s.env);
+
+    s.client.emergency_pause(&symbol_short!("exploit"), &None);
+
+    assert!(s.client.try_mint(&to, &10, &s.admin).is_err());
+}
+
+#[test]
+fn emergency_pause_extends_an_ongoing_pause() {
+    let s = setup();
+    let pauser = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &pauser, &PAUSER_ROLE);
+    s.env.ledger().set_sequence_number(100);
+    s.client
+        .pause(&pauser, &symbol_short!("incident"), &Some(150));
+    s.env.ledger().set_sequence_number(120);
+
+    s.client
+        .emergency_pause(&symbol_short!("exploit"), &Some(300));
+
+    // Only the details change: no `unpaused`/`paused` pair.
+    let details = PauseDetails {
+        caller: s.owner.clone(),
+        reason: symbol_short!("exploit"),
+        until_ledger: Some(300),
+    };
+    assert_last_events(&s.env, &s.contract_id, &[&details]);
+    assert_eq!(s.env.events().all().len(), 1);
+    assert_eq!(
+        s.client.get_pause_info(),
+        Some(PauseInfo {
+            paused_by: s.owner.clone(),
+            paused_at: 100,
+            reason: symbol_short!("exploit"),
+            until_ledger: Some(300),
+        })
+    );
+
+    s.env.ledger().set_sequence_number(200);
+    assert!(s.client.is_paused());
+    s.client.emergency_pause(&symbol_short!("exploit"), &None);
+    s.env.ledger().set_sequence_number(400);
+    assert!(s.client.is_paused());
+    assert_eq!(s.client.get_pause_info().unwrap().until_ledger, None);
+}
+
+#[test]
+fn emergency_unpause_by_owner() {
+    let s = setup();
+    s.client.grant_role(&s.admin, &s.admin, &PAUSER_ROLE);
+    s.client.pause(&s.admin, &symbol_short!("incident"), &None);
+
+    s.client.emergency_unpause();
+
+    assert_last_events(
+        &s.env,
+        &s.contract_id,
+        &[
+            &Unpaused {},
+            &EmergencyUnpaused {
+                caller: s.owner.clone(),
+            },
+        ],
+    );
+    assert!(!s.client.is_paused());
+    assert_eq!(s.client.get_pause_info(), None);
+}
+
+#[test]
+fn last_pauser_cannot_be_removed_while_paused() {
+    let s = setup();
+    let pauser = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &pauser, &PAUSER_ROLE);
+    s.client.pause(&pauser, &symbol_short!("incident"), &None);
+
+    assert!(s
+        .client
+        .try_revoke_role(&s.admin, &pauser, &PAUSER_ROLE)
+        .is_err());
+    assert!(s.client.try_renounce_role(&pauser, &PAUSER_ROLE).is_err());
+
+    // With a second pauser, one of them may leave.
+    let backup = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &backup, &PAUSER_ROLE);
+    s.client.renounce_role(&pauser, &PAUSER_ROLE);
+    assert_eq!(s.client.list_pausers(), vec![&s.env, backup]);
+}
+
+#[test]
+fn last_pauser_can_be_removed_when_not_paused() {
+    let s = setup();
+    let pauser = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &pauser, &PAUSER_ROLE);
+
+    s.client.revoke_role(&s.admin, &pauser, &PAUSER_ROLE);
+    assert_eq!(s.client.list_pausers().len(), 0);
+}
+
+/// Storage layout before the switch to the `pausable` module.
+#[contracttype]
+enum LegacyDataKey {
+    Paused,
+}
+
+#[test]
+fn legacy_pause_flag_carries_over() {
+    let s = setup();
+    let to = Address::generate(&s.env);
+    s.env.as_contract(&s.contract_id, || {
+        s.env
+            .storage()
+            .instance()
+            .set(&LegacyDataKey::Paused, &true);
+    });
+
+    assert!(s.client.is_paused());
+    assert!(s.client.try_mint(&to, &10, &s.admin).is_err());
+
+    s.client.emergency_unpause();
+    s.env.as_contract(&s.contract_id, || {
+        let paused: bool = s
+            .env
+            .storage()
+            .instance()
+            .get(&LegacyDataKey::Paused)
+            .unwrap();
+        assert!(!paused);
+    });
+}
+
+// ============================================================================
+// Upgrades
+// ============================================================================
+
+/// Stores `count` pending transfers in the version-1 layout.
+fn seed_v1_storage(s: &Setup, count: u64) -> std::vec::Vec<PendingTransferV1> {
+    let transfers: std::vec::Vec<_> = (0..count)
+        .map(|id| PendingTransferV1 {
+            id,
+            from: Address::generate(&s.env),
+            to: Address::generate(&s.env),
+            amount: 25,
+            approvals: 1,
+            required_approvals: 2,
+            executed: false,
+        })
+        .collect();
+    s.env.as_contract(&s.contract_id, || {
+        let storage = s.env.storage().instance();
+        storage.remove(&DataKey::StorageVersion);
+        for old in &transfers {
+            storage.set(&DataKey::PendingTransfer(old.id), old);
+        }
+        storage.set(&DataKey::PendingTransferCounter, &count);
+    });
+    transfers
+}
+
+fn migrated(old: PendingTransferV1, contract_id: &Address) -> PendingTransfer {
+    PendingTransfer {
+        id: old.id,
+        from: old.from,
+        to: old.to,
+        amount: old.amount,
+        approvals: old.approvals,
+        required_approvals: old.required_approvals,
+        executed: old.executed,
+        proposer: contract_id.clone(),
+        cancelled: false,
+    }
+}
+
+#[test]
+fn new_deployment_has_current_storage_version() {
+    let s = setup();
+
+    assert_eq!(s.client.get_storage_version(), STORAGE_VERSION);
+}
+
+#[test]
+fn migrate_requires_upgrade() {
+    let s = setup();
+
+    assert!(s.client.try_migrate().is_err());
+}
+
+#[test]
+fn migrate_converts_v1_pending_transfers() {
+    let s = setup();
+    let viewer = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &viewer, &VIEWER_ROLE);
+    let old = seed_v1_storage(&s, 1).remove(0);
+    s.env.as_contract(&s.contract_id, || {
+        stellar_contract_utils::upgradeable::enable_migration(&s.env);
+    });
+
+    s.client.migrate();
+
+    assert_eq!(s.client.get_storage_version(), STORAGE_VERSION);
+    assert_eq!(
+        s.client.view_pending_transfer(&0, &viewer),
+        migrated(old, &s.contract_id)
+    );
+}
+
+#[test]
+fn migrate_resumes_where_the_last_batch_stopped() {
+    let s = setup();
+    let viewer = Address::generate(&s.env);
+    s.client.grant_role(&s.admin, &viewer, &VIEWER_ROLE);
+    let old = seed_v1_storage(&s, MIGRATION_BATCH_SIZE + 1);
+    s.env.as_contract(&s.contract_id, || {
+        stellar_contract_utils::upgradeable::enable_migration(&s.env);
+    });
+
+    assert!(!s.client.migrate());
+
+    assert_eq!(s.client.get_storage_version(), 1);
+    let last = MIGRATION_BATCH_SIZE - 1;
+    assert_eq!(
+        s.client.view_pending_transfer(&last, &viewer),
+        migrated(old[last as usize].clone(), &s.contract_id)
+    );
+    assert!(s
+        .client
+        .try_view_pending_transfer(&MIGRATION_BATCH_SIZE, &viewer)
+        .is_err());
+
+    assert!(s.client.migrate());
+
+    assert_eq!(s.client.get_storage_version(), STORAGE_VERSION);
+    for old in old {
+        assert_eq!(
+            s.client.view_pending_transfer(&old.id, &viewer),
+            migrated(old, &s.contract_id)
+        );
+    }
+    assert!(s.client.try_migrate().is_err());
+}
+
+// ============================================================================
+// Event Topics
+// ============================================================================
+
+/// Topic layout of every custom event, as seen by RPC `getEvents` filters.
+#[test]
+fn event_topic_layout() {
+    let env = Env::default();
+    let a = Address::generate(&env);
+    let b = Address::generate(&env);
+    let c = Address::generate(&env);
+    let name = |name: &str| Symbol::new(&env, name);
+    let assert_topics = |event: &dyn Event, expected: Vec<soroban_sdk::Val>| {
+        assert_eq!(event.topics(&env), expected);
+    };
+
+    assert_topics(
+        &Initialized {
+            admin: a.clone(),
+            owner: b.clone(),
+            initial_roles: Vec::new(&env),
+            version: (0, 1, 0),
+            capabilities: Vec::new(&env),
+        },
+        (name("initialized"),).into_val(&env),
+    );
+    assert_topics(
+        &Minted {
+            to: a.clone(),
+            amount: 1,
+            caller: b.clone(),
+        },
+        (name("minted"), a.clone(), b.clone()).into_val(&env),
+    );
+    assert_topics(
+        &Burned {
+            from: a.clone(),
+            amount: 1,
+            caller: b.clone(),
+        },
+        (name("burned"), a.clone(), b.clone()).into_val(&env),
+    );
+    assert_topics(
+        &PauseDetails {
+            caller: a.clone(),
+            reason: symbol_short!("incident"),
+            until_ledger: None,
+        },
+        (name("pause_details"), a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &EmergencyUnpaused { caller: a.clone() },
+        (name("emergency_unpaused"), a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &TransferExecuted {
+            from: a.clone(),
+            to: b.clone(),
+            amount: 1,
+            caller: c.clone(),
+        },
+        (name("transfer_executed"), a.clone(), b.clone(), c.clone()).into_val(&env),
+    );
+    assert_topics(
+        &BatchOperation {
+            operation: symbol_short!("mint"),
+            count: 2,
+            caller: a.clone(),
+        },
+        (name("batch_operation"), symbol_short!("mint"), a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &TransferProposed {
+            id: 7,
+            from: a.clone(),
+            to: b.clone(),
+            amount: 1,
+            proposer: c.clone(),
+        },
+        (name("transfer_proposed"), 7u64, a.clone(), b.clone()).into_val(&env),
+    );
+    assert_topics(
+        &TransferApproved {
+            id: 7,
+            approver: a.clone(),
+            current_approvals: 1,
+            required_approvals: 2,
+            delegate: None,
+        },
+        (name("transfer_approved"), 7u64, a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &TransferFinalized {
+            id: 7,
+            from: a.clone(),
+            to: b.clone(),
+            amount: 1,
+        },
+        (name("transfer_finalized"), 7u64, a.clone(), b.clone()).into_val(&env),
+    );
+    assert_topics(
+        &RoleCapSet {
+            role: APPROVER_ROLE,
+            max_members: Some(2),
+            caller: a.clone(),
+        },
+        (name("role_cap_set"), APPROVER_ROLE).into_val(&env),
+    );
+    assert_topics(
+        &RoleOffered {
+            role: MINTER_ROLE,
+            account: a.clone(),
+            caller: b.clone(),
+            live_until_ledger: 100,
+        },
+        (name("role_offered"), MINTER_ROLE, a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &RoleOfferCancelled {
+            role: MINTER_ROLE,
+            account: a.clone(),
+            caller: b.clone(),
+        },
+        (name("role_offer_cancelled"), MINTER_ROLE, a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &DelegationCreated {
+            delegator: a.clone(),
+            delegate: b.clone(),
+            role: MINTER_ROLE,
+            until_ledger: 100,
+        },
+        (
+            name("delegation_created"),
+            a.clone(),
+            b.clone(),
+            MINTER_ROLE,
+        )
+            .into_val(&env),
+    );
+    assert_topics(
+        &DelegationRevoked {
+            delegator: a.clone(),
+            delegate: b.clone(),
+            role: MINTER_ROLE,
+        },
+        (
+            name("delegation_revoked"),
+            a.clone(),
+            b.clone(),
+            MINTER_ROLE,
+        )
+            .into_val(&env),
+    );
+    assert_topics(
+        &AccountCompromised {
+            account: a.clone(),
+            caller: b.clone(),
+            revoked_roles: Vec::new(&env),
+            cancelled_transfers: Vec::new(&env),
+        },
+        (name("account_compromised"), a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &Upgraded {
+            new_wasm_hash: soroban_sdk::BytesN::from_array(&env, &[0; 32]),
+            caller: a.clone(),
+        },
+        (name("upgraded"), a.clone()).into_val(&env),
+    );
+    assert_topics(
+        &SensitiveDataAccessed {
+            data_type: symbol_short!("stats"),
+            viewer: a.clone(),
+        },
+        (name("sensitive_data_accessed"), a).into_val(&env),
+    );
+}
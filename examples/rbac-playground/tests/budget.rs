@@ -0,0 +1,439 @@
+//! Resource-budget regression tests for the calls that loop over
+//! caller-controlled sizes: the batch entrypoints, role enumeration up to
+//! `MAX_ROLE_MEMBERS`, `revoke_all_roles` with the most open transfers it can
+//! cancel, and a `mint` with many holders.
+//!
+//! Each run is compared with `budget_baseline.txt` and fails when a call uses
+//! more than recorded. After an intended change, rewrite the baseline with
+//! `UPDATE_BUDGET_BASELINE=1 cargo test -p rbac-playground --test budget`.
+//!
+//! Ledger entries and bytes are deterministic and always compared.
+//! Instructions and memory depend on the wasm rustc/LLVM emit, so they are
+//! compared only when the toolchain matches the one in the baseline's header.
+//!
+//! The contract is registered from its wasm, built from source by
+//! `rbac_test_support::wasm`, so instructions and memory include Wasm
+//! execution as on the network.
+
+use std::fmt::Write as _;
+use std::process::Command;
+
+use rbac_playground::{
+    RbacPlaygroundClient, MAX_BATCH_SIZE, MAX_OPEN_TRANSFERS, MAX_REQUIRED_APPROVALS,
+    MAX_ROLE_MEMBERS,
+};
+use soroban_sdk::{testutils::Address as _, Address, Env, Symbol, Vec};
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/budget_baseline.txt");
+
+/// Header line of the baseline naming the toolchain it was recorded with.
+const TOOLCHAIN_HEADER: &str = "# toolchain: ";
+
+const BATCH_SIZES: [u32; 4] = [1, 5, 10, MAX_BATCH_SIZE];
+const ROLE_SIZES: [u32; 5] = [1, 10, 25, 50, MAX_ROLE_MEMBERS];
+const HOLDER_COUNTS: [u32; 3] = [0, 100, 400];
+const OPEN_TRANSFER_COUNTS: [u32; 2] = [1, MAX_OPEN_TRANSFERS];
+
+/// Allowed growth of the metered instructions and memory over a baseline
+/// recorded with the same toolchain, in percent. Entries and bytes must not
+/// grow at all.
+const COST_TOLERANCE: i64 = 10;
+
+// Mainnet's per-transaction limits when the baseline was recorded.
+const TX_MAX_INSTRUCTIONS: i64 = 100_000_000;
+const TX_MAX_MEMORY_BYTES: i64 = 41_943_040;
+const TX_MAX_READ_ENTRIES: u32 = 100;
+const TX_MAX_WRITE_ENTRIES: u32 = 50;
+const TX_MAX_WRITE_BYTES: u32 = 132_096;
+const TX_MAX_CONTRACT_EVENTS_SIZE_BYTES: u32 = 16_384;
+
+#[derive(Debug, PartialEq)]
+struct Measurement {
+    /// `<call>/<size>`, e.g. `batch_mint/20`.
+    scenario: String,
+    instructions: i64,
+    mem_bytes: i64,
+    read_entries: u32,
+    write_entries: u32,
+    write_bytes: u32,
+    events_bytes: u32,
+}
+
+impl Measurement {
+    /// The resources of the last invocation in `env`.
+    fn last(env: &Env, call: &str, size: u32) -> Self {
+        let resources = env.cost_estimate().resources();
+        Measurement {
+            scenario: format!("{call}/{size}"),
+            instructions: resources.instructions,
+            mem_bytes: resources.mem_bytes,
+            read_entries: resources.disk_read_entries + resources.memory_read_entries,
+            write_entries: resources.write_entries,
+            write_bytes: resources.write_bytes,
+            events_bytes: resources.contract_events_size_bytes,
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let scenario = fields.next()?.to_string();
+        let mut next = || fields.next()?.parse::<i64>().ok();
+        Some(Measurement {
+            scenario,
+            instructions: next()?,
+            mem_bytes: next()?,
+            read_entries: next()?.try_into().ok()?,
+            write_entries: next()?.try_into().ok()?,
+            write_bytes: next()?.try_into().ok()?,
+            events_bytes: next()?.try_into().ok()?,
+        })
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "{:<24} {:>12} {:>10} {:>12} {:>13} {:>11} {:>12}",
+            self.scenario,
+            self.instructions,
+            self.mem_bytes,
+            self.read_entries,
+            self.write_entries,
+            self.write_bytes,
+            self.events_bytes
+        )
+    }
+
+    fn limit_violations(&self) -> std::vec::Vec<String> {
+        let checks = [
+            ("instructions", self.instructions, TX_MAX_INSTRUCTIONS),
+            ("mem_bytes", self.mem_bytes, TX_MAX_MEMORY_BYTES),
+            (
+                "read_entries",
+                self.read_entries.into(),
+                TX_MAX_READ_ENTRIES.into(),
+            ),
+            (
+                "write_entries",
+                self.write_entries.into(),
+                TX_MAX_WRITE_ENTRIES.into(),
+            ),
+            (
+                "write_bytes",
+                self.write_bytes.into(),
+                TX_MAX_WRITE_BYTES.into(),
+            ),
+            (
+                "events_bytes",
+                self.events_bytes.into(),
+                TX_MAX_CONTRACT_EVENTS_SIZE_BYTES.into(),
+            ),
+        ];
+        checks
+            .iter()
+            .filter(|(_, value, limit)| value > limit)
+            .map(|(name, value, limit)| {
+                format!(
+                    "{}: {name} {value} exceeds the limit of {limit}",
+                    self.scenario
+                )
+            })
+            .collect()
+    }
+
+    /// Where `self` uses more than `baseline`; instructions and memory only
+    /// if `same_toolchain`.
+    fn regressions(&self, baseline: &Measurement, same_toolchain: bool) -> std::vec::Vec<String> {
+        let with_tolerance = |value: i64| {
+            if same_toolchain {
+                value + value * COST_TOLERANCE / 100
+            } else {
+                i64::MAX
+            }
+        };
+        let checks = [
+            (
+                "instructions",
+                self.instructions,
+                with_tolerance(baseline.instructions),
+            ),
+            (
+                "mem_bytes",
+                self.mem_bytes,
+                with_tolerance(baseline.mem_bytes),
+            ),
+            (
+                "read_entries",
+                self.read_entries.into(),
+                baseline.read_entries.into(),
+            ),
+            (
+                "write_entries",
+                self.write_entries.into(),
+                baseline.write_entries.into(),
+            ),
+            (
+                "write_bytes",
+                self.write_bytes.into(),
+                baseline.write_bytes.into(),
+            ),
+            (
+                "events_bytes",
+                self.events_bytes.into(),
+                baseline.events_bytes.into(),
+            ),
+        ];
+        checks
+            .iter()
+            .filter(|(_, value, allowed)| value > allowed)
+            .map(|(name, value, allowed)| {
+                format!(
+                    "{}: {name} {value} is above the baseline's {allowed}",
+                    self.scenario
+                )
+            })
+            .collect()
+    }
+}
+
+struct Playground {
+    env: Env,
+    admin: Address,
+    client: RbacPlaygroundClient<'static>,
+}
+
+impl Playground {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let contract_id = env.register(
+            rbac_test_support::wasm("rbac-playground"),
+            (
+                &admin,
+                &admin,
+                Vec::<(Symbol, Address)>::new(&env),
+                None::<Vec<(Address, i128)>>,
+            ),
+        );
+        let client = RbacPlaygroundClient::new(&env, &contract_id);
+        Playground { env, admin, client }
+    }
+
+    fn accounts(&self, count: u32) -> Vec<Address> {
+        let mut accounts = Vec::new(&self.env);
+        for _ in 0..count {
+            accounts.push_back(Address::generate(&self.env));
+        }
+        accounts
+    }
+
+    fn amounts(&self, count: u32) -> Vec<i128> {
+        let mut amounts = Vec::new(&self.env);
+        for _ in 0..count {
+            amounts.push_back(100);
+        }
+        amounts
+    }
+
+    fn pairs(&self, accounts: &Vec<Address>, role: &str) -> Vec<(Address, Symbol)> {
+        let mut pairs = Vec::new(&self.env);
+        for account in accounts.iter() {
+            pairs.push_back((account, Symbol::new(&self.env, role)));
+        }
+        pairs
+    }
+
+    /// Mints to `count` new holders, `MAX_BATCH_SIZE` at a time.
+    fn add_holders(&self, count: u32) {
+        let mut added = 0;
+        while added < count {
+            let size = MAX_BATCH_SIZE.min(count - added);
+            self.client
+                .batch_mint(&self.accounts(size), &self.amounts(size), &self.admin);
+            added += size;
+        }
+    }
+}
+
+fn measure_batches() -> std::vec::Vec<Measurement> {
+    let mut measurements = std::vec::Vec::new();
+    for size in BATCH_SIZES {
+        let p = Playground::new();
+        let accounts = p.accounts(size);
+        let amounts = p.amounts(size);
+        p.client.batch_mint(&accounts, &amounts, &p.admin);
+        measurements.push(Measurement::last(&p.env, "batch_mint", size));
+        p.client.batch_burn(&accounts, &amounts, &p.admin);
+        measurements.push(Measurement::last(&p.env, "batch_burn", size));
+
+        let grants = p.pairs(&accounts, "viewer");
+        p.client.batch_grant_roles(&grants, &p.admin);
+        measurements.push(Measurement::last(&p.env, "batch_grant_roles", size));
+        p.client.batch_revoke_roles(&grants, &p.admin);
+        measurements.push(Measurement::last(&p.env, "batch_revoke_roles", size));
+    }
+    measurements
+}
+
+fn measure_role_enumeration() -> std::vec::Vec<Measurement> {
+    let mut measurements = std::vec::Vec::new();
+    for size in ROLE_SIZES {
+        let p = Playground::new();
+        let mut granted = 0;
+        while granted < size {
+            let batch = MAX_BATCH_SIZE.min(size - granted);
+            p.client
+                .batch_grant_roles(&p.pairs(&p.accounts(batch), "viewer"), &p.admin);
+            granted += batch;
+        }
+        assert_eq!(p.client.list_viewers().len(), size);
+        measurements.push(Measurement::last(&p.env, "list_role_members", size));
+    }
+    measurements
+}
+
+/// `revoke_all_roles` on an account holding every role, with open
+/// transfers it proposed that each lack one approval from distinct approvers.
+fn measure_revocation() -> std::vec::Vec<Measurement> {
+    const ROLES: [&str; 7] = [
+        "operator", "minter", "burner", "pauser", "viewer", "transfer", "approver",
+    ];
+    let mut measurements = std::vec::Vec::new();
+    for open in OPEN_TRANSFER_COUNTS {
+        let p = Playground::new();
+        let account = Address::generate(&p.env);
+        for role in ROLES {
+            p.client
+                .grant_role(&p.admin, &account, &Symbol::new(&p.env, role));
+        }
+        for _ in 0..open {
+            let id = p.client.propose_transfer(
+                &p.admin,
+                &account,
+                &1,
+                &MAX_REQUIRED_APPROVALS,
+                &account,
+            );
+            let approvers = p.accounts(MAX_REQUIRED_APPROVALS - 1);
+            p.client
+                .batch_grant_roles(&p.pairs(&approvers, "approver"), &p.admin);
+            for approver in approvers.iter() {
+                p.client.approve_transfer(&id, &approver);
+            }
+        }
+        p.client.revoke_all_roles(&account);
+        measurements.push(Measurement::last(&p.env, "revoke_all_roles", open));
+    }
+    measurements
+}
+
+/// A single `mint` once many accounts hold balances. Each balance is an entry
+/// of its own, so the cost should not grow with the holders.
+fn measure_instance_growth() -> std::vec::Vec<Measurement> {
+    let mut measurements = std::vec::Vec::new();
+    for holders in HOLDER_COUNTS {
+        let p = Playground::new();
+        p.add_holders(holders);
+        p.client.mint(&p.admin, &1, &p.admin);
+        measurements.push(Measurement::last(&p.env, "mint_with_holders", holders));
+    }
+    measurements
+}
+
+/// `rustc --version` of the toolchain building the wasm.
+fn toolchain() -> String {
+    let output = Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+        .arg("--version")
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn baseline_file(measurements: &[Measurement]) -> String {
+    let mut file = format!(
+        "# Generated by tests/budget.rs; see its docs before editing.\n\
+         {TOOLCHAIN_HEADER}{}\n\
+         # scenario               instructions  mem_bytes read_entries write_entries write_bytes events_bytes\n",
+        toolchain()
+    );
+    for measurement in measurements {
+        writeln!(file, "{}", measurement.line()).unwrap();
+    }
+    file
+}
+
+#[test]
+fn resources_stay_within_limits_and_baseline() {
+    let mut measurements = measure_batches();
+    measurements.extend(measure_role_enumeration());
+    measurements.extend(measure_revocation());
+    measurements.extend(measure_instance_growth());
+
+    let violations: std::vec::Vec<String> = measurements
+        .iter()
+        .flat_map(Measurement::limit_violations)
+        .collect();
+    assert!(violations.is_empty(), "{}", violations.join("\n"));
+
+    if std::env::var_os("UPDATE_BUDGET_BASELINE").is_some() {
+        std::fs::write(BASELINE, baseline_file(&measurements)).unwrap();
+        return;
+    }
+    let recorded = std::fs::read_to_string(BASELINE).unwrap();
+    let same_toolchain = recorded
+        .lines()
+        .find_map(|line| line.strip_prefix(TOOLCHAIN_HEADER))
+        .is_some_and(|recorded_with| recorded_with == toolchain());
+    let baseline: std::vec::Vec<Measurement> = recorded
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| Measurement::parse(line).unwrap_or_else(|| panic!("bad line: {line}")))
+        .collect();
+    let mut problems = std::vec::Vec::new();
+    for measurement in &measurements {
+        match baseline.iter().find(|b| b.scenario == measurement.scenario) {
+            Some(recorded) => problems.extend(measurement.regressions(recorded, same_toolchain)),
+            None => problems.push(format!("{}: not in the baseline", measurement.scenario)),
+        }
+    }
+    assert!(
+        problems.is_empty(),
+        "{}\nrerun with UPDATE_BUDGET_BASELINE=1 if this is intended",
+        problems.join("\n")
+    );
+}
+
+#[test]
+fn batches_over_max_batch_size_are_rejected() {
+    let p = Playground::new();
+    let accounts = p.accounts(MAX_BATCH_SIZE + 1);
+    let amounts = p.amounts(MAX_BATCH_SIZE + 1);
+    let grants = p.pairs(&accounts, "viewer");
+
+    assert!(p
+        .client
+        .try_batch_mint(&accounts, &amounts, &p.admin)
+        .is_err());
+    assert!(p.client.try_batch_grant_roles(&grants, &p.admin).is_err());
+
+    // The same entries go through in two allowed batches, but cannot be
+    // burned or revoked in one.
+    let (head, tail) = (
+        accounts.slice(..MAX_BATCH_SIZE),
+        accounts.slice(MAX_BATCH_SIZE..),
+    );
+    for part in [head, tail] {
+        p.client.batch_mint(&part, &p.amounts(part.len()), &p.admin);
+        p.client
+            .batch_grant_roles(&p.pairs(&part, "viewer"), &p.admin);
+    }
+    assert!(p
+        .client
+        .try_batch_burn(&accounts, &amounts, &p.admin)
+        .is_err());
+    assert!(p.client.try_batch_revoke_roles(&grants, &p.admin).is_err());
+    assert_eq!(
+        p.client.get_total_supply(),
+        100 * i128::from(MAX_BATCH_SIZE + 1)
+    );
+    assert_eq!(p.client.list_viewers().len(), MAX_BATCH_SIZE + 1);
+}
@@ -0,0 +1,259 @@
+//! Property tests over arbitrary sequences of token calls.
+//!
+//! proptest shrinks a failing sequence to a minimal one and records its seed
+//! in `token_invariants.proptest-regressions`, which is replayed first on the
+//! next run; check it in with the fix.
+
+use proptest::prelude::*;
+use rbac_playground::{RbacPlayground, RbacPlaygroundClient};
+use soroban_sdk::{
+    testutils::Address as _, testutils::Events as _, Address, Env, Map, Symbol, TryFromVal, Val,
+    Vec,
+};
+
+const HOLDERS: usize = 3;
+const APPROVERS: usize = 3;
+
+#[derive(Clone, Debug)]
+enum Call {
+    Mint {
+        to: usize,
+        amount: i128,
+    },
+    Burn {
+        from: usize,
+        amount: i128,
+    },
+    BatchMint {
+        entries: std::vec::Vec<(usize, i128)>,
+    },
+    BatchBurn {
+        entries: std::vec::Vec<(usize, i128)>,
+    },
+    ExecuteTransfer {
+        from: usize,
+        to: usize,
+        amount: i128,
+    },
+    ProposeTransfer {
+        from: usize,
+        to: usize,
+        amount: i128,
+        required_approvals: u32,
+    },
+    ApproveTransfer {
+        proposal: prop::sample::Index,
+        approver: usize,
+    },
+}
+
+fn holder() -> impl Strategy<Value = usize> {
+    0..HOLDERS
+}
+
+/// Mostly valid amounts, with the odd negative one.
+fn amount() -> impl Strategy<Value = i128> {
+    prop_oneof![9 => 0..=1_000i128, 1 => -100..0i128]
+}
+
+fn entries() -> impl Strategy<Value = std::vec::Vec<(usize, i128)>> {
+    prop::collection::vec((holder(), amount()), 0..4)
+}
+
+fn call() -> impl Strategy<Value = Call> {
+    prop_oneof![
+        (holder(), amount()).prop_map(|(to, amount)| Call::Mint { to, amount }),
+        (holder(), amount()).prop_map(|(from, amount)| Call::Burn { from, amount }),
+        entries().prop_map(|entries| Call::BatchMint { entries }),
+        entries().prop_map(|entries| Call::BatchBurn { entries }),
+        (holder(), holder(), amount()).prop_map(|(from, to, amount)| Call::ExecuteTransfer {
+            from,
+            to,
+            amount
+        }),
+        (holder(), holder(), amount(), 0..=APPROVERS as u32).prop_map(
+            |(from, to, amount, required_approvals)| Call::ProposeTransfer {
+                from,
+                to,
+                amount,
+                required_approvals,
+            }
+        ),
+        (any::<prop::sample::Index>(), 0..APPROVERS)
+            .prop_map(|(proposal, approver)| Call::ApproveTransfer { proposal, approver }),
+    ]
+}
+
+/// A playground with one account per token role and a few holders.
+struct Token {
+    env: Env,
+    contract_id: Address,
+    client: RbacPlaygroundClient<'static>,
+    minter: Address,
+    burner: Address,
+    operator: Address,
+    transferer: Address,
+    approvers: std::vec::Vec<Address>,
+    holders: std::vec::Vec<Address>,
+}
+
+impl Token {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let contract_id = env.register(
+            RbacPlayground,
+            (
+                &admin,
+                &owner,
+                Vec::<(Symbol, Address)>::new(&env),
+                None::<Vec<(Address, i128)>>,
+            ),
+        );
+        let client = RbacPlaygroundClient::new(&env, &contract_id);
+        let grant = |role: &str| {
+            let account = Address::generate(&env);
+            client.grant_role(&admin, &account, &Symbol::new(&env, role));
+            account
+        };
+
+        Token {
+            minter: grant("minter"),
+            burner: grant("burner"),
+            operator: grant("operator"),
+            transferer: grant("transfer"),
+            approvers: (0..APPROVERS).map(|_| grant("approver")).collect(),
+            holders: (0..HOLDERS).map(|_| Address::generate(&env)).collect(),
+            env,
+            contract_id,
+            client,
+        }
+    }
+
+    fn batch(&self, entries: &[(usize, i128)]) -> (Vec<Address>, Vec<i128>) {
+        let mut accounts = Vec::new(&self.env);
+        let mut amounts = Vec::new(&self.env);
+        for (holder, amount) in entries {
+            accounts.push_back(self.holders[*holder].clone());
+            amounts.push_back(*amount);
+        }
+        (accounts, amounts)
+    }
+
+    /// The data of the events named `name` published by the last invocation.
+    fn last_events(&self, name: Symbol) -> std::vec::Vec<Map<Symbol, Val>> {
+        self.env
+            .events()
+            .all()
+            .iter()
+            .filter(|(contract, topics, _)| {
+                *contract == self.contract_id
+                    && topics
+                        .first()
+                        .and_then(|topic| Symbol::try_from_val(&self.env, &topic).ok())
+                        == Some(name.clone())
+            })
+            .map(|(_, _, data)| Map::try_from_val(&self.env, &data).unwrap())
+            .collect()
+    }
+
+    fn field(&self, data: &Map<Symbol, Val>, field: &str) -> u32 {
+        u32::try_from_val(&self.env, &data.get(Symbol::new(&self.env, field)).unwrap()).unwrap()
+    }
+}
+
+fn run(calls: &[Call]) -> Result<(), TestCaseError> {
+    let t = Token::new();
+    let mut proposals = std::vec::Vec::new();
+    let mut finalized = std::collections::BTreeSet::new();
+
+    for call in calls {
+        match call {
+            Call::Mint { to, amount } => {
+                let _ = t.client.try_mint(&t.holders[*to], amount, &t.minter);
+            }
+            Call::Burn { from, amount } => {
+                let _ = t.client.try_burn(&t.holders[*from], amount, &t.burner);
+            }
+            Call::BatchMint { entries } => {
+                let (recipients, amounts) = t.batch(entries);
+                let _ = t.client.try_batch_mint(&recipients, &amounts, &t.operator);
+            }
+            Call::BatchBurn { entries } => {
+                let (accounts, amounts) = t.batch(entries);
+                let _ = t.client.try_batch_burn(&accounts, &amounts, &t.operator);
+            }
+            Call::ExecuteTransfer { from, to, amount } => {
+                let _ = t.client.try_execute_transfer(
+                    &t.holders[*from],
+                    &t.holders[*to],
+                    amount,
+                    &t.transferer,
+                );
+            }
+            Call::ProposeTransfer {
+                from,
+                to,
+                amount,
+                required_approvals,
+            } => {
+                if let Ok(Ok(id)) = t.client.try_propose_transfer(
+                    &t.holders[*from],
+                    &t.holders[*to],
+                    amount,
+                    required_approvals,
+                    &t.transferer,
+                ) {
+                    proposals.push(id);
+                }
+            }
+            Call::ApproveTransfer { proposal, approver } => {
+                if proposals.is_empty() {
+                    continue;
+                }
+                let id = *proposal.get(&proposals);
+                if t.client
+                    .try_approve_transfer(&id, &t.approvers[*approver])
+                    .is_ok()
+                {
+                    let approved = t.last_events(Symbol::new(&t.env, "transfer_approved"));
+                    let finalizations = t.last_events(Symbol::new(&t.env, "transfer_finalized"));
+                    prop_assert_eq!(approved.len(), 1);
+                    let current = t.field(&approved[0], "current_approvals");
+                    let required = t.field(&approved[0], "required_approvals");
+
+                    // Finalized exactly when this approval reaches the threshold.
+                    prop_assert_eq!(finalizations.len(), usize::from(current == required));
+                    if !finalizations.is_empty() {
+                        prop_assert!(finalized.insert(id), "transfer {} executed twice", id);
+                    }
+                }
+            }
+        }
+
+        let balances: std::vec::Vec<i128> = t
+            .holders
+            .iter()
+            .map(|holder| t.client.get_balance(holder))
+            .collect();
+        prop_assert!(
+            balances.iter().all(|balance| *balance >= 0),
+            "negative balance: {:?}",
+            balances
+        );
+        prop_assert_eq!(t.client.get_total_supply(), balances.iter().sum::<i128>());
+    }
+    Ok(())
+}
+
+proptest! {
+    // 64 sequences by default; `PROPTEST_CASES` still overrides it.
+    #![proptest_config(ProptestConfig { cases: 64, ..ProptestConfig::default() })]
+
+    #[test]
+    fn token_invariants_hold(calls in prop::collection::vec(call(), 1..40)) {
+        run(&calls)?;
+    }
+}
@@ -0,0 +1,181 @@
+//! Upgrades deployed wasm to this contract's wasm, both built from source:
+//! from the storage-version-1 build in `playground-v1`, and from this build
+//! onto itself.
+//!
+//! `playground-v1` is the original contract with an `upgrade` entrypoint
+//! added. The original has none, so its deployments cannot be upgraded and
+//! must be redeployed; the v1 test covers the migration code only.
+
+use rbac_playground::{DataKey, PendingTransfer, RbacPlaygroundClient, Upgraded, STORAGE_VERSION};
+use rbac_playground_v1::RbacPlaygroundV1Client;
+use rbac_test_support::{assert_last_events, sandbox, wasm};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, MockAuth, MockAuthInvoke},
+    Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec,
+};
+
+fn upload(env: &Env, package: &str) -> BytesN<32> {
+    env.deployer()
+        .upload_contract_wasm(Bytes::from_slice(env, wasm(package)))
+}
+
+#[test]
+fn upgrade_from_v1_and_migrate_its_storage() {
+    let env = sandbox();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let proposer = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let contract_id = env.register(wasm("rbac-playground-v1"), (&admin, &owner));
+
+    let v1 = RbacPlaygroundV1Client::new(&env, &contract_id);
+    v1.grant_role(&admin, &proposer, &symbol_short!("transfer"));
+    v1.grant_role(&admin, &viewer, &symbol_short!("viewer"));
+    v1.mint(&from, &100, &admin);
+    let id = v1.propose_transfer(&from, &to, &25, &2, &proposer);
+
+    let wasm_hash = upload(&env, "rbac-playground");
+    v1.upgrade(&wasm_hash);
+
+    assert_eq!(
+        env.auths()[0],
+        (
+            owner.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    contract_id.clone(),
+                    Symbol::new(&env, "upgrade"),
+                    (wasm_hash,).into_val(&env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+
+    let upgraded = RbacPlaygroundClient::new(&env, &contract_id);
+    assert_eq!(upgraded.get_storage_version(), 1);
+    assert!(upgraded.migrate());
+
+    assert_eq!(upgraded.get_storage_version(), STORAGE_VERSION);
+    // Version 1 did not record proposers; the contract stands in for them.
+    assert_eq!(
+        upgraded.view_pending_transfer(&id, &viewer),
+        PendingTransfer {
+            id,
+            from: from.clone(),
+            to,
+            amount: 25,
+            approvals: 0,
+            required_approvals: 2,
+            executed: false,
+            proposer: contract_id.clone(),
+            cancelled: false,
+        }
+    );
+    // Balances, roles and the admin survive the code change.
+    assert_eq!(upgraded.get_balance(&from), 100);
+    assert_eq!(upgraded.get_admin(), Some(admin.clone()));
+    assert!(upgraded
+        .has_role(&viewer, &symbol_short!("viewer"))
+        .is_some());
+    assert!(!upgraded.is_paused());
+    // Migration runs once per upgrade.
+    assert!(upgraded.try_migrate().is_err());
+
+    // Version 1 kept balances in instance storage; the next change moves
+    // one to persistent storage.
+    let key = DataKey::Balance(from.clone());
+    let stored_in = |env: &Env| {
+        env.as_contract(&contract_id, || {
+            (
+                env.storage().instance().has(&key),
+                env.storage().persistent().has(&key),
+            )
+        })
+    };
+    assert_eq!(stored_in(&env), (true, false));
+    upgraded.mint(&from, &50, &admin);
+    assert_eq!(upgraded.get_balance(&from), 150);
+    assert_eq!(stored_in(&env), (false, true));
+}
+
+#[test]
+fn upgrade_publishes_upgraded_and_allows_one_migrate() {
+    let env = sandbox();
+    let owner = Address::generate(&env);
+    let contract_id = env.register(
+        wasm("rbac-playground"),
+        (
+            Address::generate(&env),
+            &owner,
+            Vec::<(Symbol, Address)>::new(&env),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+    let wasm_hash = upload(&env, "rbac-playground");
+
+    assert!(client.try_migrate().is_err());
+    client.upgrade(&wasm_hash);
+
+    let event = Upgraded {
+        new_wasm_hash: wasm_hash,
+        caller: owner,
+    };
+    assert_last_events(&env, &contract_id, &[&event]);
+
+    assert!(client.migrate());
+    assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+    assert!(client.try_migrate().is_err());
+}
+
+/// `upgrade`'s permission matrix entry, which the unit test of the matrix
+/// leaves out because it needs a wasm to upgrade to.
+#[test]
+fn upgrade_matches_its_permission_matrix_entry() {
+    let env = sandbox();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let contract_id = env.register(
+        wasm("rbac-playground"),
+        (
+            &admin,
+            &owner,
+            Vec::<(Symbol, Address)>::new(&env),
+            None::<Vec<(Address, i128)>>,
+        ),
+    );
+    let client = RbacPlaygroundClient::new(&env, &contract_id);
+    let wasm_hash = upload(&env, "rbac-playground");
+    let function = Symbol::new(&env, "upgrade");
+    let signed_by = |signer: &Address| {
+        env.mock_auths(&[MockAuth {
+            address: signer,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "upgrade",
+                args: (wasm_hash.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+    };
+
+    assert_eq!(client.required_role(&function), None);
+    assert!(!client.can(&admin, &function));
+    assert!(!client.can(&outsider, &function));
+    signed_by(&admin);
+    assert!(client.try_upgrade(&wasm_hash).is_err());
+    signed_by(&outsider);
+    assert!(client.try_upgrade(&wasm_hash).is_err());
+
+    // Not gated on the pause state.
+    env.mock_all_auths();
+    client.emergency_pause(&symbol_short!("incident"), &None);
+    assert!(client.can(&owner, &function));
+    signed_by(&owner);
+    client.upgrade(&wasm_hash);
+}
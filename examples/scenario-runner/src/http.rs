@@ -0,0 +1,45 @@
+//! Requests to the RPC endpoint and friendbot, over `http://` or `https://`.
+
+use std::io::{self, Read};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A response's status code and body.
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+pub(crate) fn get(url: &str) -> io::Result<Response> {
+    read(agent().get(url).call())
+}
+
+pub(crate) fn post_json(url: &str, body: &str) -> io::Result<Response> {
+    read(
+        agent()
+            .post(url)
+            .set("Accept", "application/json")
+            .set("Content-Type", "application/json")
+            .send_string(body),
+    )
+}
+
+fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| ureq::AgentBuilder::new().timeout(TIMEOUT).build())
+}
+
+/// Reads the response, including one with an error status: callers report
+/// the status with what the body says.
+fn read(result: Result<ureq::Response, ureq::Error>) -> io::Result<Response> {
+    let response = match result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(error)) => return Err(io::Error::other(error)),
+    };
+    let status = response.status();
+    let mut body = Vec::new();
+    response.into_reader().read_to_end(&mut body)?;
+    Ok(Response { status, body })
+}
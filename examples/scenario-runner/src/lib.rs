@@ -0,0 +1,451 @@
+//! Runs declarative RBAC playground scenarios.
+//!
+//! A [`Scenario`] names its identities, the playground's constructor
+//! arguments and a list of steps: role changes, token calls, proposals and
+//! approvals, admin and ownership transfers, and ledger advances. [`run`]
+//! deploys the playground and executes the steps on a [`Backend`], either the
+//! in-process [`Sandbox`] or a Soroban RPC endpoint through [`Rpc`], and
+//! reports each transaction with its decoded events.
+//!
+//! Identities sign with ed25519 keys from [`Keys`]: derived from a seed and
+//! their name, so a scenario run twice with the same seed uses the same
+//! accounts, or read from a key file. Keys derived from the public
+//! [`SANDBOX_SEED`] are refused by every backend but the sandbox.
+//!
+//! ```ignore
+//! let scenario = Scenario::from_json(&std::fs::read_to_string(path)?)?;
+//! let mut sandbox = Sandbox::new();
+//! let reports = rbac_scenario_runner::run(&scenario, &mut sandbox, &Keys::sandbox(), |report| {
+//!     println!("{}", serde_json::to_string(report).unwrap());
+//! })?;
+//! ```
+
+use core::fmt;
+use std::collections::BTreeMap;
+use std::io;
+
+use ed25519_dalek::{Signer as _, SigningKey};
+use rbac_event_decoder::DecodeError;
+use rbac_state_reconstructor::LedgerEvent;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use stellar_strkey::ed25519::PrivateKey;
+use stellar_xdr::curr::{
+    AccountId, ContractEvent, PublicKey, ScAddress, ScMapEntry, ScVal, Uint256,
+};
+
+mod http;
+mod rpc;
+mod sandbox;
+pub mod scenario;
+
+pub use rpc::{Rpc, BASE_FEE};
+pub use sandbox::Sandbox;
+pub use scenario::{Amount, Deploy, Op, Outcome, Scenario, Step};
+
+/// An account a scenario signs with.
+#[derive(Clone, Debug)]
+pub struct Identity {
+    pub name: String,
+    key: SigningKey,
+}
+
+impl Identity {
+    /// The identity `name` for `seed`: the key is sha256 of `{seed}/{name}`,
+    /// so anyone who knows the seed can sign as it.
+    pub fn derive(seed: &str, name: &str) -> Self {
+        let secret: [u8; 32] = Sha256::digest(format!("{seed}/{name}")).into();
+        Identity::from_secret(name, &secret)
+    }
+
+    /// The identity `name` signing with the ed25519 secret key `secret`.
+    pub fn from_secret(name: &str, secret: &[u8; 32]) -> Self {
+        Identity {
+            name: name.to_string(),
+            key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    pub fn account_id(&self) -> AccountId {
+        AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(self.public_key())))
+    }
+
+    pub fn address(&self) -> ScAddress {
+        ScAddress::Account(self.account_id())
+    }
+
+    /// Signs `payload`, a transaction hash.
+    pub fn sign(&self, payload: &[u8]) -> [u8; 64] {
+        self.key.sign(payload).to_bytes()
+    }
+}
+
+/// The seed [`Keys::sandbox`] derives from. It is public, so keys derived
+/// from it are only accepted by the sandbox.
+pub const SANDBOX_SEED: &str = "rbac-playground";
+
+/// Where a run's identities get their keys.
+#[derive(Clone, Debug)]
+pub enum Keys {
+    /// Derived from a seed and each identity's name by [`Identity::derive`].
+    Derived(String),
+    /// Secret keys by identity name, from a key file.
+    Secret(BTreeMap<String, [u8; 32]>),
+}
+
+impl Keys {
+    /// Keys derived from [`SANDBOX_SEED`].
+    pub fn sandbox() -> Self {
+        Keys::Derived(SANDBOX_SEED.to_string())
+    }
+
+    /// Parses a key file: a JSON object of Stellar secret seeds (`S...`) by
+    /// identity name, e.g. `{"admin": "SB...", "alice": "SC..."}`.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let seeds: BTreeMap<String, String> = serde_json::from_str(json)
+            .map_err(|error| Error::Scenario(format!("invalid key file: {error}")))?;
+        let keys = seeds
+            .into_iter()
+            .map(|(name, seed)| match PrivateKey::from_string(&seed) {
+                Ok(key) => Ok((name, key.0)),
+                Err(_) => Err(Error::Scenario(format!(
+                    "invalid key file: the key of `{name}` is not a secret seed"
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Keys::Secret(keys))
+    }
+
+    /// Whether anyone could derive these keys.
+    pub fn is_public(&self) -> bool {
+        matches!(self, Keys::Derived(seed) if seed == SANDBOX_SEED)
+    }
+
+    /// The identity `name`.
+    pub fn identity(&self, name: &str) -> Result<Identity, Error> {
+        match self {
+            Keys::Derived(seed) => Ok(Identity::derive(seed, name)),
+            Keys::Secret(keys) => keys
+                .get(name)
+                .map(|secret| Identity::from_secret(name, secret))
+                .ok_or_else(|| Error::Scenario(format!("no key for identity `{name}`"))),
+        }
+    }
+}
+
+/// A transaction a backend submitted, or tried to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transaction {
+    /// `None` when the transaction was rejected before submission.
+    pub hash: Option<String>,
+    pub ledger: u32,
+    /// The transaction's 1-based position in its ledger.
+    pub application_order: u32,
+    /// The ledger close time in unix seconds.
+    pub timestamp: u64,
+    pub returned: Option<ScVal>,
+    /// The contract events the transaction published.
+    pub events: Vec<ContractEvent>,
+    /// Why the transaction failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Where a scenario runs.
+pub trait Backend {
+    /// Whether identities may sign with [`Keys::is_public`] keys, which is
+    /// only safe where nothing of value is at stake.
+    fn allows_public_keys(&self) -> bool {
+        false
+    }
+
+    /// Makes sure every identity has an account.
+    fn prepare(&mut self, identities: &[Identity]) -> Result<(), Error>;
+
+    /// Deploys the playground from `deployer` with `constructor_args` and
+    /// returns its address and the deploying transaction.
+    fn deploy(
+        &mut self,
+        deployer: &Identity,
+        constructor_args: Vec<ScVal>,
+    ) -> Result<(ScAddress, Transaction), Error>;
+
+    /// Calls `function` on `contract`, authorized and signed by `source`.
+    /// A call the contract rejects is a `Transaction` with an `error`.
+    fn invoke(
+        &mut self,
+        source: &Identity,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Transaction, Error>;
+
+    /// The sequence of the latest closed ledger.
+    fn ledger(&mut self) -> Result<u32, Error>;
+
+    /// Returns once `ledgers` more ledgers have closed.
+    fn advance(&mut self, ledgers: u32) -> Result<(), Error>;
+}
+
+/// Errors that stop a run. Failed steps are reported, not raised.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Xdr(stellar_xdr::curr::Error),
+    /// The RPC endpoint answered `method` with an error, or nonsense.
+    Rpc {
+        method: String,
+        message: String,
+    },
+    /// The scenario is invalid, or its deployment failed.
+    Scenario(String),
+    /// A transaction published an event the decoder does not know.
+    Decode(DecodeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "I/O error: {error}"),
+            Error::Xdr(error) => write!(f, "invalid XDR: {error}"),
+            Error::Rpc { method, message } => write!(f, "{method}: {message}"),
+            Error::Scenario(message) => f.write_str(message),
+            Error::Decode(error) => write!(f, "failed to decode event: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<stellar_xdr::curr::Error> for Error {
+    fn from(error: stellar_xdr::curr::Error) -> Self {
+        Error::Xdr(error)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(error: DecodeError) -> Self {
+        Error::Decode(error)
+    }
+}
+
+/// What one step did.
+///
+/// Serializes as one JSON object with the step's events as `LedgerEvent`s,
+/// which `rbac-state-reconstructor` reads.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepReport {
+    /// 0 for the deployment, then the step's 1-based position.
+    pub step: usize,
+    pub op: String,
+    /// The identity that signed the step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The ledger the step's transaction closed in, or the latest ledger.
+    pub ledger: u32,
+    pub tx_hash: Option<String>,
+    pub status: Outcome,
+    pub expected: Outcome,
+    /// The call's return value, as JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returned: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The deployed contract, on the deployment step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// Each identity's account, on the deployment step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identities: Option<BTreeMap<String, String>>,
+    pub events: Vec<LedgerEvent>,
+}
+
+impl StepReport {
+    /// Whether the step did what the scenario expected.
+    pub fn as_expected(&self) -> bool {
+        self.status == self.expected
+    }
+}
+
+/// Deploys the playground on `backend` and runs `scenario`'s steps, passing
+/// each report to `on_report` as it is made.
+pub fn run<B: Backend>(
+    scenario: &Scenario,
+    backend: &mut B,
+    keys: &Keys,
+    mut on_report: impl FnMut(&StepReport),
+) -> Result<Vec<StepReport>, Error> {
+    scenario.validate()?;
+    if keys.is_public() && !backend.allows_public_keys() {
+        return Err(Error::Scenario(format!(
+            "keys derived from the public seed `{SANDBOX_SEED}` are for the sandbox only; \
+             use a secret seed or a key file"
+        )));
+    }
+    let identities: BTreeMap<&str, Identity> = scenario
+        .identities
+        .iter()
+        .map(|name| Ok((name.as_str(), keys.identity(name)?)))
+        .collect::<Result<_, Error>>()?;
+    let address = |name: &str| ScVal::Address(identities[name].address());
+    backend.prepare(&identities.values().cloned().collect::<Vec<_>>())?;
+
+    let deployer = &identities[scenario.deploy.admin.as_str()];
+    let (contract, deployment) = backend.deploy(deployer, scenario.deploy.args(address)?)?;
+    if let Some(error) = deployment.error {
+        return Err(Error::Scenario(format!("deploy: {error}")));
+    }
+    let mut deploy_report = report(0, "deploy", deployer, Outcome::Success, deployment)?;
+    deploy_report.contract = Some(contract.to_string());
+    deploy_report.identities = Some(
+        identities
+            .iter()
+            .map(|(name, identity)| (name.to_string(), identity.account_id().to_string()))
+            .collect(),
+    );
+    on_report(&deploy_report);
+    let mut reports = vec![deploy_report];
+
+    for (index, step) in scenario.steps.iter().enumerate() {
+        let step_report = match (&step.op, step.op.signer()) {
+            (Op::AdvanceLedgers { ledgers }, _) => {
+                backend.advance(*ledgers)?;
+                StepReport {
+                    step: index + 1,
+                    op: step.op.name().to_string(),
+                    source: None,
+                    ledger: backend.ledger()?,
+                    tx_hash: None,
+                    status: Outcome::Success,
+                    expected: step.expect,
+                    returned: None,
+                    error: None,
+                    contract: None,
+                    identities: None,
+                    events: Vec::new(),
+                }
+            }
+            (op, Some(signer)) => {
+                let source = &identities[signer];
+                let args = op.args(address, backend.ledger()?)?;
+                let transaction = backend.invoke(source, &contract, op.name(), args)?;
+                report(index + 1, op.name(), source, step.expect, transaction)?
+            }
+            (op, None) => unreachable!("`{}` has no signer", op.name()),
+        };
+        on_report(&step_report);
+        reports.push(step_report);
+    }
+    Ok(reports)
+}
+
+fn report(
+    step: usize,
+    op: &str,
+    source: &Identity,
+    expected: Outcome,
+    transaction: Transaction,
+) -> Result<StepReport, Error> {
+    let tx_hash = transaction.hash.clone().unwrap_or_default();
+    let events = transaction
+        .events
+        .iter()
+        .enumerate()
+        .map(|(index, event)| {
+            Ok(LedgerEvent {
+                id: event_id(transaction.ledger, transaction.application_order, index),
+                ledger: transaction.ledger,
+                timestamp: transaction.timestamp.to_string(),
+                tx_hash: tx_hash.clone(),
+                decoded: rbac_event_decoder::decode(event)?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(StepReport {
+        step,
+        op: op.to_string(),
+        source: Some(source.name.clone()),
+        ledger: transaction.ledger,
+        tx_hash: transaction.hash,
+        status: match transaction.error {
+            None => Outcome::Success,
+            Some(_) => Outcome::Failure,
+        },
+        expected,
+        returned: transaction.returned.as_ref().map(scval_json),
+        error: transaction.error,
+        contract: None,
+        identities: None,
+        events,
+    })
+}
+
+/// The id Soroban RPC gives the `index`th event of a transaction: the
+/// transaction's TOID and the event's position, zero-padded.
+pub fn event_id(ledger: u32, application_order: u32, index: usize) -> String {
+    let toid = (u64::from(ledger) << 32) | (u64::from(application_order) << 12);
+    format!("{toid:019}-{index:010}")
+}
+
+/// Renders a contract value as JSON: 128-bit and larger integers as decimal
+/// strings, addresses as strkeys, bytes as hex, and maps with symbol or
+/// string keys as objects.
+pub fn scval_json(value: &ScVal) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        ScVal::Void => Value::Null,
+        ScVal::Bool(b) => Value::from(*b),
+        ScVal::U32(n) => Value::from(*n),
+        ScVal::I32(n) => Value::from(*n),
+        ScVal::U64(n) => Value::from(*n),
+        ScVal::I64(n) => Value::from(*n),
+        ScVal::Timepoint(t) => Value::from(t.0),
+        ScVal::Duration(d) => Value::from(d.0),
+        ScVal::U128(n) => Value::from(u128::from(n).to_string()),
+        ScVal::I128(n) => Value::from(i128::from(n).to_string()),
+        ScVal::Symbol(s) => Value::from(s.to_utf8_string_lossy()),
+        ScVal::String(s) => Value::from(s.to_utf8_string_lossy()),
+        ScVal::Bytes(bytes) => Value::from(
+            bytes
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>(),
+        ),
+        ScVal::Address(address) => Value::from(address.to_string()),
+        ScVal::Vec(Some(items)) => items.iter().map(scval_json).collect(),
+        ScVal::Map(Some(entries)) => map_json(entries),
+        other => Value::from(format!("{other:?}")),
+    }
+}
+
+fn map_json(entries: &[ScMapEntry]) -> serde_json::Value {
+    let key = |entry: &ScMapEntry| match &entry.key {
+        ScVal::Symbol(s) => Some(s.to_utf8_string_lossy()),
+        ScVal::String(s) => Some(s.to_utf8_string_lossy()),
+        _ => None,
+    };
+    if entries.iter().all(|entry| key(entry).is_some()) {
+        entries
+            .iter()
+            .map(|entry| (key(entry).unwrap(), scval_json(&entry.val)))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    } else {
+        entries
+            .iter()
+            .map(|entry| serde_json::json!([scval_json(&entry.key), scval_json(&entry.val)]))
+            .collect()
+    }
+}
@@ -0,0 +1,180 @@
+//! Runs a scenario and prints one JSON report per step.
+//!
+//! ```text
+//! rbac-scenario-runner [OPTIONS] SCENARIO
+//! ```
+//!
+//! Without `--rpc` the scenario runs in the in-process sandbox, natively or
+//! from `--wasm`. With `--events`, the events are also written as JSON lines
+//! that `rbac-state-reconstructor` replays.
+//!
+//! In the sandbox, identity keys default to ones derived from the public
+//! `SANDBOX_SEED`. With `--rpc` they must come from a secret `--seed` or a
+//! `--keys` file.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+
+use rbac_scenario_runner::{run, Error, Keys, Outcome, Rpc, Sandbox, Scenario, StepReport};
+
+const USAGE: &str = "usage: rbac-scenario-runner [OPTIONS] SCENARIO
+
+Runs the JSON scenario file SCENARIO and prints a JSON report per step.
+
+options:
+  --rpc URL        run against the Soroban RPC endpoint at URL (needs --wasm,
+                   and --seed or --keys)
+  --friendbot URL  fund missing accounts from the friendbot at URL
+  --wasm PATH      the playground wasm to deploy
+  --seed SEED      derive identity keys from the secret SEED (sandbox
+                   default: the public seed rbac-playground)
+  --keys PATH      read identity keys from PATH, a JSON object of secret
+                   seeds (S...) by identity name
+  --events PATH    also write the events to PATH as JSON lines";
+
+#[derive(Default)]
+struct Options {
+    scenario: String,
+    rpc: Option<String>,
+    friendbot: Option<String>,
+    wasm: Option<String>,
+    seed: Option<String>,
+    keys: Option<String>,
+    events: Option<String>,
+}
+
+fn parse(args: &[String]) -> Option<Options> {
+    let mut options = Options::default();
+    let mut scenario = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--rpc" => &mut options.rpc,
+            "--friendbot" => &mut options.friendbot,
+            "--wasm" => &mut options.wasm,
+            "--seed" => &mut options.seed,
+            "--keys" => &mut options.keys,
+            "--events" => &mut options.events,
+            flag if flag.starts_with("--") => return None,
+            _ => &mut scenario,
+        };
+        if value.is_some() {
+            return None;
+        }
+        *value = Some(if arg.starts_with("--") {
+            args.next()?.clone()
+        } else {
+            arg.clone()
+        });
+    }
+    options.scenario = scenario?;
+    if options.rpc.is_some() && options.wasm.is_none() {
+        return None;
+    }
+    if options.friendbot.is_some() && options.rpc.is_none() {
+        return None;
+    }
+    if options.seed.is_some() && options.keys.is_some() {
+        return None;
+    }
+    if options.rpc.is_some() && options.seed.is_none() && options.keys.is_none() {
+        return None;
+    }
+    Some(options)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.as_slice(), [arg] if arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let Some(options) = parse(&args) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    match execute(&options) {
+        Ok(reports) => {
+            let unexpected: Vec<&StepReport> = reports
+                .iter()
+                .filter(|report| !report.as_expected())
+                .collect();
+            for report in &unexpected {
+                let outcome = match report.status {
+                    Outcome::Success => "succeeded",
+                    Outcome::Failure => "failed",
+                };
+                eprintln!(
+                    "step {} ({}) {outcome} unexpectedly{}",
+                    report.step,
+                    report.op,
+                    report
+                        .error
+                        .as_ref()
+                        .map(|error| format!(": {error}"))
+                        .unwrap_or_default()
+                );
+            }
+            if unexpected.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execute(options: &Options) -> Result<Vec<StepReport>, Error> {
+    let json = fs::read_to_string(&options.scenario)?;
+    let scenario = Scenario::from_json(&json)
+        .map_err(|error| Error::Scenario(format!("{}: {error}", options.scenario)))?;
+    let wasm = options.wasm.as_ref().map(fs::read).transpose()?;
+    let keys = match (&options.seed, &options.keys) {
+        (Some(seed), _) => Keys::Derived(seed.clone()),
+        (_, Some(path)) => Keys::from_json(&fs::read_to_string(path)?)
+            .map_err(|error| Error::Scenario(format!("{path}: {error}")))?,
+        (None, None) => Keys::sandbox(),
+    };
+
+    let mut events = options
+        .events
+        .as_ref()
+        .map(|path| File::create(path).map(BufWriter::new))
+        .transpose()?;
+    let mut write_error = None;
+    let print = |report: &StepReport| {
+        println!("{}", serde_json::to_string(report).unwrap());
+        if let Some(events) = &mut events {
+            for event in &report.events {
+                if let Err(error) = writeln!(events, "{}", serde_json::to_string(event).unwrap()) {
+                    write_error.get_or_insert(error);
+                }
+            }
+        }
+    };
+
+    let reports = match (&options.rpc, wasm) {
+        (Some(url), Some(wasm)) => {
+            let mut rpc = Rpc::connect(url, wasm)?;
+            if let Some(friendbot) = &options.friendbot {
+                rpc = rpc.with_friendbot(friendbot);
+            }
+            run(&scenario, &mut rpc, &keys, print)?
+        }
+        (_, Some(wasm)) => run(&scenario, &mut Sandbox::with_wasm(wasm), &keys, print)?,
+        (_, None) => run(&scenario, &mut Sandbox::new(), &keys, print)?,
+    };
+    if let Some(error) = write_error {
+        return Err(error.into());
+    }
+    if let Some(events) = &mut events {
+        events.flush()?;
+    }
+    Ok(reports)
+}
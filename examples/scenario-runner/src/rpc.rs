@@ -0,0 +1,448 @@
+//! A Soroban RPC endpoint as a [`Backend`].
+//!
+//! Each call is simulated, given the footprint, fee and authorization the
+//! simulation returns, signed by its source and submitted; the runner then
+//! polls for the result. A call that fails simulation is reported without
+//! being submitted, so it has no hash and uses no sequence number.
+
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    ContractEvent, ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress,
+    CreateContractArgsV2, DecoratedSignature, Hash, HostFunction, InvokeContractArgs,
+    InvokeHostFunctionOp, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, Memo, MuxedAccount,
+    Operation, OperationBody, OperationResult, OperationResultTr, Preconditions, ReadXdr,
+    ScAddress, ScSymbol, ScVal, SequenceNumber, Signature, SignatureHint,
+    SorobanAuthorizationEntry, SorobanCredentials, SorobanTransactionData, Transaction as Tx,
+    TransactionEnvelope, TransactionExt, TransactionMeta, TransactionResult,
+    TransactionResultResult, TransactionV1Envelope, Uint256, WriteXdr,
+};
+
+use crate::{http, Backend, Error, Identity, Transaction};
+
+/// The inclusion fee offered on top of the simulated resource fee.
+pub const BASE_FEE: u32 = 100;
+
+/// How long to wait for a submitted transaction before giving up.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A Soroban RPC endpoint, such as quickstart's `http://localhost:8000/rpc`.
+pub struct Rpc {
+    url: String,
+    wasm: Vec<u8>,
+    network_id: [u8; 32],
+    friendbot: Option<String>,
+    poll_interval: Duration,
+    requests: u64,
+}
+
+impl Rpc {
+    /// Connects to the endpoint at `url`, which the playground's `wasm` is
+    /// deployed to.
+    pub fn connect(url: &str, wasm: Vec<u8>) -> Result<Self, Error> {
+        let mut rpc = Rpc {
+            url: url.to_string(),
+            wasm,
+            network_id: [0; 32],
+            friendbot: None,
+            poll_interval: Duration::from_secs(1),
+            requests: 0,
+        };
+        let network = rpc.call("getNetwork", json!({}))?;
+        let passphrase = str_field("getNetwork", &network, "passphrase")?;
+        rpc.network_id = Sha256::digest(passphrase).into();
+        Ok(rpc)
+    }
+
+    /// Funds identities without an account from the friendbot at `url`.
+    pub fn with_friendbot(mut self, url: &str) -> Self {
+        self.friendbot = Some(url.to_string());
+        self
+    }
+
+    /// Polls for transactions and ledgers every `interval`; 1 second by
+    /// default.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sends one JSON-RPC request and returns its `result`.
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, Error> {
+        self.requests += 1;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.requests,
+            "method": method,
+            "params": params,
+        });
+        let response = http::post_json(&self.url, &request.to_string())?;
+        let mut body: Value = serde_json::from_slice(&response.body)
+            .map_err(|error| rpc_error(method, format!("HTTP {}: {error}", response.status)))?;
+        if let Some(error) = body.get("error") {
+            let message = match error.get("message").and_then(Value::as_str) {
+                Some(message) => message.to_string(),
+                None => error.to_string(),
+            };
+            return Err(rpc_error(method, message));
+        }
+        match body.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(rpc_error(method, "response has no result".to_string())),
+        }
+    }
+
+    /// The account's current sequence number, or `None` if it does not
+    /// exist.
+    fn sequence(&mut self, identity: &Identity) -> Result<Option<i64>, Error> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: identity.account_id(),
+        });
+        let result = self.call(
+            "getLedgerEntries",
+            json!({ "keys": [key.to_xdr_base64(Limits::none())?] }),
+        )?;
+        let Some(entry) = result
+            .get("entries")
+            .and_then(Value::as_array)
+            .and_then(|entries| entries.first())
+        else {
+            return Ok(None);
+        };
+        match LedgerEntryData::from_xdr_base64(
+            str_field("getLedgerEntries", entry, "xdr")?,
+            Limits::none(),
+        )? {
+            LedgerEntryData::Account(account) => Ok(Some(account.seq_num.0)),
+            _ => Err(rpc_error(
+                "getLedgerEntries",
+                "returned an entry that is not an account".to_string(),
+            )),
+        }
+    }
+
+    /// Simulates, signs and submits `host_function` from `source`, and waits
+    /// for its result.
+    fn submit(
+        &mut self,
+        source: &Identity,
+        host_function: HostFunction,
+    ) -> Result<Transaction, Error> {
+        let sequence = self
+            .sequence(source)?
+            .ok_or_else(|| Error::Scenario(format!("identity `{}` has no account", source.name)))?;
+        let mut tx = Tx {
+            source_account: MuxedAccount::Ed25519(Uint256(source.public_key())),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(sequence + 1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function,
+                    auth: Default::default(),
+                }),
+            }]
+            .try_into()?,
+            ext: TransactionExt::V0,
+        };
+
+        let simulation = self.call(
+            "simulateTransaction",
+            json!({ "transaction": envelope(tx.clone(), vec![])?.to_xdr_base64(Limits::none())? }),
+        )?;
+        if let Some(error) = simulation.get("error").and_then(Value::as_str) {
+            return Ok(Transaction {
+                ledger: u32_field("simulateTransaction", &simulation, "latestLedger")?,
+                error: Some(format!("simulation failed: {error}")),
+                ..Transaction::default()
+            });
+        }
+        let auth = simulation
+            .get("results")
+            .and_then(|results| results.get(0))
+            .and_then(|result| result.get("auth"))
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| {
+                let entry = entry.as_str().unwrap_or_default();
+                SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if auth
+            .iter()
+            .any(|entry| !matches!(entry.credentials, SorobanCredentials::SourceAccount))
+        {
+            return Err(Error::Scenario(format!(
+                "the call needs a signature from an account other than `{}`",
+                source.name
+            )));
+        }
+        let resource_fee = u32_field("simulateTransaction", &simulation, "minResourceFee")?;
+        tx.fee = BASE_FEE + resource_fee;
+        tx.ext = TransactionExt::V1(SorobanTransactionData::from_xdr_base64(
+            str_field("simulateTransaction", &simulation, "transactionData")?,
+            Limits::none(),
+        )?);
+        let mut operations = tx.operations.to_vec();
+        if let OperationBody::InvokeHostFunction(op) = &mut operations[0].body {
+            op.auth = auth.try_into()?;
+        }
+        tx.operations = operations.try_into()?;
+
+        let hash = tx.hash(self.network_id)?;
+        let public_key = source.public_key();
+        let signature = DecoratedSignature {
+            hint: SignatureHint(public_key[28..].try_into().unwrap()),
+            signature: Signature(source.sign(&hash).to_vec().try_into()?),
+        };
+        let envelope = envelope(tx, vec![signature])?.to_xdr_base64(Limits::none())?;
+        let hash = hex(&hash);
+
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let sent = self.call("sendTransaction", json!({ "transaction": envelope }))?;
+            match str_field("sendTransaction", &sent, "status")? {
+                "PENDING" | "DUPLICATE" => break,
+                "TRY_AGAIN_LATER" if Instant::now() < deadline => thread::sleep(self.poll_interval),
+                "ERROR" => {
+                    let error = match sent.get("errorResultXdr").and_then(Value::as_str) {
+                        Some(result) => result_error(&TransactionResult::from_xdr_base64(
+                            result,
+                            Limits::none(),
+                        )?),
+                        None => "rejected".to_string(),
+                    };
+                    return Ok(Transaction {
+                        hash: Some(hash),
+                        ledger: u32_field("sendTransaction", &sent, "latestLedger")?,
+                        error: Some(error),
+                        ..Transaction::default()
+                    });
+                }
+                status => {
+                    return Err(rpc_error(
+                        "sendTransaction",
+                        format!("transaction {hash}: status {status}"),
+                    ))
+                }
+            }
+        }
+
+        loop {
+            let result = self.call("getTransaction", json!({ "hash": hash }))?;
+            let status = str_field("getTransaction", &result, "status")?;
+            if status == "NOT_FOUND" {
+                if Instant::now() >= deadline {
+                    return Err(rpc_error(
+                        "getTransaction",
+                        format!("transaction {hash} was not applied within {TIMEOUT:?}"),
+                    ));
+                }
+                thread::sleep(self.poll_interval);
+                continue;
+            }
+
+            let meta = TransactionMeta::from_xdr_base64(
+                str_field("getTransaction", &result, "resultMetaXdr")?,
+                Limits::none(),
+            )?;
+            let (events, returned) = outcome(meta);
+            let error = match status {
+                "SUCCESS" => None,
+                _ => Some(result_error(&TransactionResult::from_xdr_base64(
+                    str_field("getTransaction", &result, "resultXdr")?,
+                    Limits::none(),
+                )?)),
+            };
+            return Ok(Transaction {
+                hash: Some(hash),
+                ledger: u32_field("getTransaction", &result, "ledger")?,
+                application_order: u32_field("getTransaction", &result, "applicationOrder")?,
+                timestamp: u64_field("getTransaction", &result, "createdAt")?,
+                returned: returned.filter(|_| error.is_none()),
+                events: if error.is_none() { events } else { Vec::new() },
+                error,
+            });
+        }
+    }
+}
+
+impl Backend for Rpc {
+    fn prepare(&mut self, identities: &[Identity]) -> Result<(), Error> {
+        for identity in identities {
+            if self.sequence(identity)?.is_some() {
+                continue;
+            }
+            let account = identity.account_id();
+            let Some(friendbot) = &self.friendbot else {
+                return Err(Error::Scenario(format!(
+                    "identity `{}` ({account}) has no account; fund it or pass a friendbot",
+                    identity.name
+                )));
+            };
+            let response = http::get(&format!("{friendbot}?addr={account}"))?;
+            if !(200..300).contains(&response.status) {
+                return Err(Error::Scenario(format!(
+                    "friendbot could not fund `{}` ({account}): HTTP {}",
+                    identity.name, response.status
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads the wasm, then creates the contract from the deployer's
+    /// address with a fresh salt, so every run deploys a new instance.
+    fn deploy(
+        &mut self,
+        deployer: &Identity,
+        constructor_args: Vec<ScVal>,
+    ) -> Result<(ScAddress, Transaction), Error> {
+        let upload = self.submit(
+            deployer,
+            HostFunction::UploadContractWasm(self.wasm.clone().try_into()?),
+        )?;
+        if let Some(error) = upload.error {
+            return Err(Error::Scenario(format!(
+                "uploading the wasm failed: {error}"
+            )));
+        }
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let salt: [u8; 32] = Sha256::digest(format!("{}/{nanos}", deployer.name)).into();
+        let create = HostFunction::CreateContractV2(CreateContractArgsV2 {
+            contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+                address: deployer.address(),
+                salt: Uint256(salt),
+            }),
+            executable: ContractExecutable::Wasm(Hash(Sha256::digest(&self.wasm).into())),
+            constructor_args: constructor_args.try_into()?,
+        });
+        let transaction = self.submit(deployer, create)?;
+        match &transaction.returned {
+            Some(ScVal::Address(contract)) => Ok((contract.clone(), transaction)),
+            _ => Err(Error::Scenario(format!(
+                "creating the contract failed: {}",
+                transaction
+                    .error
+                    .as_deref()
+                    .unwrap_or("no address returned")
+            ))),
+        }
+    }
+
+    fn invoke(
+        &mut self,
+        source: &Identity,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Transaction, Error> {
+        let function_name = ScSymbol::try_from(function)
+            .map_err(|_| Error::Scenario(format!("`{function}` is not a valid symbol")))?;
+        self.submit(
+            source,
+            HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name,
+                args: args.try_into()?,
+            }),
+        )
+    }
+
+    fn ledger(&mut self) -> Result<u32, Error> {
+        let latest = self.call("getLatestLedger", json!({}))?;
+        u32_field("getLatestLedger", &latest, "sequence")
+    }
+
+    /// Waits for the network to close `ledgers` ledgers.
+    fn advance(&mut self, ledgers: u32) -> Result<(), Error> {
+        let target = self.ledger()? + ledgers;
+        while self.ledger()? < target {
+            thread::sleep(self.poll_interval);
+        }
+        Ok(())
+    }
+}
+
+fn envelope(tx: Tx, signatures: Vec<DecoratedSignature>) -> Result<TransactionEnvelope, Error> {
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: signatures.try_into()?,
+    }))
+}
+
+/// The events and return value in a transaction's meta.
+fn outcome(meta: TransactionMeta) -> (Vec<ContractEvent>, Option<ScVal>) {
+    match meta {
+        TransactionMeta::V3(meta) => match meta.soroban_meta {
+            Some(soroban) => (soroban.events.to_vec(), Some(soroban.return_value)),
+            None => (Vec::new(), None),
+        },
+        TransactionMeta::V4(meta) => (
+            meta.operations
+                .iter()
+                .flat_map(|operation| operation.events.iter().cloned())
+                .collect(),
+            meta.soroban_meta.and_then(|soroban| soroban.return_value),
+        ),
+        _ => (Vec::new(), None),
+    }
+}
+
+/// Why a transaction failed, from its result code.
+fn result_error(result: &TransactionResult) -> String {
+    match &result.result {
+        TransactionResultResult::TxFailed(operations) => match operations.first() {
+            Some(OperationResult::OpInner(OperationResultTr::InvokeHostFunction(result))) => {
+                format!("TxFailed: {}", result.name())
+            }
+            _ => "TxFailed".to_string(),
+        },
+        result => result.name().to_string(),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn rpc_error(method: &str, message: String) -> Error {
+    Error::Rpc {
+        method: method.to_string(),
+        message,
+    }
+}
+
+fn str_field<'a>(method: &str, value: &'a Value, field: &str) -> Result<&'a str, Error> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| rpc_error(method, format!("response has no `{field}`")))
+}
+
+/// A number field, which RPC encodes as a JSON number or, for 64-bit values,
+/// a string.
+fn u64_field(method: &str, value: &Value, field: &str) -> Result<u64, Error> {
+    match value.get(field) {
+        Some(Value::Number(n)) => n.as_u64(),
+        Some(Value::String(s)) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| rpc_error(method, format!("response has no numeric `{field}`")))
+}
+
+fn u32_field(method: &str, value: &Value, field: &str) -> Result<u32, Error> {
+    u32::try_from(u64_field(method, value, field)?)
+        .map_err(|_| rpc_error(method, format!("`{field}` is out of range")))
+}
@@ -0,0 +1,227 @@
+//! The in-process soroban sandbox as a [`Backend`].
+
+use std::panic::{self, AssertUnwindSafe};
+
+use rbac_playground::RbacPlayground;
+use soroban_sdk::{
+    testutils::Ledger as _,
+    xdr::{ContractEventBody, ContractEventType},
+    Address, Env, Symbol, TryFromVal, Val,
+};
+use stellar_xdr::curr::{
+    InvokeContractArgs, ScAddress, ScSymbol, ScVal, SorobanAuthorizationEntry,
+    SorobanAuthorizedFunction, SorobanAuthorizedInvocation, SorobanCredentials,
+};
+
+use crate::{Backend, Error, Identity, Transaction};
+
+/// A sandbox that closes one ledger, 5 seconds long, per transaction. It
+/// starts at ledger 100, 2023-11-14T22:13:20Z.
+///
+/// Calls are authorized by their source alone, as on a network: a call that
+/// needs another account's signature fails.
+pub struct Sandbox {
+    env: Env,
+    wasm: Option<Vec<u8>>,
+    transactions: u32,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sandbox {
+    /// A sandbox that runs the playground natively.
+    pub fn new() -> Self {
+        let env = Env::default();
+        env.ledger().set_sequence_number(100);
+        env.ledger().set_timestamp(1_700_000_000);
+        Sandbox {
+            env,
+            wasm: None,
+            transactions: 0,
+        }
+    }
+
+    /// A sandbox that runs the playground from its compiled `wasm`.
+    pub fn with_wasm(wasm: Vec<u8>) -> Self {
+        Sandbox {
+            wasm: Some(wasm),
+            ..Self::new()
+        }
+    }
+
+    /// The sandbox's environment, to query the contract after a run.
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    fn address(&self, address: &ScAddress) -> Result<Address, Error> {
+        Address::try_from_val(&self.env, address)
+            .map_err(|_| Error::Scenario(format!("`{address}` is not a valid address")))
+    }
+
+    fn vals(&self, args: &[ScVal]) -> Result<soroban_sdk::Vec<Val>, Error> {
+        let mut vals = soroban_sdk::Vec::new(&self.env);
+        for arg in args {
+            let val = Val::try_from_val(&self.env, arg)
+                .map_err(|_| Error::Scenario(format!("cannot pass {arg:?} to the contract")))?;
+            vals.push_back(val);
+        }
+        Ok(vals)
+    }
+
+    /// The transaction for the last invocation, then closes the ledger.
+    /// Call it before any other invocation, which replaces the events.
+    fn close(&mut self, returned: Option<ScVal>, error: Option<String>) -> Transaction {
+        self.transactions += 1;
+        let events = self
+            .env
+            .host()
+            .get_events()
+            .unwrap()
+            .0
+            .into_iter()
+            .filter(|e| !e.failed_call && e.event.type_ == ContractEventType::Contract)
+            .map(|e| e.event)
+            .collect();
+        let transaction = Transaction {
+            hash: Some(format!("{:064x}", self.transactions)),
+            ledger: self.env.ledger().sequence(),
+            application_order: 1,
+            timestamp: self.env.ledger().timestamp(),
+            returned,
+            events,
+            error,
+        };
+        self.advance(1).unwrap();
+        transaction
+    }
+
+    /// The message of the panic that failed the last invocation, from the
+    /// diagnostic event the host logs when it catches one.
+    fn panic_message(&self) -> Option<String> {
+        self.env
+            .host()
+            .get_events()
+            .ok()?
+            .0
+            .into_iter()
+            .filter(|e| e.event.type_ == ContractEventType::Diagnostic)
+            .find_map(|e| {
+                let ContractEventBody::V0(body) = e.event.body;
+                let log = match body.data {
+                    ScVal::String(s) => s.to_utf8_string_lossy(),
+                    ScVal::Vec(Some(items)) => match items.first() {
+                        Some(ScVal::String(s)) => s.to_utf8_string_lossy(),
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                let rest = log.strip_prefix("caught panic '")?;
+                Some(rest[..rest.find("' from contract function")?].to_string())
+            })
+    }
+}
+
+impl Backend for Sandbox {
+    fn allows_public_keys(&self) -> bool {
+        true
+    }
+
+    /// Accounts need no setup in the sandbox.
+    fn prepare(&mut self, _identities: &[Identity]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn deploy(
+        &mut self,
+        _deployer: &Identity,
+        constructor_args: Vec<ScVal>,
+    ) -> Result<(ScAddress, Transaction), Error> {
+        let args = self.vals(&constructor_args)?;
+        let env = &self.env;
+        let registered = panic::catch_unwind(AssertUnwindSafe(|| match &self.wasm {
+            Some(wasm) => env.register(wasm.as_slice(), args),
+            None => env.register(RbacPlayground, args),
+        }));
+        let contract = registered.map_err(|payload| {
+            let message = payload
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| payload.downcast_ref::<&str>().copied())
+                .unwrap_or("constructor panicked");
+            Error::Scenario(format!("deploy: {message}"))
+        })?;
+        let contract = ScAddress::from(&contract);
+        let returned = ScVal::Address(contract.clone());
+        Ok((contract, self.close(Some(returned), None)))
+    }
+
+    fn invoke(
+        &mut self,
+        source: &Identity,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Transaction, Error> {
+        let function_name = ScSymbol::try_from(function)
+            .map_err(|_| Error::Scenario(format!("`{function}` is not a valid symbol")))?;
+        // Authorize the call the way a network does: the transaction's
+        // source account signs for it.
+        self.env
+            .host()
+            .set_source_account(source.account_id())
+            .unwrap();
+        self.env.set_auths(&[SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::SourceAccount,
+            root_invocation: SorobanAuthorizedInvocation {
+                function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                    contract_address: contract.clone(),
+                    function_name,
+                    args: args.clone().try_into()?,
+                }),
+                sub_invocations: Default::default(),
+            },
+        }]);
+        let contract = self.address(contract)?;
+        let args = self.vals(&args)?;
+
+        let result = self.env.try_invoke_contract::<Val, soroban_sdk::Error>(
+            &contract,
+            &Symbol::new(&self.env, function),
+            args,
+        );
+        let (returned, error) = match result {
+            Ok(Ok(val)) => (ScVal::try_from_val(&self.env, &val).ok(), None),
+            Ok(Err(error)) => (None, Some(format!("{error:?}"))),
+            Err(error) => {
+                let error = match error {
+                    Ok(error) => format!("{error:?}"),
+                    Err(error) => format!("{error:?}"),
+                };
+                let error = match self.panic_message() {
+                    Some(message) => format!("{error}: {message}"),
+                    None => error,
+                };
+                (None, Some(error))
+            }
+        };
+        Ok(self.close(returned, error))
+    }
+
+    /// The ledger before the one the next transaction closes.
+    fn ledger(&mut self) -> Result<u32, Error> {
+        Ok(self.env.ledger().sequence() - 1)
+    }
+
+    fn advance(&mut self, ledgers: u32) -> Result<(), Error> {
+        self.env.ledger().with_mut(|info| {
+            info.sequence_number += ledgers;
+            info.timestamp += 5 * u64::from(ledgers);
+        });
+        Ok(())
+    }
+}
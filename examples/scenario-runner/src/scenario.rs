@@ -0,0 +1,456 @@
+//! The scenario file format.
+//!
+//! A scenario is a JSON object naming its identities, how to deploy the
+//! playground, and the steps to run:
+//!
+//! ```json
+//! {
+//!   "identities": ["admin", "alice"],
+//!   "deploy": { "admin": "admin", "owner": "admin", "initial_roles": [["viewer", "alice"]] },
+//!   "steps": [
+//!     { "op": "grant_role", "caller": "admin", "account": "alice", "role": "minter" },
+//!     { "op": "mint", "caller": "alice", "to": "alice", "amount": 1000 },
+//!     { "op": "advance_ledgers", "ledgers": 10 },
+//!     { "op": "burn", "caller": "alice", "from": "alice", "amount": 1, "expect": "failure" }
+//!   ]
+//! }
+//! ```
+//!
+//! Each `op` is a contract function, with the function's arguments as fields
+//! and identities given by name. Functions whose signer is not among their
+//! arguments take it as an extra field (`owner`, `admin`, `new_owner`,
+//! `new_admin`), and transfers take `live_for`, the ledgers after the latest
+//! closed one they stay live, instead of `live_until_ledger`.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use stellar_xdr::curr::{ScSymbol, ScVal, ScVec};
+
+use crate::Error;
+
+/// A scenario file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Names of the accounts the scenario uses.
+    pub identities: Vec<String>,
+    pub deploy: Deploy,
+    pub steps: Vec<Step>,
+}
+
+/// The playground's constructor arguments. `admin` deploys the contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deploy {
+    pub admin: String,
+    pub owner: String,
+    /// `(role, account)` pairs.
+    #[serde(default)]
+    pub initial_roles: Vec<(String, String)>,
+    /// `(account, amount)` pairs.
+    #[serde(default)]
+    pub initial_balances: Vec<(String, Amount)>,
+}
+
+/// One step: a contract call, or waiting for ledgers to close.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    #[serde(flatten)]
+    pub op: Op,
+    /// Whether the call should succeed; a step that does otherwise fails the
+    /// run.
+    #[serde(default)]
+    pub expect: Outcome,
+}
+
+/// Whether a step succeeded, or is expected to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    #[default]
+    Success,
+    Failure,
+}
+
+/// A token amount, written as a JSON integer or, beyond the 64-bit range,
+/// a decimal string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Amount(pub i128);
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match i64::try_from(self.0) {
+            Ok(amount) => serializer.serialize_i64(amount),
+            Err(_) => serializer.collect_str(&self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Integer(i64),
+            String(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Integer(amount) => Ok(Amount(amount.into())),
+            Repr::String(amount) => amount.parse().map(Amount).map_err(de::Error::custom),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op {
+    GrantRole {
+        caller: String,
+        account: String,
+        role: String,
+    },
+    RevokeRole {
+        caller: String,
+        account: String,
+        role: String,
+    },
+    RenounceRole {
+        caller: String,
+        role: String,
+    },
+    /// `(account, role)` pairs.
+    BatchGrantRoles {
+        grants: Vec<(String, String)>,
+        caller: String,
+    },
+    /// `(account, role)` pairs.
+    BatchRevokeRoles {
+        revocations: Vec<(String, String)>,
+        caller: String,
+    },
+    Mint {
+        to: String,
+        amount: Amount,
+        caller: String,
+    },
+    Burn {
+        from: String,
+        amount: Amount,
+        caller: String,
+    },
+    BatchMint {
+        recipients: Vec<String>,
+        amounts: Vec<Amount>,
+        caller: String,
+    },
+    BatchBurn {
+        accounts: Vec<String>,
+        amounts: Vec<Amount>,
+        caller: String,
+    },
+    ExecuteTransfer {
+        from: String,
+        to: String,
+        amount: Amount,
+        caller: String,
+    },
+    ProposeTransfer {
+        from: String,
+        to: String,
+        amount: Amount,
+        required_approvals: u32,
+        proposer: String,
+    },
+    ApproveTransfer {
+        id: u64,
+        approver: String,
+    },
+    Pause {
+        caller: String,
+        reason: String,
+    },
+    Unpause {
+        caller: String,
+    },
+    ViewSensitiveStats {
+        caller: String,
+    },
+    /// `live_for` 0 cancels a pending transfer.
+    TransferOwnership {
+        owner: String,
+        new_owner: String,
+        live_for: u32,
+    },
+    AcceptOwnership {
+        new_owner: String,
+    },
+    RenounceOwnership {
+        owner: String,
+    },
+    /// `live_for` 0 cancels a pending transfer.
+    TransferAdminRole {
+        admin: String,
+        new_admin: String,
+        live_for: u32,
+    },
+    AcceptAdminTransfer {
+        new_admin: String,
+    },
+    RenounceAdmin {
+        admin: String,
+    },
+    /// Closes, or waits for, this many ledgers.
+    AdvanceLedgers {
+        ledgers: u32,
+    },
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Checks that every identity a step names is declared.
+    pub fn validate(&self) -> Result<(), Error> {
+        let check = |context: &str, name: &str| {
+            if self.identities.iter().any(|identity| identity == name) {
+                Ok(())
+            } else {
+                Err(Error::Scenario(format!(
+                    "{context}: unknown identity `{name}`"
+                )))
+            }
+        };
+        check("deploy", &self.deploy.admin)?;
+        check("deploy", &self.deploy.owner)?;
+        for (_, account) in &self.deploy.initial_roles {
+            check("deploy", account)?;
+        }
+        for (account, _) in &self.deploy.initial_balances {
+            check("deploy", account)?;
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let context = format!("step {} ({})", index + 1, step.op.name());
+            for name in step.op.identities() {
+                check(&context, name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Op {
+    /// The contract function, or `advance_ledgers`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::GrantRole { .. } => "grant_role",
+            Op::RevokeRole { .. } => "revoke_role",
+            Op::RenounceRole { .. } => "renounce_role",
+            Op::BatchGrantRoles { .. } => "batch_grant_roles",
+            Op::BatchRevokeRoles { .. } => "batch_revoke_roles",
+            Op::Mint { .. } => "mint",
+            Op::Burn { .. } => "burn",
+            Op::BatchMint { .. } => "batch_mint",
+            Op::BatchBurn { .. } => "batch_burn",
+            Op::ExecuteTransfer { .. } => "execute_transfer",
+            Op::ProposeTransfer { .. } => "propose_transfer",
+            Op::ApproveTransfer { .. } => "approve_transfer",
+            Op::Pause { .. } => "pause",
+            Op::Unpause { .. } => "unpause",
+            Op::ViewSensitiveStats { .. } => "view_sensitive_stats",
+            Op::TransferOwnership { .. } => "transfer_ownership",
+            Op::AcceptOwnership { .. } => "accept_ownership",
+            Op::RenounceOwnership { .. } => "renounce_ownership",
+            Op::TransferAdminRole { .. } => "transfer_admin_role",
+            Op::AcceptAdminTransfer { .. } => "accept_admin_transfer",
+            Op::RenounceAdmin { .. } => "renounce_admin",
+            Op::AdvanceLedgers { .. } => "advance_ledgers",
+        }
+    }
+
+    /// The identity that signs the call; `None` for `advance_ledgers`.
+    pub fn signer(&self) -> Option<&str> {
+        match self {
+            Op::GrantRole { caller, .. }
+            | Op::RevokeRole { caller, .. }
+            | Op::RenounceRole { caller, .. }
+            | Op::BatchGrantRoles { caller, .. }
+            | Op::BatchRevokeRoles { caller, .. }
+            | Op::Mint { caller, .. }
+            | Op::Burn { caller, .. }
+            | Op::BatchMint { caller, .. }
+            | Op::BatchBurn { caller, .. }
+            | Op::ExecuteTransfer { caller, .. }
+            | Op::Pause { caller, .. }
+            | Op::Unpause { caller }
+            | Op::ViewSensitiveStats { caller } => Some(caller),
+            Op::ProposeTransfer { proposer, .. } => Some(proposer),
+            Op::ApproveTransfer { approver, .. } => Some(approver),
+            Op::TransferOwnership { owner, .. } | Op::RenounceOwnership { owner } => Some(owner),
+            Op::AcceptOwnership { new_owner } => Some(new_owner),
+            Op::TransferAdminRole { admin, .. } | Op::RenounceAdmin { admin } => Some(admin),
+            Op::AcceptAdminTransfer { new_admin } => Some(new_admin),
+            Op::AdvanceLedgers { .. } => None,
+        }
+    }
+
+    /// Every identity the step names.
+    fn identities(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.signer().into_iter().collect();
+        match self {
+            Op::GrantRole { account, .. } | Op::RevokeRole { account, .. } => names.push(account),
+            Op::BatchGrantRoles { grants: pairs, .. }
+            | Op::BatchRevokeRoles {
+                revocations: pairs, ..
+            } => names.extend(pairs.iter().map(|(account, _)| account.as_str())),
+            Op::Mint { to: account, .. } | Op::Burn { from: account, .. } => names.push(account),
+            Op::BatchMint {
+                recipients: accounts,
+                ..
+            }
+            | Op::BatchBurn { accounts, .. } => names.extend(accounts.iter().map(String::as_str)),
+            Op::ExecuteTransfer { from, to, .. } | Op::ProposeTransfer { from, to, .. } => {
+                names.push(from);
+                names.push(to);
+            }
+            Op::TransferOwnership { new_owner, .. } => names.push(new_owner),
+            Op::TransferAdminRole { new_admin, .. } => names.push(new_admin),
+            _ => {}
+        }
+        names
+    }
+
+    /// The call's arguments, with identities resolved by `address` and
+    /// `live_for` counted from `ledger`.
+    pub fn args(&self, address: impl Fn(&str) -> ScVal, ledger: u32) -> Result<Vec<ScVal>, Error> {
+        let live_until =
+            |live_for: u32| ScVal::U32(if live_for == 0 { 0 } else { ledger + live_for });
+        let pairs = |pairs: &[(String, String)]| -> Result<ScVal, Error> {
+            let entries = pairs
+                .iter()
+                .map(|(account, role)| list(vec![address(account), symbol(role)?]))
+                .collect::<Result<_, _>>()?;
+            list(entries)
+        };
+        let addresses = |names: &[String]| list(names.iter().map(|name| address(name)).collect());
+        let amounts = |amounts: &[Amount]| list(amounts.iter().map(|a| ScVal::from(a.0)).collect());
+
+        Ok(match self {
+            Op::GrantRole {
+                caller,
+                account,
+                role,
+            }
+            | Op::RevokeRole {
+                caller,
+                account,
+                role,
+            } => vec![address(caller), address(account), symbol(role)?],
+            Op::RenounceRole { caller, role } => vec![address(caller), symbol(role)?],
+            Op::BatchGrantRoles {
+                grants: entries,
+                caller,
+            }
+            | Op::BatchRevokeRoles {
+                revocations: entries,
+                caller,
+            } => vec![pairs(entries)?, address(caller)],
+            Op::Mint {
+                to: account,
+                amount,
+                caller,
+            }
+            | Op::Burn {
+                from: account,
+                amount,
+                caller,
+            } => vec![address(account), ScVal::from(amount.0), address(caller)],
+            Op::BatchMint {
+                recipients: accounts,
+                amounts: values,
+                caller,
+            }
+            | Op::BatchBurn {
+                accounts,
+                amounts: values,
+                caller,
+            } => vec![addresses(accounts)?, amounts(values)?, address(caller)],
+            Op::ExecuteTransfer {
+                from,
+                to,
+                amount,
+                caller,
+            } => vec![
+                address(from),
+                address(to),
+                ScVal::from(amount.0),
+                address(caller),
+            ],
+            Op::ProposeTransfer {
+                from,
+                to,
+                amount,
+                required_approvals,
+                proposer,
+            } => vec![
+                address(from),
+                address(to),
+                ScVal::from(amount.0),
+                ScVal::U32(*required_approvals),
+                address(proposer),
+            ],
+            Op::ApproveTransfer { id, approver } => vec![ScVal::U64(*id), address(approver)],
+            Op::Pause { caller, reason } => vec![address(caller), symbol(reason)?, ScVal::Void],
+            Op::Unpause { caller } | Op::ViewSensitiveStats { caller } => vec![address(caller)],
+            Op::TransferOwnership {
+                new_owner,
+                live_for,
+                ..
+            } => vec![address(new_owner), live_until(*live_for)],
+            Op::TransferAdminRole {
+                new_admin,
+                live_for,
+                ..
+            } => vec![address(new_admin), live_until(*live_for)],
+            Op::AcceptOwnership { .. }
+            | Op::RenounceOwnership { .. }
+            | Op::AcceptAdminTransfer { .. }
+            | Op::RenounceAdmin { .. }
+            | Op::AdvanceLedgers { .. } => vec![],
+        })
+    }
+}
+
+impl Deploy {
+    /// The constructor's arguments, with identities resolved by `address`.
+    pub fn args(&self, address: impl Fn(&str) -> ScVal) -> Result<Vec<ScVal>, Error> {
+        let roles = self
+            .initial_roles
+            .iter()
+            .map(|(role, account)| list(vec![symbol(role)?, address(account)]))
+            .collect::<Result<_, _>>()?;
+        let balances = if self.initial_balances.is_empty() {
+            ScVal::Void
+        } else {
+            list(
+                self.initial_balances
+                    .iter()
+                    .map(|(account, amount)| list(vec![address(account), ScVal::from(amount.0)]))
+                    .collect::<Result<_, _>>()?,
+            )?
+        };
+        Ok(vec![
+            address(&self.admin),
+            address(&self.owner),
+            list(roles)?,
+            balances,
+        ])
+    }
+}
+
+fn symbol(name: &str) -> Result<ScVal, Error> {
+    ScSymbol::try_from(name)
+        .map(ScVal::Symbol)
+        .map_err(|_| Error::Scenario(format!("`{name}` is not a valid symbol")))
+}
+
+fn list(values: Vec<ScVal>) -> Result<ScVal, Error> {
+    Ok(ScVal::Vec(Some(ScVec(values.try_into()?))))
+}
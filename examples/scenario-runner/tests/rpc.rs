@@ -0,0 +1,488 @@
+//! Runs scenarios against a fake Soroban RPC server.
+//!
+//! The server checks each submitted transaction's sequence number, fee and
+//! signature, then applies it to a sandbox and serves the sandbox's result as
+//! transaction meta. A run through it must report what a sandbox run does.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use rbac_scenario_runner::{
+    run, Backend, Error, Identity, Keys, Outcome, Rpc, Sandbox, Scenario, StepReport, BASE_FEE,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    AccountEntry, AccountId, ContractIdPreimage, HostFunction, InvokeHostFunctionResult,
+    LedgerEntryData, LedgerKey, Limits, MuxedAccount, OperationBody, OperationMetaV2,
+    OperationResult, OperationResultTr, PublicKey, ReadXdr, ScVal, SequenceNumber,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    SorobanCredentials, SorobanTransactionData, SorobanTransactionMetaV2, TransactionEnvelope,
+    TransactionMeta, TransactionMetaV4, TransactionResult, TransactionResultResult, Uint256,
+    WriteXdr,
+};
+
+const SEED: &str = "rpc-tests";
+
+fn keys() -> Keys {
+    Keys::Derived(SEED.to_string())
+}
+const PASSPHRASE: &str = "Standalone Network ; February 2017";
+const RESOURCE_FEE: u32 = 5_000;
+const WASM: &[u8] = b"\0asm not really";
+
+const SCENARIO: &str = r#"{
+    "identities": ["admin", "alice", "bob"],
+    "deploy": {"admin": "admin", "owner": "admin", "initial_roles": [["burner", "bob"]]},
+    "steps": [
+        {"op": "grant_role", "caller": "admin", "account": "alice", "role": "transfer"},
+        {"op": "mint", "to": "alice", "amount": 500, "caller": "admin"},
+        {"op": "burn", "from": "alice", "amount": 501, "caller": "bob", "expect": "failure"},
+        {"op": "propose_transfer", "from": "alice", "to": "bob", "amount": 100, "required_approvals": 1, "proposer": "alice"},
+        {"op": "transfer_ownership", "owner": "admin", "new_owner": "bob", "live_for": 20},
+        {"op": "advance_ledgers", "ledgers": 3},
+        {"op": "accept_ownership", "new_owner": "bob"}
+    ]
+}"#;
+
+/// A transaction the server accepted, and whether it was well-formed.
+#[derive(Debug)]
+struct Submitted {
+    source: [u8; 32],
+    sequence: i64,
+    expected_sequence: i64,
+    fee: u32,
+    signed: bool,
+}
+
+/// The fake server's state. It lives on the server thread, since the sandbox
+/// cannot cross threads.
+struct Server {
+    sandbox: Sandbox,
+    identities: BTreeMap<[u8; 32], Identity>,
+    accounts: BTreeMap<[u8; 32], i64>,
+    results: BTreeMap<String, Value>,
+    polled: BTreeSet<String>,
+    rejected_in_simulation: Option<&'static str>,
+    submitted: mpsc::Sender<Submitted>,
+}
+
+/// Starts a server on a free port and returns its base URL and the
+/// transactions it accepts.
+fn start(
+    scenario: &Scenario,
+    rejected_in_simulation: Option<&'static str>,
+) -> (String, mpsc::Receiver<Submitted>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    let names = scenario.identities.clone();
+    thread::spawn(move || {
+        let mut server = Server {
+            sandbox: Sandbox::new(),
+            identities: names
+                .iter()
+                .map(|name| {
+                    let identity = Identity::derive(SEED, name);
+                    (identity.public_key(), identity)
+                })
+                .collect(),
+            accounts: BTreeMap::new(),
+            results: BTreeMap::new(),
+            polled: BTreeSet::new(),
+            rejected_in_simulation,
+            submitted: sender,
+        };
+        for stream in listener.incoming() {
+            server.serve(stream.unwrap());
+        }
+    });
+    (url, receiver)
+}
+
+impl Server {
+    fn serve(&mut self, mut stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header == "\r\n" {
+                break;
+            }
+            if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let response = match request_line.split_whitespace().nth(1).unwrap() {
+            path if path.starts_with("/friendbot?addr=") => {
+                let account: AccountId = path["/friendbot?addr=".len()..].parse().unwrap();
+                self.accounts.insert(account_key(&account), 42 << 32);
+                json!({ "successful": true })
+            }
+            _ => {
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let method = request["method"].as_str().unwrap();
+                let result = self.handle(method, &request["params"]);
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+            }
+        };
+        // Chunked, which the client must decode. One request per connection.
+        let body = response.to_string();
+        let (first, rest) = body.split_at(body.len() / 2);
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n\
+             {:x}\r\n{first}\r\n{:x}\r\n{rest}\r\n0\r\n\r\n",
+            first.len(),
+            rest.len()
+        )
+        .unwrap();
+    }
+
+    fn handle(&mut self, method: &str, params: &Value) -> Value {
+        match method {
+            "getNetwork" => json!({ "passphrase": PASSPHRASE }),
+            // Polling for the latest ledger gives the network time to close
+            // one.
+            "getLatestLedger" => {
+                let sequence = self.sandbox.ledger().unwrap();
+                self.sandbox.advance(1).unwrap();
+                json!({ "sequence": sequence })
+            }
+            "getLedgerEntries" => {
+                let key =
+                    LedgerKey::from_xdr_base64(params["keys"][0].as_str().unwrap(), Limits::none())
+                        .unwrap();
+                let LedgerKey::Account(key) = key else {
+                    panic!("unexpected key {key:?}")
+                };
+                let entries: Vec<Value> = self
+                    .accounts
+                    .get(&account_key(&key.account_id))
+                    .map(|sequence| {
+                        let entry = LedgerEntryData::Account(AccountEntry {
+                            account_id: key.account_id.clone(),
+                            balance: 10_000_000_000,
+                            seq_num: SequenceNumber(*sequence),
+                            num_sub_entries: 0,
+                            inflation_dest: None,
+                            flags: 0,
+                            home_domain: Default::default(),
+                            thresholds: Default::default(),
+                            signers: Default::default(),
+                            ext: Default::default(),
+                        });
+                        json!({ "xdr": entry.to_xdr_base64(Limits::none()).unwrap() })
+                    })
+                    .into_iter()
+                    .collect();
+                json!({ "entries": entries })
+            }
+            "simulateTransaction" => self.simulate(&envelope(params)),
+            "sendTransaction" => self.send(envelope(params)),
+            "getTransaction" => {
+                let hash = params["hash"].as_str().unwrap().to_string();
+                // Not found on the first poll, as if still pending.
+                if self.polled.insert(hash.clone()) {
+                    return json!({ "status": "NOT_FOUND" });
+                }
+                self.results[&hash].clone()
+            }
+            _ => panic!("unexpected method {method}"),
+        }
+    }
+
+    fn simulate(&mut self, envelope: &TransactionEnvelope) -> Value {
+        let latest = self.sandbox.ledger().unwrap();
+        let function = host_function(envelope);
+        if let HostFunction::InvokeContract(args) = &function {
+            if Some(args.function_name.to_utf8_string_lossy().as_str())
+                == self.rejected_in_simulation
+            {
+                return json!({ "error": "HostError: rejected", "latestLedger": latest });
+            }
+        }
+        let auth = SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::SourceAccount,
+            root_invocation: SorobanAuthorizedInvocation {
+                function: match function {
+                    HostFunction::InvokeContract(args) => {
+                        SorobanAuthorizedFunction::ContractFn(args)
+                    }
+                    HostFunction::CreateContractV2(args) => {
+                        SorobanAuthorizedFunction::CreateContractV2HostFn(args)
+                    }
+                    _ => return self.simulated(latest, vec![]),
+                },
+                sub_invocations: Default::default(),
+            },
+        };
+        self.simulated(latest, vec![auth])
+    }
+
+    fn simulated(&self, latest: u32, auth: Vec<SorobanAuthorizationEntry>) -> Value {
+        let auth: Vec<String> = auth
+            .iter()
+            .map(|entry| entry.to_xdr_base64(Limits::none()).unwrap())
+            .collect();
+        json!({
+            "transactionData": SorobanTransactionData::default().to_xdr_base64(Limits::none()).unwrap(),
+            "minResourceFee": RESOURCE_FEE.to_string(),
+            "results": [{ "auth": auth, "xdr": ScVal::Void.to_xdr_base64(Limits::none()).unwrap() }],
+            "latestLedger": latest,
+        })
+    }
+
+    fn send(&mut self, envelope: TransactionEnvelope) -> Value {
+        let hash = envelope.hash(Sha256::digest(PASSPHRASE).into()).unwrap();
+        let TransactionEnvelope::Tx(signed) = &envelope else {
+            panic!("unexpected envelope {envelope:?}")
+        };
+        let MuxedAccount::Ed25519(Uint256(source)) = signed.tx.source_account else {
+            panic!("unexpected source {:?}", signed.tx.source_account)
+        };
+        let signature = &signed.signatures[0];
+        let signed_by_source = signature.hint.0 == source[28..]
+            && VerifyingKey::from_bytes(&source)
+                .unwrap()
+                .verify(
+                    &hash,
+                    &Signature::from_slice(signature.signature.as_slice()).unwrap(),
+                )
+                .is_ok();
+        let expected_sequence = self.accounts[&source] + 1;
+        self.submitted
+            .send(Submitted {
+                source,
+                sequence: signed.tx.seq_num.0,
+                expected_sequence,
+                fee: signed.tx.fee,
+                signed: signed_by_source,
+            })
+            .unwrap();
+        self.accounts.insert(source, signed.tx.seq_num.0);
+
+        let identity = &self.identities[&source];
+        let transaction = match host_function(&envelope) {
+            HostFunction::UploadContractWasm(_) => {
+                let ledger = self.sandbox.ledger().unwrap() + 1;
+                self.sandbox.advance(1).unwrap();
+                rbac_scenario_runner::Transaction {
+                    ledger,
+                    returned: Some(ScVal::Bytes(Default::default())),
+                    ..Default::default()
+                }
+            }
+            HostFunction::CreateContractV2(args) => {
+                assert!(matches!(
+                    args.contract_id_preimage,
+                    ContractIdPreimage::Address(_)
+                ));
+                let (contract, mut transaction) = self
+                    .sandbox
+                    .deploy(identity, args.constructor_args.to_vec())
+                    .unwrap();
+                transaction.returned = Some(ScVal::Address(contract));
+                transaction
+            }
+            HostFunction::InvokeContract(args) => self
+                .sandbox
+                .invoke(
+                    identity,
+                    &args.contract_address,
+                    &args.function_name.to_utf8_string_lossy(),
+                    args.args.to_vec(),
+                )
+                .unwrap(),
+            function => panic!("unexpected host function {function:?}"),
+        };
+
+        let meta = TransactionMeta::V4(TransactionMetaV4 {
+            operations: vec![OperationMetaV2 {
+                ext: Default::default(),
+                changes: Default::default(),
+                events: transaction.events.clone().try_into().unwrap(),
+            }]
+            .try_into()
+            .unwrap(),
+            soroban_meta: Some(SorobanTransactionMetaV2 {
+                ext: Default::default(),
+                return_value: transaction.returned.clone(),
+            }),
+            ..Default::default()
+        });
+        let result = TransactionResult {
+            fee_charged: i64::from(signed.tx.fee),
+            result: match &transaction.error {
+                None => TransactionResultResult::TxSuccess(
+                    vec![OperationResult::OpInner(
+                        OperationResultTr::InvokeHostFunction(InvokeHostFunctionResult::Success(
+                            Default::default(),
+                        )),
+                    )]
+                    .try_into()
+                    .unwrap(),
+                ),
+                Some(_) => TransactionResultResult::TxFailed(
+                    vec![OperationResult::OpInner(
+                        OperationResultTr::InvokeHostFunction(InvokeHostFunctionResult::Trapped),
+                    )]
+                    .try_into()
+                    .unwrap(),
+                ),
+            },
+            ext: Default::default(),
+        };
+        let hash = hex(&hash);
+        self.results.insert(
+            hash.clone(),
+            json!({
+                "status": if transaction.error.is_none() { "SUCCESS" } else { "FAILED" },
+                "ledger": transaction.ledger,
+                "createdAt": transaction.timestamp.to_string(),
+                "applicationOrder": 1,
+                "resultXdr": result.to_xdr_base64(Limits::none()).unwrap(),
+                "resultMetaXdr": meta.to_xdr_base64(Limits::none()).unwrap(),
+            }),
+        );
+        json!({ "status": "PENDING", "hash": hash })
+    }
+}
+
+fn envelope(params: &Value) -> TransactionEnvelope {
+    TransactionEnvelope::from_xdr_base64(params["transaction"].as_str().unwrap(), Limits::none())
+        .unwrap()
+}
+
+fn host_function(envelope: &TransactionEnvelope) -> HostFunction {
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("unexpected envelope {envelope:?}")
+    };
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("unexpected operation {:?}", envelope.tx.operations[0])
+    };
+    op.host_function.clone()
+}
+
+fn account_key(account: &AccountId) -> [u8; 32] {
+    let AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key))) = account;
+    *key
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn connect(url: &str) -> Rpc {
+    Rpc::connect(&format!("{url}/rpc"), WASM.to_vec())
+        .unwrap()
+        .with_friendbot(&format!("{url}/friendbot"))
+        .with_poll_interval(Duration::from_millis(1))
+}
+
+/// The parts of a report that do not depend on the backend's ledgers and
+/// hashes. The fake closes a ledger whenever it is polled, so ledgers differ
+/// from a sandbox run's.
+fn outcome(report: &StepReport) -> Value {
+    let events: Vec<Value> = report
+        .events
+        .iter()
+        .map(|event| {
+            let mut event = serde_json::to_value(&event.decoded).unwrap();
+            event.as_object_mut().unwrap().remove("live_until_ledger");
+            event
+        })
+        .collect();
+    json!([report.op, report.status, report.returned, events])
+}
+
+#[test]
+fn rpc_run_matches_sandbox_run() {
+    let scenario = Scenario::from_json(SCENARIO).unwrap();
+    let (url, submitted) = start(&scenario, None);
+    let reports = run(&scenario, &mut connect(&url), &keys(), |_| {}).unwrap();
+    let expected = run(&scenario, &mut Sandbox::new(), &keys(), |_| {}).unwrap();
+
+    assert!(reports.iter().all(StepReport::as_expected), "{reports:#?}");
+    assert_eq!(
+        reports.iter().map(outcome).collect::<Vec<_>>(),
+        expected.iter().map(outcome).collect::<Vec<_>>()
+    );
+    let burn = &reports[3];
+    assert_eq!(burn.status, Outcome::Failure);
+    assert_eq!(burn.error.as_deref(), Some("TxFailed: Trapped"));
+    assert!(burn.tx_hash.is_some());
+
+    // Upload, create, then one transaction per contract call.
+    let submitted: Vec<Submitted> = submitted.try_iter().collect();
+    assert_eq!(submitted.len(), 2 + 6);
+    for transaction in &submitted {
+        assert!(transaction.signed, "{transaction:?}");
+        assert_eq!(transaction.sequence, transaction.expected_sequence);
+        assert_eq!(transaction.fee, BASE_FEE + RESOURCE_FEE);
+    }
+    let sources: Vec<[u8; 32]> = submitted.iter().map(|t| t.source).collect();
+    let signers = [
+        "admin", "admin", "admin", "admin", "bob", "alice", "admin", "bob",
+    ];
+    assert_eq!(
+        sources,
+        signers.map(|name| Identity::derive(SEED, name).public_key())
+    );
+
+    // Event ids follow the transaction's ledger and position in it.
+    let mint = &reports[2];
+    assert_eq!(
+        mint.events[0].id,
+        format!(
+            "{:019}-{:010}",
+            (u64::from(mint.ledger) << 32) | (1 << 12),
+            0
+        )
+    );
+}
+
+#[test]
+fn calls_rejected_in_simulation_are_not_submitted() {
+    let scenario = Scenario::from_json(SCENARIO).unwrap();
+    let (url, submitted) = start(&scenario, Some("mint"));
+    let reports = run(&scenario, &mut connect(&url), &keys(), |_| {}).unwrap();
+
+    let mint = &reports[2];
+    assert_eq!(mint.status, Outcome::Failure);
+    assert_eq!(mint.tx_hash, None);
+    assert_eq!(
+        mint.error.as_deref(),
+        Some("simulation failed: HostError: rejected")
+    );
+    assert!(mint.events.is_empty());
+    // Only the mint was not submitted, and no sequence number was skipped.
+    let submitted: Vec<Submitted> = submitted.try_iter().collect();
+    assert_eq!(submitted.len(), 2 + 5);
+    assert!(submitted
+        .iter()
+        .all(|transaction| transaction.sequence == transaction.expected_sequence));
+}
+
+#[test]
+fn public_sandbox_keys_are_refused() {
+    let scenario = Scenario::from_json(SCENARIO).unwrap();
+    let (url, submitted) = start(&scenario, None);
+
+    match run(&scenario, &mut connect(&url), &Keys::sandbox(), |_| {}) {
+        Err(Error::Scenario(message)) => assert!(message.contains("sandbox only"), "{message}"),
+        other => panic!("expected a scenario error, got {other:?}"),
+    }
+    assert_eq!(submitted.try_iter().count(), 0);
+}
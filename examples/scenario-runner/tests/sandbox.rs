@@ -0,0 +1,350 @@
+//! Runs the checked-in scenarios in the sandbox, and replays their events.
+
+use std::process::Command;
+use std::str::FromStr;
+
+use rbac_playground::RbacPlaygroundClient;
+use rbac_scenario_runner::{
+    run, Error, Identity, Keys, Op, Outcome, Sandbox, Scenario, StepReport,
+};
+use rbac_state_reconstructor::{read_events, LedgerEvent, ReplayOptions, State};
+use soroban_sdk::{Address, TryFromVal};
+use stellar_strkey::ed25519::PrivateKey;
+use stellar_xdr::curr::ScAddress;
+
+const SEED: &str = "scenario-tests";
+
+fn keys() -> Keys {
+    Keys::Derived(SEED.to_string())
+}
+
+/// A `list_*` view of the members of one role.
+type RoleList = fn(&RbacPlaygroundClient<'_>) -> soroban_sdk::Vec<Address>;
+
+/// Every role with a `list_*` view, and that view.
+const ROLE_LISTS: [(&str, RoleList); 7] = [
+    ("operator", |c| c.list_operators()),
+    ("minter", |c| c.list_minters()),
+    ("burner", |c| c.list_burners()),
+    ("pauser", |c| c.list_pausers()),
+    ("viewer", |c| c.list_viewers()),
+    ("transfer", |c| c.list_transferers()),
+    ("approver", |c| c.list_approvers()),
+];
+
+fn scenario(name: &str) -> Scenario {
+    let path = format!("{}/scenarios/{name}", env!("CARGO_MANIFEST_DIR"));
+    Scenario::from_json(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn run_in_sandbox(scenario: &Scenario) -> (Sandbox, Vec<StepReport>) {
+    let mut sandbox = Sandbox::new();
+    let reports = run(scenario, &mut sandbox, &keys(), |_| {}).unwrap();
+    (sandbox, reports)
+}
+
+fn events(reports: &[StepReport]) -> Vec<LedgerEvent> {
+    reports
+        .iter()
+        .flat_map(|report| report.events.iter().cloned())
+        .collect()
+}
+
+fn account(name: &str) -> String {
+    Identity::derive(SEED, name).account_id().to_string()
+}
+
+/// Asserts that replaying the run's events gives the roles, owner and admin
+/// the contract reports.
+fn assert_replay_matches_views(sandbox: &Sandbox, reports: &[StepReport]) -> State {
+    let contract = reports[0].contract.clone().unwrap();
+    let env = sandbox.env();
+    let address = Address::try_from_val(env, &ScAddress::from_str(&contract).unwrap()).unwrap();
+    let client = RbacPlaygroundClient::new(env, &address);
+    let state = State::replay_with(ReplayOptions::EXTENDED, &events(reports));
+
+    for (role, list) in ROLE_LISTS {
+        let mut on_chain: Vec<String> = list(&client)
+            .iter()
+            .map(|a| a.to_string().to_string())
+            .collect();
+        on_chain.sort();
+        assert_eq!(
+            state.role_members(&contract, role),
+            on_chain,
+            "members of {role}"
+        );
+    }
+    assert_eq!(
+        state.owner(&contract).map(str::to_string),
+        client
+            .get_owner()
+            .map(|owner| owner.to_string().to_string()),
+    );
+    assert_eq!(
+        state.admin(&contract).map(str::to_string),
+        client
+            .get_admin()
+            .map(|admin| admin.to_string().to_string()),
+    );
+    state
+}
+
+#[test]
+fn test_data_scenario_runs_as_expected() {
+    let scenario = scenario("test-data.json");
+    let (sandbox, reports) = run_in_sandbox(&scenario);
+
+    assert_eq!(reports.len(), scenario.steps.len() + 1);
+    for report in &reports {
+        assert!(report.as_expected(), "{report:?}");
+        if report.status == Outcome::Failure {
+            assert!(report.error.is_some());
+            assert!(report.events.is_empty());
+        }
+    }
+
+    let deploy = &reports[0];
+    assert_eq!(deploy.op, "deploy");
+    assert_eq!(deploy.ledger, 100);
+    assert_eq!(
+        deploy.identities.as_ref().unwrap()["alice"],
+        account("alice")
+    );
+    // Each transaction closes a ledger, and `advance_ledgers` skips ahead.
+    let mut ledger = 100;
+    for (report, step) in reports[1..].iter().zip(&scenario.steps) {
+        ledger += match step.op {
+            Op::AdvanceLedgers { ledgers } => ledgers,
+            _ => 1,
+        };
+        assert_eq!(report.ledger, ledger, "step {}", report.step);
+    }
+
+    let burn = reports
+        .iter()
+        .find(|report| report.op == "burn" && report.expected == Outcome::Failure)
+        .unwrap();
+    assert!(burn
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("Insufficient balance"));
+    let proposal = reports
+        .iter()
+        .find(|report| report.op == "propose_transfer")
+        .unwrap();
+    assert_eq!(proposal.returned, Some(serde_json::json!(0)));
+
+    let state = assert_replay_matches_views(&sandbox, &reports);
+    let contract = deploy.contract.as_ref().unwrap();
+    assert_eq!(state.owner(contract), Some(account("alice").as_str()));
+    assert_eq!(state.admin(contract), Some(account("bob").as_str()));
+}
+
+#[test]
+fn many_accounts_scenario_grants_every_role() {
+    let scenario = scenario("many-accounts.json");
+    let (sandbox, reports) = run_in_sandbox(&scenario);
+
+    assert!(reports.iter().all(StepReport::as_expected));
+    let granted: usize = reports[1..]
+        .iter()
+        .map(|report| {
+            report
+                .events
+                .iter()
+                .filter(|event| serde_json::to_value(event).unwrap()["event"] == "role_granted")
+                .count()
+        })
+        .sum();
+    assert_eq!(granted, 53);
+
+    // The constructor also makes the admin a minter and an operator.
+    let state = assert_replay_matches_views(&sandbox, &reports);
+    assert_eq!(state.role_memberships.len(), 55);
+}
+
+#[test]
+fn identities_are_derived_from_the_seed() {
+    assert_eq!(
+        Identity::derive("a", "alice").account_id(),
+        Identity::derive("a", "alice").account_id()
+    );
+    assert_ne!(
+        Identity::derive("a", "alice").account_id(),
+        Identity::derive("b", "alice").account_id()
+    );
+    assert_ne!(
+        Identity::derive("a", "alice").account_id(),
+        Identity::derive("a", "bob").account_id()
+    );
+}
+
+#[test]
+fn key_files_give_each_identity_its_secret_key() {
+    let scenario = Scenario::from_json(
+        r#"{
+            "identities": ["admin", "alice"],
+            "deploy": {"admin": "admin", "owner": "admin"},
+            "steps": [{"op": "grant_role", "caller": "admin", "account": "alice", "role": "minter"}]
+        }"#,
+    )
+    .unwrap();
+    let seed = |n: u8| PrivateKey([n; 32]).to_string();
+    let keys = Keys::from_json(&format!(
+        r#"{{"admin": "{}", "alice": "{}"}}"#,
+        seed(1),
+        seed(2)
+    ))
+    .unwrap();
+
+    let reports = run(&scenario, &mut Sandbox::new(), &keys, |_| {}).unwrap();
+    assert!(reports.iter().all(StepReport::as_expected));
+    let identities = reports[0].identities.as_ref().unwrap();
+    assert_eq!(
+        identities["admin"],
+        Identity::from_secret("admin", &[1; 32])
+            .account_id()
+            .to_string()
+    );
+    assert_eq!(
+        identities["alice"],
+        Identity::from_secret("alice", &[2; 32])
+            .account_id()
+            .to_string()
+    );
+
+    let partial = Keys::from_json(&format!(r#"{{"admin": "{}"}}"#, seed(1))).unwrap();
+    match run(&scenario, &mut Sandbox::new(), &partial, |_| {}) {
+        Err(Error::Scenario(message)) => assert_eq!(message, "no key for identity `alice`"),
+        other => panic!("expected a scenario error, got {other:?}"),
+    }
+    let account_id = Identity::from_secret("admin", &[1; 32]).account_id();
+    assert!(Keys::from_json(&format!(r#"{{"admin": "{account_id}"}}"#)).is_err());
+}
+
+#[test]
+fn unknown_identities_are_rejected() {
+    let scenario = Scenario::from_json(
+        r#"{
+            "identities": ["admin"],
+            "deploy": {"admin": "admin", "owner": "admin"},
+            "steps": [{"op": "mint", "to": "mallory", "amount": 1, "caller": "admin"}]
+        }"#,
+    )
+    .unwrap();
+    match run(&scenario, &mut Sandbox::new(), &keys(), |_| {}) {
+        Err(Error::Scenario(message)) => {
+            assert_eq!(message, "step 1 (mint): unknown identity `mallory`")
+        }
+        other => panic!("expected a scenario error, got {other:?}"),
+    }
+}
+
+#[test]
+fn amounts_beyond_64_bits_are_strings() {
+    let scenario = Scenario::from_json(
+        r#"{
+            "identities": ["admin"],
+            "deploy": {"admin": "admin", "owner": "admin", "initial_roles": [["minter", "admin"]]},
+            "steps": [
+                {"op": "mint", "to": "admin", "amount": "100000000000000000000", "caller": "admin"}
+            ]
+        }"#,
+    )
+    .unwrap();
+    let (_, reports) = run_in_sandbox(&scenario);
+    assert!(reports.iter().all(StepReport::as_expected));
+    let minted = serde_json::to_value(&reports[1].events[0]).unwrap();
+    assert_eq!(minted["amount"], "100000000000000000000");
+}
+
+#[test]
+fn cli_prints_reports_and_writes_events() {
+    let events_path = format!("{}/cli-events.jsonl", env!("CARGO_TARGET_TMPDIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_rbac-scenario-runner"))
+        .args(["--seed", SEED, "--events", &events_path])
+        .arg(format!(
+            "{}/scenarios/test-data.json",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let (_, reports) = run_in_sandbox(&scenario("test-data.json"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let printed: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let expected: Vec<serde_json::Value> = reports
+        .iter()
+        .map(|report| serde_json::to_value(report).unwrap())
+        .collect();
+    assert_eq!(printed, expected);
+
+    let written = read_events(std::io::BufReader::new(
+        std::fs::File::open(&events_path).unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(written, events(&reports));
+}
+
+#[test]
+fn cli_needs_secret_keys_for_rpc() {
+    let scenario = format!("{}/scenarios/test-data.json", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_rbac-scenario-runner"))
+        .args([
+            "--rpc",
+            "http://127.0.0.1:1/rpc",
+            "--wasm",
+            "playground.wasm",
+        ])
+        .arg(&scenario)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("usage:"));
+}
+
+#[test]
+fn cli_fails_when_a_step_is_unexpected() {
+    let path = format!("{}/unexpected.json", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(
+        &path,
+        r#"{
+            "identities": ["admin", "alice"],
+            "deploy": {"admin": "admin", "owner": "admin"},
+            "steps": [
+                {"op": "grant_role", "caller": "alice", "account": "alice", "role": "minter"},
+                {"op": "grant_role", "caller": "admin", "account": "alice", "role": "minter", "expect": "failure"}
+            ]
+        }"#,
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rbac-scenario-runner"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 3);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("step 1 (grant_role) failed unexpectedly"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("step 2 (grant_role) succeeded unexpectedly"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,276 @@
+//! Generates indexer definitions from the contract events of a built wasm.
+//!
+//! Every `#[contractevent]` the contract links, its own and those of
+//! `stellar-access` and `stellar-contract-utils`, has an `EventV0` entry in
+//! the contract spec. [`graphql`] turns each into an `@entity` type with a
+//! field per parameter, plus a `PlaygroundEventType` enum with a value per
+//! event, and [`topic_filters`] into the filter a SubQuery event handler
+//! subscribes with.
+//!
+//! The indexer's own `schema.graphql` records the `stellar-access` events as
+//! `AccessControlEvent`s, one `EventType` value each. The entities of the
+//! [`unhandled`] events are checked in as `schema/playground.graphql`, for the
+//! indexer to take up once it handles them, and the filters of all events as
+//! `schema/topics.json`. The tests check both files against the contract, and
+//! the indexer's `schema.graphql` and `project.ts` against `topics.json`.
+
+use std::fmt::Write as _;
+
+use serde_json::{json, Value};
+use soroban_spec::read::FromWasmError;
+use stellar_xdr::curr::{ScSpecEntry, ScSpecEventParamLocationV0, ScSpecEventV0, ScSpecTypeDef};
+
+/// The file [`graphql`] is checked in as, under `schema/`.
+pub const GRAPHQL_FILE: &str = "playground.graphql";
+
+/// The file [`topic_filters`] is checked in as, under `schema/`.
+pub const TOPICS_FILE: &str = "topics.json";
+
+/// The entity the indexer records events with an `EventType` value as.
+pub const HANDLED_ENTITY: &str = "AccessControlEvent";
+
+/// Events whose `EventType` value is not their name in capitals, after the
+/// handler that records them.
+const EVENT_TYPE_ALIASES: [(&str, &str); 1] = [("OwnershipTransfer", "OWNERSHIP_TRANSFER_STARTED")];
+
+/// The fields every event entity has, before its parameters.
+const COMMON_FIELDS: [(&str, &str); 2] = [
+    ("id", "ID! # txHash-logIndex"),
+    ("contract", "String! @index"),
+];
+
+/// The fields every event entity has, after its parameters.
+const CONTEXT_FIELDS: [(&str, &str); 3] = [
+    ("ledger", "Int!"),
+    ("timestamp", "Date! @index"),
+    ("txHash", "String!"),
+];
+
+/// The event entries of the spec in `wasm`, by name.
+pub fn events(wasm: &[u8]) -> Result<Vec<ScSpecEventV0>, FromWasmError> {
+    let mut events: Vec<ScSpecEventV0> = soroban_spec::read::from_wasm(wasm)?
+        .into_iter()
+        .filter_map(|entry| match entry {
+            ScSpecEntry::EventV0(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    events.sort_by_key(|event| event.name.to_utf8_string_lossy());
+    Ok(events)
+}
+
+/// A GraphQL entity per event, then the `PlaygroundEventType` enum.
+///
+/// Fields are the camelCase parameter names. Those that clash with the
+/// common fields (`id`, `contract`, `ledger`, ...) get the first word of the
+/// event name in front, so `TransferApproved`'s `id` becomes `transferId`.
+/// Topic parameters are indexed, like the events' `contract`.
+pub fn graphql(events: &[ScSpecEventV0]) -> String {
+    let mut schema =
+        String::from("# Generated by rbac-schema-gen from the playground's contract events.\n");
+    schema.push_str(
+        "# Do not edit; regenerate with UPDATE_SCHEMA=1 cargo test -p rbac-schema-gen.\n",
+    );
+    for event in events {
+        let name = event.name.to_utf8_string_lossy();
+        schema.push('\n');
+        comment(&mut schema, &event.doc.to_utf8_string_lossy());
+        writeln!(schema, "type {name}Event @entity {{").unwrap();
+        for (field, type_) in COMMON_FIELDS {
+            writeln!(schema, "  {field}: {type_}").unwrap();
+        }
+        for param in event.params.iter() {
+            let mut field = camel_case(&param.name.to_utf8_string_lossy());
+            if COMMON_FIELDS
+                .iter()
+                .chain(&CONTEXT_FIELDS)
+                .any(|(common, _)| *common == field)
+            {
+                field = format!("{}{}", first_word(&name), capitalized(&field));
+            }
+            let index = match param.location {
+                ScSpecEventParamLocationV0::TopicList => " @index",
+                ScSpecEventParamLocationV0::Data => "",
+            };
+            writeln!(schema, "  {field}: {}{index}", field_type(&param.type_)).unwrap();
+        }
+        for (field, type_) in CONTEXT_FIELDS {
+            writeln!(schema, "  {field}: {type_}").unwrap();
+        }
+        schema.push_str("}\n");
+    }
+
+    schema.push_str("\n# Every event above\nenum PlaygroundEventType {\n");
+    for event in events {
+        writeln!(
+            schema,
+            "  {}",
+            event_type(&event.name.to_utf8_string_lossy())
+        )
+        .unwrap();
+    }
+    schema.push_str("}\n");
+    schema
+}
+
+/// The values of the `EventType` enum in the indexer's `schema`.
+pub fn event_types(schema: &str) -> Vec<&str> {
+    let Some(start) = schema.find("enum EventType {\n") else {
+        return Vec::new();
+    };
+    schema[start..]
+        .lines()
+        .skip(1)
+        .take_while(|line| line.trim() != "}")
+        .filter_map(|line| line.split('#').next().map(str::trim))
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// The events the indexer's `schema` has no `EventType` value for, which it
+/// would need [`graphql`]'s entities to record.
+pub fn unhandled(schema: &str, events: &[ScSpecEventV0]) -> Vec<ScSpecEventV0> {
+    let handled = event_types(schema);
+    events
+        .iter()
+        .filter(|event| {
+            !handled.contains(&handled_type(&event.name.to_utf8_string_lossy()).as_str())
+        })
+        .cloned()
+        .collect()
+}
+
+/// A JSON list with, for each event, the entity the indexer's `schema`
+/// records it as, that entity's type value and the `filter` a SubQuery
+/// handler for it needs. Events with an `EventType` value are
+/// `AccessControlEvent`s; the others have their [`graphql`] entity and
+/// `PlaygroundEventType` value.
+pub fn topic_filters(schema: &str, events: &[ScSpecEventV0]) -> Value {
+    let handled = event_types(schema);
+    Value::Array(
+        events
+            .iter()
+            .map(|event| {
+                let name = event.name.to_utf8_string_lossy();
+                let (entity, type_) = match handled_type(&name) {
+                    type_ if handled.contains(&type_.as_str()) => {
+                        (HANDLED_ENTITY.to_string(), type_)
+                    }
+                    _ => (format!("{name}Event"), event_type(&name)),
+                };
+                let topics: Vec<String> = event
+                    .prefix_topics
+                    .iter()
+                    .map(|topic| topic.to_utf8_string_lossy())
+                    .collect();
+                json!({
+                    "event": name,
+                    "entity": entity,
+                    "type": type_,
+                    "filter": { "topics": topics },
+                })
+            })
+            .collect(),
+    )
+}
+
+/// The first topic of every `topics: [...]` filter in a SubQuery project
+/// manifest's `source`, whatever its quotes and spacing.
+pub fn handler_topics(source: &str) -> Vec<&str> {
+    source
+        .split("topics:")
+        .skip(1)
+        .filter_map(|rest| {
+            let list = rest.trim_start().strip_prefix('[')?;
+            let first = list[..list.find(']')?].split(',').next()?;
+            Some(first.trim().trim_matches(['\'', '"', '`']))
+        })
+        .collect()
+}
+
+/// The GraphQL type of a field holding a `type_` parameter. Integers wider
+/// than 32 bits are `BigInt`s, and composite types `JSON`.
+fn field_type(type_: &ScSpecTypeDef) -> String {
+    if let ScSpecTypeDef::Option(option) = type_ {
+        let inner = field_type(&option.value_type);
+        return inner.strip_suffix('!').unwrap_or(&inner).to_string();
+    }
+    let name = match type_ {
+        ScSpecTypeDef::Bool => "Boolean".to_string(),
+        ScSpecTypeDef::U32 | ScSpecTypeDef::I32 => "Int".to_string(),
+        ScSpecTypeDef::U64
+        | ScSpecTypeDef::I64
+        | ScSpecTypeDef::U128
+        | ScSpecTypeDef::I128
+        | ScSpecTypeDef::U256
+        | ScSpecTypeDef::I256
+        | ScSpecTypeDef::Timepoint
+        | ScSpecTypeDef::Duration => "BigInt".to_string(),
+        // Addresses and symbols as strkeys and text, bytes as hex.
+        ScSpecTypeDef::Address
+        | ScSpecTypeDef::MuxedAddress
+        | ScSpecTypeDef::Symbol
+        | ScSpecTypeDef::String
+        | ScSpecTypeDef::Bytes
+        | ScSpecTypeDef::BytesN(_) => "String".to_string(),
+        ScSpecTypeDef::Vec(vec) => match field_type(&vec.element_type).as_str() {
+            "JSON!" => "JSON".to_string(),
+            element => format!("[{element}]"),
+        },
+        _ => "JSON".to_string(),
+    };
+    format!("{name}!")
+}
+
+/// The `PlaygroundEventType` value of the event `name`: `ROLE_GRANTED` for
+/// `RoleGranted`.
+fn event_type(name: &str) -> String {
+    let mut value = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            value.push('_');
+        }
+        value.push(c.to_ascii_uppercase());
+    }
+    value
+}
+
+/// The `EventType` value the indexer records the event `name` with, if it
+/// does.
+fn handled_type(name: &str) -> String {
+    EVENT_TYPE_ALIASES
+        .iter()
+        .find(|(event, _)| *event == name)
+        .map_or_else(|| event_type(name), |(_, value)| value.to_string())
+}
+
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let first = words.next().unwrap_or_default().to_string();
+    words.fold(first, |field, word| field + &capitalized(word))
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// `transfer` for `TransferApproved`.
+fn first_word(name: &str) -> String {
+    let end = name
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| c.is_ascii_uppercase())
+        .map_or(name.len(), |(i, _)| i);
+    name[..end].to_ascii_lowercase()
+}
+
+/// Writes `text` as `#` comment lines.
+fn comment(schema: &mut String, text: &str) {
+    for line in text.lines() {
+        writeln!(schema, "# {line}").unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+//! Writes the GraphQL definitions and topic filters for a playground wasm.
+//!
+//! ```text
+//! rbac-schema-gen [--check] WASM SCHEMA DIR
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use rbac_schema_gen::{events, graphql, topic_filters, unhandled, GRAPHQL_FILE, TOPICS_FILE};
+
+const USAGE: &str = "usage: rbac-schema-gen [--check] WASM SCHEMA DIR
+
+Reads the contract events from the spec of the wasm at WASM and writes the
+GraphQL entities and enum of those the indexer's schema.graphql at SCHEMA has
+no EventType value for to DIR/playground.graphql, and every event's topic
+filter to DIR/topics.json. SCHEMA is only read.
+
+With --check, writes nothing and fails when either file is out of date.";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.as_slice(), [arg] if arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let check = args.first().is_some_and(|arg| arg == "--check");
+    if check {
+        args.remove(0);
+    }
+    let [wasm, schema_path, dir] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let read = |path: &String| fs::read(path).map_err(|error| format!("{path}: {error}"));
+    let inputs = read(wasm)
+        .and_then(|bytes| events(&bytes).map_err(|error| format!("{wasm}: {error}")))
+        .and_then(|events| {
+            let schema = String::from_utf8(read(schema_path)?)
+                .map_err(|error| format!("{schema_path}: {error}"))?;
+            Ok((events, schema))
+        });
+    let (events, schema) = match inputs {
+        Ok(inputs) => inputs,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let generated = graphql(&unhandled(&schema, &events));
+    let topics = serde_json::to_string_pretty(&topic_filters(&schema, &events)).unwrap() + "\n";
+    for (file, contents) in [(GRAPHQL_FILE, generated), (TOPICS_FILE, topics)] {
+        let path = Path::new(dir).join(file);
+        if check {
+            if fs::read_to_string(&path).ok().as_ref() != Some(&contents) {
+                eprintln!("{}: out of date with the contract's events", path.display());
+                return ExitCode::FAILURE;
+            }
+        } else if let Err(error) = fs::write(&path, contents) {
+            eprintln!("{}: {error}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,305 @@
+//! Checks the checked-in schema against the events of the playground wasm,
+//! and the indexer's `schema.graphql` and `project.ts` against the checked-in
+//! topic filters.
+//!
+//! After changing an event, rewrite `schema/` with
+//! `UPDATE_SCHEMA=1 cargo test -p rbac-schema-gen`.
+
+use std::process::Command;
+
+use rbac_schema_gen::{
+    event_types, events, graphql, handler_topics, topic_filters, unhandled, GRAPHQL_FILE,
+    HANDLED_ENTITY, TOPICS_FILE,
+};
+use serde_json::Value;
+use stellar_xdr::curr::ScSpecEventV0;
+
+const SCHEMA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/schema");
+
+/// The indexer's schema, which the generated files are checked against.
+const INDEXER_SCHEMA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../schema.graphql");
+
+/// The indexer's manifests, whose event handlers filter on the topics of the
+/// events it records as `AccessControlEvent`s.
+const PROJECTS: [&str; 2] = ["project.ts", "project-mainnet.ts"];
+
+fn wasm() -> &'static [u8] {
+    rbac_test_support::wasm("rbac-playground")
+}
+
+fn playground_events() -> Vec<ScSpecEventV0> {
+    events(wasm()).unwrap()
+}
+
+fn topics_file(schema: &str, events: &[ScSpecEventV0]) -> String {
+    serde_json::to_string_pretty(&topic_filters(schema, events)).unwrap() + "\n"
+}
+
+fn checked_in(file: &str) -> String {
+    std::fs::read_to_string(format!("{SCHEMA_DIR}/{file}")).unwrap()
+}
+
+fn indexer_schema() -> String {
+    std::fs::read_to_string(INDEXER_SCHEMA).unwrap()
+}
+
+fn project(name: &str) -> String {
+    std::fs::read_to_string(format!("{}/../../{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+}
+
+/// The fields of the entity `name` in `schema`, as written.
+fn entity<'a>(schema: &'a str, name: &str) -> Vec<&'a str> {
+    let start = schema
+        .find(&format!("type {name} @entity {{\n"))
+        .unwrap_or_else(|| panic!("no entity {name}"));
+    schema[start..]
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != "}")
+        .map(str::trim)
+        .collect()
+}
+
+#[test]
+fn checked_in_schema_matches_the_contract() {
+    let events = playground_events();
+    let schema = indexer_schema();
+    let files = [
+        (GRAPHQL_FILE, graphql(&unhandled(&schema, &events))),
+        (TOPICS_FILE, topics_file(&schema, &events)),
+    ];
+
+    if std::env::var_os("UPDATE_SCHEMA").is_some() {
+        for (file, contents) in &files {
+            std::fs::write(format!("{SCHEMA_DIR}/{file}"), contents).unwrap();
+        }
+        return;
+    }
+    for (file, contents) in &files {
+        assert!(
+            checked_in(file) == *contents,
+            "schema/{file} is out of date with the contract's events\n\
+             rerun with UPDATE_SCHEMA=1 to regenerate it"
+        );
+    }
+}
+
+#[test]
+fn generated_entities_cover_the_events_the_indexer_does_not_record() {
+    let events = playground_events();
+    let schema = indexer_schema();
+    let generated = checked_in(GRAPHQL_FILE);
+
+    // The library events the indexer records as AccessControlEvents.
+    let unrecorded: Vec<String> = unhandled(&schema, &events)
+        .iter()
+        .map(|event| event.name.to_utf8_string_lossy())
+        .collect();
+    for name in ["RoleGranted", "OwnershipTransfer", "RoleAdminChanged"] {
+        assert!(!unrecorded.iter().any(|event| event == name), "{name}");
+        assert!(!generated.contains(&format!("type {name}Event @entity")));
+    }
+    for name in ["Minted", "TransferApproved", "Upgraded"] {
+        assert!(unrecorded.iter().any(|event| event == name), "{name}");
+        entity(&generated, &format!("{name}Event"));
+    }
+
+    // The generated types stay out of the indexer's schema, so it has no
+    // entities without a handler, and none of them clash with its own.
+    assert!(!schema.contains("rbac-schema-gen"));
+    assert!(!schema.contains("PlaygroundEventType"));
+    for line in generated.lines() {
+        if let Some(name) = line.strip_prefix("type ") {
+            let name = name.split(' ').next().unwrap();
+            assert!(
+                !schema.contains(&format!("type {name} @entity")),
+                "schema.graphql already defines {name}"
+            );
+        }
+    }
+
+    // An event the indexer stopped recording gets an entity again.
+    let without = schema.replace("  ROLE_GRANTED\n", "");
+    assert_eq!(unhandled(&without, &events).len(), unrecorded.len() + 1);
+    assert!(graphql(&unhandled(&without, &events)).contains("type RoleGrantedEvent @entity"));
+}
+
+#[test]
+fn indexer_handles_every_access_control_event() {
+    let schema = indexer_schema();
+    let filters: Value = serde_json::from_str(&checked_in(TOPICS_FILE)).unwrap();
+    let mut recorded: Vec<(&str, &str)> = filters
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|filter| filter["entity"] == HANDLED_ENTITY)
+        .map(|filter| {
+            (
+                filter["filter"]["topics"][0].as_str().unwrap(),
+                filter["type"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    recorded.sort();
+
+    // Every EventType value belongs to a contract event.
+    let mut types: Vec<&str> = recorded.iter().map(|(_, type_)| *type_).collect();
+    types.sort();
+    let mut event_types = event_types(&schema);
+    event_types.sort();
+    assert_eq!(
+        types, event_types,
+        "schema.graphql has EventType values no contract event has"
+    );
+
+    // And project.ts has a handler for each, filtering on its topic.
+    let mut topics: Vec<&str> = recorded.iter().map(|(topic, _)| *topic).collect();
+    topics.dedup();
+    for name in PROJECTS {
+        let source = project(name);
+        let mut used = handler_topics(&source);
+        assert_eq!(
+            used.len(),
+            source.matches("StellarHandlerKind.Event").count()
+        );
+        used.sort();
+        assert_eq!(
+            used, topics,
+            "{name}'s event handlers differ from the events schema.graphql records"
+        );
+    }
+}
+
+#[test]
+fn every_event_has_an_entity_a_type_and_a_filter() {
+    let events = playground_events();
+    let schema = graphql(&events);
+    let filters = topic_filters(&schema, &events);
+    let filters = filters.as_array().unwrap();
+
+    // The playground's own events and the library events it links.
+    for name in [
+        "Minted",
+        "RoleCapSet",
+        "TransferApproved",
+        "RoleGranted",
+        "OwnershipTransfer",
+    ] {
+        assert!(
+            events
+                .iter()
+                .any(|event| event.name.to_utf8_string_lossy() == name),
+            "missing {name}"
+        );
+    }
+    assert_eq!(filters.len(), events.len());
+    let enum_start = schema.find("enum PlaygroundEventType {\n").unwrap();
+    for (event, filter) in events.iter().zip(filters) {
+        let name = event.name.to_utf8_string_lossy();
+        assert_eq!(filter["event"], name.as_str());
+        entity(&schema, &format!("{name}Event"));
+        let type_ = filter["type"].as_str().unwrap();
+        assert!(schema[enum_start..].contains(&format!("\n  {type_}\n")));
+    }
+
+    assert_eq!(
+        entity(&schema, "RoleGrantedEvent"),
+        [
+            "id: ID! # txHash-logIndex",
+            "contract: String! @index",
+            "role: String! @index",
+            "account: String! @index",
+            "caller: String!",
+            "ledger: Int!",
+            "timestamp: Date! @index",
+            "txHash: String!",
+        ]
+    );
+    let role_granted = filters
+        .iter()
+        .find(|filter| filter["event"] == "RoleGranted")
+        .unwrap();
+    assert_eq!(role_granted["type"], "ROLE_GRANTED");
+    assert_eq!(
+        role_granted["filter"]["topics"],
+        serde_json::json!(["role_granted"])
+    );
+
+    // The indexer records library events as AccessControlEvents.
+    let filters = topic_filters(&indexer_schema(), &events);
+    let filter = |name: &str| {
+        filters
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|filter| filter["event"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(filter("RoleGranted")["entity"], "AccessControlEvent");
+    assert_eq!(
+        filter("OwnershipTransfer")["type"],
+        "OWNERSHIP_TRANSFER_STARTED"
+    );
+    assert_eq!(filter("Minted")["entity"], "MintedEvent");
+    assert_eq!(filter("Minted")["type"], "MINTED");
+
+    // Optional values are nullable, and clashing names are prefixed.
+    assert!(entity(&schema, "RoleCapSetEvent").contains(&"maxMembers: Int"));
+    let approved = entity(&schema, "TransferApprovedEvent");
+    assert!(approved.contains(&"transferId: BigInt! @index"));
+    assert!(approved.contains(&"currentApprovals: Int!"));
+}
+
+#[test]
+fn handler_topics_ignore_quote_style() {
+    let source = "filter: { topics: ['role_granted'] }\n\
+                  filter: {\n  topics: [\n    \"role_revoked\",\n  ],\n}\n\
+                  filter: { topics:[`admin_renounced`, 'x'] }";
+    assert_eq!(
+        handler_topics(source),
+        ["role_granted", "role_revoked", "admin_renounced"]
+    );
+}
+
+#[test]
+fn cli_writes_the_schema_files() {
+    let dir = format!("{}/schema-cli", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let wasm_path = format!("{dir}/playground.wasm");
+    std::fs::write(&wasm_path, wasm()).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_rbac-schema-gen"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&[&wasm_path, INDEXER_SCHEMA, &dir]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for file in [GRAPHQL_FILE, TOPICS_FILE] {
+        assert_eq!(
+            std::fs::read_to_string(format!("{dir}/{file}")).unwrap(),
+            checked_in(file)
+        );
+    }
+    let output = run(&["--check", &wasm_path, INDEXER_SCHEMA, SCHEMA_DIR]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // A stale topics file fails the check.
+    std::fs::write(format!("{dir}/{TOPICS_FILE}"), "[]\n").unwrap();
+    let output = run(&["--check", &wasm_path, INDEXER_SCHEMA, &dir]);
+    assert!(!output.status.success());
+
+    let missing_wasm = format!("{dir}/missing.wasm");
+    let output = run(&[&missing_wasm, INDEXER_SCHEMA, &dir]);
+    assert!(!output.status.success());
+}
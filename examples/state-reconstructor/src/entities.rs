@@ -0,0 +1,136 @@
+//! The entities of `schema.graphql`, plus the admin and pending transfers the
+//! schema has no entity for.
+
+use rbac_event_decoder::{AccessControlEvent, EventType};
+use serde::{Deserialize, Serialize};
+
+use crate::LedgerEvent;
+
+/// An `AccessControlEvent` with its ledger context.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessControlEventRecord {
+    pub id: String,
+    #[serde(flatten)]
+    pub event: AccessControlEvent,
+    pub block_height: u64,
+    pub timestamp: String,
+    pub tx_hash: String,
+    pub ledger: u32,
+}
+
+impl AccessControlEventRecord {
+    pub(crate) fn new(source: &LedgerEvent, event: AccessControlEvent) -> Self {
+        AccessControlEventRecord {
+            id: format!("{}-{}", source.id, id_suffix(event.event_type)),
+            event,
+            block_height: source.ledger.into(),
+            timestamp: source.timestamp.clone(),
+            tx_hash: source.tx_hash.clone(),
+            ledger: source.ledger,
+        }
+    }
+}
+
+/// The suffix each handler appends to the SubQuery event id.
+fn id_suffix(event_type: EventType) -> &'static str {
+    match event_type {
+        EventType::RoleGranted => "granted",
+        EventType::RoleRevoked => "revoked",
+        EventType::AdminTransferInitiated => "admin-init",
+        EventType::AdminTransferCompleted => "admin-complete",
+        EventType::AdminRenounced => "admin-renounced",
+        EventType::RoleAdminChanged => "role-admin-changed",
+        EventType::OwnershipTransferStarted => "ownership-start",
+        EventType::OwnershipTransferCompleted => "ownership",
+        EventType::OwnershipRenounced => "ownership-renounced",
+    }
+}
+
+/// `RoleMembership` in `schema.graphql`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleMembership {
+    /// `contract-role-account`.
+    pub id: String,
+    pub contract: String,
+    pub role: String,
+    pub account: String,
+    pub granted_at: String,
+    pub granted_by: Option<String>,
+    pub tx_hash: String,
+}
+
+/// `ContractOwnership` in `schema.graphql`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractOwnership {
+    /// The contract address.
+    pub id: String,
+    pub contract: String,
+    pub owner: String,
+    pub previous_owner: Option<String>,
+    pub transferred_at: String,
+    pub tx_hash: String,
+}
+
+/// `ContractType` in `schema.graphql`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContractType {
+    AccessControl,
+    Ownable,
+    AccessControlOwnable,
+}
+
+/// `Contract` in `schema.graphql`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contract {
+    /// The contract address.
+    pub id: String,
+    pub address: String,
+    #[serde(rename = "type")]
+    pub contract_type: ContractType,
+    pub deployed_at: Option<String>,
+    pub deploy_tx_hash: Option<String>,
+    pub last_activity_at: String,
+}
+
+/// The minimum TTL of temporary storage entries, in ledgers, on Mainnet,
+/// Testnet and in the soroban sandbox.
+pub const MIN_TEMPORARY_TTL: u32 = 16;
+
+/// A two-step admin or ownership transfer waiting to be accepted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransfer {
+    pub from: String,
+    pub to: String,
+    /// The `liveUntilLedger` the transfer was started with.
+    pub live_until_ledger: u32,
+    /// The last ledger at which `to` can accept.
+    ///
+    /// The pending account lives in temporary storage, whose TTL is at least
+    /// [`MIN_TEMPORARY_TTL`] and is only ever extended while the entry is
+    /// stored. A transfer can therefore stay acceptable past its
+    /// `liveUntilLedger`, e.g. when it replaces a longer one.
+    pub acceptable_until_ledger: u32,
+    pub tx_hash: String,
+}
+
+impl PendingTransfer {
+    /// Whether the transfer can still be accepted at `ledger`.
+    pub fn is_live(&self, ledger: u32) -> bool {
+        ledger <= self.acceptable_until_ledger
+    }
+}
+
+/// A contract's admin and pending transfers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractAccess {
+    pub admin: Option<String>,
+    pub pending_admin_transfer: Option<PendingTransfer>,
+    pub pending_ownership_transfer: Option<PendingTransfer>,
+}
@@ -0,0 +1,378 @@
+//! Reference model of the indexer's state for RBAC playground contracts.
+//!
+//! Replays an ordered stream of decoded events into the entities the SubQuery
+//! handlers in `src/mappings/mappingHandlers.ts` maintain: `RoleMembership`,
+//! `ContractOwnership`, `Contract` and the `AccessControlEvent` history. It
+//! also tracks each contract's admin and its pending admin and ownership
+//! transfers, which the schema has no entity for. Diffing the indexer against
+//! a replay shows where the handlers drift from what the contracts emitted.
+//!
+//! By default the entities are exactly what the handlers store. Where the
+//! events carry more than the handlers use, [`ReplayOptions`] can fill it in:
+//!
+//! - `seed_from_initialized`: the playground's `initialized` event seeds the
+//!   first owner and admin and the `Contract`'s deployment fields, which no
+//!   other event reveals and no handler reads.
+//! - `previous_owner`: `ContractOwnership.previousOwner` is the owner before
+//!   the transfer rather than always empty.
+//!
+//! [`ReplayOptions::EXTENDED`] enables both, and gives the owner and admin the
+//! contracts' own views report.
+//!
+//! Pending transfers expire when the contract's storage entry does, which can
+//! be later than the `liveUntilLedger` in their event; see
+//! [`PendingTransfer::acceptable_until_ledger`].
+//!
+//! ```ignore
+//! let events = rbac_state_reconstructor::read_events(std::io::stdin().lock())?;
+//! let state = rbac_state_reconstructor::State::replay(&events);
+//! println!("{}", serde_json::to_string_pretty(&state)?);
+//! ```
+
+use core::fmt;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+
+use rbac_event_decoder::{DecodedEvent, Event};
+use serde::{Deserialize, Serialize};
+
+mod entities;
+
+pub use entities::{
+    AccessControlEventRecord, Contract, ContractAccess, ContractOwnership, ContractType,
+    PendingTransfer, RoleMembership, MIN_TEMPORARY_TTL,
+};
+
+/// A decoded event with the ledger context the indexer receives with it.
+///
+/// Serializes as one flat object, e.g. `{"eventId": "...", "ledger": 12,
+/// "timestamp": "...", "txHash": "...", "contract": "C...", "event":
+/// "role_granted", ...fields}`. The id is `eventId` so it does not clash
+/// with the `id` of the transfer events.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEvent {
+    /// The SubQuery event id, which `AccessControlEvent` ids extend.
+    #[serde(rename = "eventId")]
+    pub id: String,
+    pub ledger: u32,
+    /// The ledger close time, copied as given into the `Date` fields.
+    pub timestamp: String,
+    pub tx_hash: String,
+    #[serde(flatten)]
+    pub decoded: DecodedEvent,
+}
+
+/// Additions to the handlers' behaviour; the default adds nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplayOptions {
+    /// Seed the owner, admin and deployment fields from `initialized`.
+    pub seed_from_initialized: bool,
+    /// Record the owner before each transfer as `previousOwner`.
+    pub previous_owner: bool,
+}
+
+impl ReplayOptions {
+    /// The handlers' behaviour, as `mappingHandlers.ts` implements it.
+    pub const HANDLERS: ReplayOptions = ReplayOptions {
+        seed_from_initialized: false,
+        previous_owner: false,
+    };
+
+    /// Every addition enabled.
+    pub const EXTENDED: ReplayOptions = ReplayOptions {
+        seed_from_initialized: true,
+        previous_owner: true,
+    };
+}
+
+/// The reconstructed state of every contract seen in a replay.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct State {
+    /// `AccessControlEvent`s in replay order.
+    pub access_control_events: Vec<AccessControlEventRecord>,
+    /// `RoleMembership`s by id.
+    pub role_memberships: BTreeMap<String, RoleMembership>,
+    /// `ContractOwnership`s by contract.
+    pub contract_ownerships: BTreeMap<String, ContractOwnership>,
+    /// `Contract`s by address.
+    pub contracts: BTreeMap<String, Contract>,
+    /// Admins and pending transfers by contract.
+    pub access: BTreeMap<String, ContractAccess>,
+}
+
+impl State {
+    /// Replays `events` in order from an empty state, as the handlers do.
+    pub fn replay<'a>(events: impl IntoIterator<Item = &'a LedgerEvent>) -> Self {
+        Self::replay_with(ReplayOptions::HANDLERS, events)
+    }
+
+    /// Replays `events` in order from an empty state, with `options`.
+    pub fn replay_with<'a>(
+        options: ReplayOptions,
+        events: impl IntoIterator<Item = &'a LedgerEvent>,
+    ) -> Self {
+        let mut state = State::default();
+        for event in events {
+            state.apply_with(options, event);
+        }
+        state
+    }
+
+    /// Applies one event as the handlers do.
+    pub fn apply(&mut self, source: &LedgerEvent) {
+        self.apply_with(ReplayOptions::HANDLERS, source);
+    }
+
+    /// Applies one event with `options`. Events the indexer does not store
+    /// are ignored.
+    pub fn apply_with(&mut self, options: ReplayOptions, source: &LedgerEvent) {
+        let contract = &source.decoded.contract;
+        if let Some(event) = source.decoded.access_control_event() {
+            self.access_control_events
+                .push(AccessControlEventRecord::new(source, event));
+        }
+
+        match &source.decoded.event {
+            Event::RoleGranted(event) => {
+                let id = format!("{contract}-{}-{}", event.role, event.account);
+                let membership = RoleMembership {
+                    id: id.clone(),
+                    contract: contract.clone(),
+                    role: event.role.to_string(),
+                    account: event.account.to_string(),
+                    granted_at: source.timestamp.clone(),
+                    granted_by: Some(event.caller.to_string()),
+                    tx_hash: source.tx_hash.clone(),
+                };
+                self.role_memberships.insert(id, membership);
+                self.touch(source, ContractType::AccessControl);
+            }
+            Event::RoleRevoked(event) => {
+                let id = format!("{contract}-{}-{}", event.role, event.account);
+                self.role_memberships.remove(&id);
+                self.touch(source, ContractType::AccessControl);
+            }
+            Event::RoleAdminChanged(_) => self.touch(source, ContractType::AccessControl),
+            Event::AdminTransferInitiated(event) => {
+                let access = self.access_mut(contract);
+                access.pending_admin_transfer = pending_transfer(
+                    access.pending_admin_transfer.as_ref(),
+                    source,
+                    &event.current_admin,
+                    &event.new_admin,
+                    event.live_until_ledger,
+                );
+            }
+            Event::AdminTransferCompleted(event) => {
+                let access = self.access_mut(contract);
+                access.admin = Some(event.new_admin.to_string());
+                access.pending_admin_transfer = None;
+                self.touch(source, ContractType::AccessControl);
+            }
+            Event::AdminRenounced(_) => {
+                self.access_mut(contract).admin = None;
+                self.touch(source, ContractType::AccessControl);
+            }
+            Event::OwnershipTransfer(event) => {
+                let access = self.access_mut(contract);
+                access.pending_ownership_transfer = pending_transfer(
+                    access.pending_ownership_transfer.as_ref(),
+                    source,
+                    &event.old_owner,
+                    &event.new_owner,
+                    event.live_until_ledger,
+                );
+            }
+            Event::OwnershipTransferCompleted(event) => {
+                self.access_mut(contract).pending_ownership_transfer = None;
+                self.set_owner(options, source, event.new_owner.to_string());
+                self.touch(source, ContractType::Ownable);
+            }
+            Event::OwnershipRenounced(_) => {
+                self.contract_ownerships.remove(contract);
+                self.touch(source, ContractType::Ownable);
+            }
+            Event::Initialized(event) if options.seed_from_initialized => {
+                self.access_mut(contract).admin = Some(event.admin.to_string());
+                self.set_owner(options, source, event.owner.to_string());
+                let deployed = self.contracts.entry(contract.clone()).or_insert(Contract {
+                    id: contract.clone(),
+                    address: contract.clone(),
+                    contract_type: ContractType::AccessControlOwnable,
+                    deployed_at: None,
+                    deploy_tx_hash: None,
+                    last_activity_at: source.timestamp.clone(),
+                });
+                deployed.contract_type = ContractType::AccessControlOwnable;
+                deployed.deployed_at = Some(source.timestamp.clone());
+                deployed.deploy_tx_hash = Some(source.tx_hash.clone());
+                deployed.last_activity_at = source.timestamp.clone();
+            }
+            _ => {}
+        }
+    }
+
+    /// Accounts holding `role` on `contract`, ordered by address.
+    pub fn role_members(&self, contract: &str, role: &str) -> Vec<&str> {
+        self.role_memberships
+            .values()
+            .filter(|m| m.contract == contract && m.role == role)
+            .map(|m| m.account.as_str())
+            .collect()
+    }
+
+    /// Whether `account` holds `role` on `contract`.
+    pub fn has_role(&self, contract: &str, role: &str, account: &str) -> bool {
+        self.role_memberships
+            .contains_key(&format!("{contract}-{role}-{account}"))
+    }
+
+    /// The current owner of `contract`.
+    pub fn owner(&self, contract: &str) -> Option<&str> {
+        self.contract_ownerships
+            .get(contract)
+            .map(|ownership| ownership.owner.as_str())
+    }
+
+    /// The current admin of `contract`.
+    pub fn admin(&self, contract: &str) -> Option<&str> {
+        self.access.get(contract)?.admin.as_deref()
+    }
+
+    /// The admin transfer of `contract` that can still be accepted at `ledger`.
+    pub fn pending_admin_transfer(&self, contract: &str, ledger: u32) -> Option<&PendingTransfer> {
+        let pending = self.access.get(contract)?.pending_admin_transfer.as_ref()?;
+        pending.is_live(ledger).then_some(pending)
+    }
+
+    /// The ownership transfer of `contract` that can still be accepted at
+    /// `ledger`.
+    pub fn pending_ownership_transfer(
+        &self,
+        contract: &str,
+        ledger: u32,
+    ) -> Option<&PendingTransfer> {
+        let pending = self
+            .access
+            .get(contract)?
+            .pending_ownership_transfer
+            .as_ref()?;
+        pending.is_live(ledger).then_some(pending)
+    }
+
+    fn access_mut(&mut self, contract: &str) -> &mut ContractAccess {
+        self.access.entry(contract.to_string()).or_default()
+    }
+
+    fn set_owner(&mut self, options: ReplayOptions, source: &LedgerEvent, owner: String) {
+        let contract = &source.decoded.contract;
+        let previous_owner = if options.previous_owner {
+            self.owner(contract).map(str::to_string)
+        } else {
+            None
+        };
+        let ownership = ContractOwnership {
+            id: contract.clone(),
+            contract: contract.clone(),
+            owner,
+            previous_owner,
+            transferred_at: source.timestamp.clone(),
+            tx_hash: source.tx_hash.clone(),
+        };
+        self.contract_ownerships.insert(contract.clone(), ownership);
+    }
+
+    /// `updateContractMetadata`: records activity and widens the type when a
+    /// contract shows both access control and ownership events.
+    fn touch(&mut self, source: &LedgerEvent, contract_type: ContractType) {
+        let address = &source.decoded.contract;
+        let contract = self.contracts.entry(address.clone()).or_insert(Contract {
+            id: address.clone(),
+            address: address.clone(),
+            contract_type,
+            deployed_at: None,
+            deploy_tx_hash: None,
+            last_activity_at: source.timestamp.clone(),
+        });
+        contract.last_activity_at = source.timestamp.clone();
+        if contract.contract_type != contract_type {
+            contract.contract_type = ContractType::AccessControlOwnable;
+        }
+    }
+}
+
+/// The transfer that replaces `previous`, or `None` when it was cancelled
+/// (`live_until_ledger` 0).
+///
+/// Mirrors the pending entry's TTL: a new entry lives for
+/// [`MIN_TEMPORARY_TTL`] ledgers, an entry still stored keeps its TTL, and
+/// either is then extended to `live_until_ledger` if that is later.
+fn pending_transfer(
+    previous: Option<&PendingTransfer>,
+    source: &LedgerEvent,
+    from: &rbac_event_decoder::Address,
+    to: &rbac_event_decoder::Address,
+    live_until_ledger: u32,
+) -> Option<PendingTransfer> {
+    if live_until_ledger == 0 {
+        return None;
+    }
+    let stored_until = match previous {
+        Some(previous) if previous.is_live(source.ledger) => previous.acceptable_until_ledger,
+        _ => source.ledger + MIN_TEMPORARY_TTL - 1,
+    };
+    Some(PendingTransfer {
+        from: from.to_string(),
+        to: to.to_string(),
+        live_until_ledger,
+        acceptable_until_ledger: stored_until.max(live_until_ledger),
+        tx_hash: source.tx_hash.clone(),
+    })
+}
+
+/// Errors raised while reading an event stream.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// A line is not a `LedgerEvent`. Lines are numbered from 1.
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "failed to read events: {error}"),
+            ReadError::Json { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+/// Reads one JSON `LedgerEvent` per line, skipping blank lines.
+pub fn read_events(reader: impl BufRead) -> Result<Vec<LedgerEvent>, ReadError> {
+    let mut events = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).map_err(|error| ReadError::Json {
+            line: index + 1,
+            error,
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
@@ -0,0 +1,65 @@
+//! Replays events and prints the reconstructed state as JSON.
+//!
+//! ```text
+//! rbac-state-reconstructor [--extended] [EVENTS]
+//! ```
+//!
+//! `EVENTS` is a file with one JSON `LedgerEvent` per line; without it, or
+//! with `-`, events are read from stdin. The state is what the handlers
+//! store, or with `--extended`, what `ReplayOptions::EXTENDED` adds to it.
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::process::ExitCode;
+
+use rbac_state_reconstructor::{read_events, ReplayOptions, State};
+
+const USAGE: &str = "usage: rbac-state-reconstructor [--extended] [EVENTS]
+
+Replays JSON-lines events from EVENTS, or stdin, and prints the state the
+indexer's handlers would store.
+
+  --extended  also seed the owner and admin from `initialized` and record
+              each transfer's previous owner";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match args.first().map(String::as_str) {
+        Some("--extended") => {
+            args.remove(0);
+            ReplayOptions::EXTENDED
+        }
+        _ => ReplayOptions::HANDLERS,
+    };
+    let events = match args.as_slice() {
+        [] => read_events(io::stdin().lock()),
+        [arg] if arg == "-" => read_events(io::stdin().lock()),
+        [arg] if arg == "-h" || arg == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        [path] => match File::open(path) {
+            Ok(file) => read_events(BufReader::new(file)),
+            Err(error) => {
+                eprintln!("{path}: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match events {
+        Ok(events) => {
+            let state = State::replay_with(options, &events);
+            println!("{}", serde_json::to_string_pretty(&state).unwrap());
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
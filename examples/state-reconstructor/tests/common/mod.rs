@@ -0,0 +1,116 @@
+//! Runs the playground in the sandbox and records its events the way the
+//! indexer receives them.
+
+#![allow(dead_code)]
+
+use rbac_playground::{RbacPlayground, RbacPlaygroundClient};
+use rbac_state_reconstructor::{ReplayOptions, State};
+pub use rbac_test_support::strkey;
+use rbac_test_support::{sandbox, EventLog};
+use soroban_sdk::{testutils::Address as _, Address, Env, Symbol, Vec};
+
+/// A `list_*` view of the members of one role.
+pub type RoleList = fn(&RbacPlaygroundClient<'static>) -> Vec<Address>;
+
+/// Every role with a `list_*` view, and that view.
+pub const ROLE_LISTS: [(&str, RoleList); 7] = [
+    ("operator", |c| c.list_operators()),
+    ("minter", |c| c.list_minters()),
+    ("burner", |c| c.list_burners()),
+    ("pauser", |c| c.list_pausers()),
+    ("viewer", |c| c.list_viewers()),
+    ("transfer", |c| c.list_transferers()),
+    ("approver", |c| c.list_approvers()),
+];
+
+/// A deployed playground and the events it has published so far.
+pub struct Chain {
+    pub env: Env,
+    pub contract_id: Address,
+    pub admin: Address,
+    pub owner: Address,
+    pub log: EventLog,
+}
+
+impl Chain {
+    /// A sandbox to deploy into, with every auth mocked.
+    pub fn env() -> Env {
+        sandbox()
+    }
+
+    /// Deploys the playground with `initial_roles` and records the
+    /// constructor's events.
+    pub fn deploy(env: Env, initial_roles: &[(&str, &Address)]) -> Self {
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let mut roles = Vec::new(&env);
+        for (role, account) in initial_roles {
+            roles.push_back((Symbol::new(&env, role), (*account).clone()));
+        }
+        let contract_id = env.register(
+            RbacPlayground,
+            (&admin, &owner, roles, None::<Vec<(Address, i128)>>),
+        );
+
+        let mut chain = Chain {
+            env,
+            contract_id,
+            admin,
+            owner,
+            log: EventLog::new(),
+        };
+        chain.record();
+        chain
+    }
+
+    pub fn client(&self) -> RbacPlaygroundClient<'static> {
+        RbacPlaygroundClient::new(&self.env, &self.contract_id)
+    }
+
+    pub fn contract(&self) -> String {
+        strkey(&self.contract_id)
+    }
+
+    pub fn ledger(&self) -> u32 {
+        self.env.ledger().sequence()
+    }
+
+    /// Records the events of the last invocation, see `EventLog::record`.
+    pub fn record(&mut self) {
+        self.log.record(&self.env);
+    }
+
+    /// Replays the events with every `ReplayOptions` addition, so the owner
+    /// and admin are comparable with the contract's views.
+    pub fn replay(&self) -> State {
+        State::replay_with(ReplayOptions::EXTENDED, &self.log.events)
+    }
+}
+
+/// Asserts that the replayed roles, owner and admin match the contract's
+/// views.
+pub fn assert_matches_views(chain: &Chain, state: &State) {
+    let client = chain.client();
+    let contract = chain.contract();
+
+    for (role, list) in ROLE_LISTS {
+        let mut on_chain: std::vec::Vec<String> =
+            list(&client).iter().map(|a| strkey(&a)).collect();
+        on_chain.sort();
+        assert_eq!(
+            state.role_members(&contract, role),
+            on_chain,
+            "members of {role}"
+        );
+    }
+    assert_eq!(
+        state.owner(&contract).map(str::to_string),
+        client.get_owner().map(|owner| strkey(&owner)),
+        "owner"
+    );
+    assert_eq!(
+        state.admin(&contract).map(str::to_string),
+        client.get_admin().map(|admin| strkey(&admin)),
+        "admin"
+    );
+}
@@ -0,0 +1,209 @@
+//! Drives the playground with random role and ownership calls and checks,
+//! after every step, that replaying the emitted events gives the same state
+//! as the contract's views.
+//!
+//! A failure names its seed; rerun just that seed with
+//! `DIFFERENTIAL_SEED=<seed> cargo test --test differential`, and raise
+//! `DIFFERENTIAL_STEPS` for longer runs.
+
+mod common;
+
+use common::{strkey, Chain};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rbac_state_reconstructor::{ReplayOptions, State};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    Address, Symbol,
+};
+
+const SEEDS: [u64; 4] = [1, 2, 3, 4];
+const STEPS: usize = 120;
+const ACCOUNTS: usize = 4;
+const ROLES: [&str; 4] = ["minter", "burner", "viewer", "approver"];
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    GrantRole,
+    RevokeRole,
+    RenounceRole,
+    TransferAdmin,
+    CancelAdminTransfer,
+    AcceptAdminTransfer,
+    TransferOwnership,
+    CancelOwnershipTransfer,
+    AcceptOwnership,
+    RenounceAdmin,
+    RenounceOwnership,
+    Wait,
+}
+
+/// Relative weights; renouncing ends the admin or owner paths for the rest of
+/// a run, so it is rare.
+const OPS: [(Op, u32); 12] = [
+    (Op::GrantRole, 30),
+    (Op::RevokeRole, 15),
+    (Op::RenounceRole, 10),
+    (Op::TransferAdmin, 8),
+    (Op::CancelAdminTransfer, 3),
+    (Op::AcceptAdminTransfer, 8),
+    (Op::TransferOwnership, 8),
+    (Op::CancelOwnershipTransfer, 3),
+    (Op::AcceptOwnership, 8),
+    (Op::RenounceAdmin, 1),
+    (Op::RenounceOwnership, 1),
+    (Op::Wait, 5),
+];
+
+fn env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok()?.parse().ok()
+}
+
+#[test]
+fn event_replay_matches_views() {
+    let seeds = match env_var("DIFFERENTIAL_SEED") {
+        Some(seed) => vec![seed],
+        None => SEEDS.to_vec(),
+    };
+    let steps = env_var("DIFFERENTIAL_STEPS").unwrap_or(STEPS);
+    for seed in seeds {
+        run(seed, steps);
+    }
+}
+
+fn run(seed: u64, steps: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let env = Chain::env();
+    let accounts: Vec<Address> = (0..ACCOUNTS).map(|_| Address::generate(&env)).collect();
+    let mut chain = Chain::deploy(env.clone(), &[]);
+    let client = chain.client();
+    let contract = chain.contract();
+    let roles: Vec<Symbol> = ROLES.iter().map(|role| Symbol::new(&env, role)).collect();
+
+    // The admin and owner join the random accounts, so transfers can go back.
+    let mut actors = accounts.clone();
+    actors.push(chain.admin.clone());
+    actors.push(chain.owner.clone());
+
+    let mut state = chain.replay();
+    let mut applied = chain.log.events.len();
+    let total: u32 = OPS.iter().map(|(_, weight)| weight).sum();
+
+    for step in 0..steps {
+        let mut pick = rng.gen_range(0..total);
+        let op = OPS
+            .iter()
+            .find(|(_, weight)| {
+                let found = pick < *weight;
+                pick = pick.saturating_sub(*weight);
+                found
+            })
+            .unwrap()
+            .0;
+        let account = actors.choose(&mut rng).unwrap().clone();
+        let role = roles.choose(&mut rng).unwrap().clone();
+        let ledger = chain.ledger();
+        let context = format!("seed {seed}, step {step}: {op:?}");
+
+        match op {
+            Op::GrantRole => {
+                if let Some(admin) = client.get_admin() {
+                    let _ = client.try_grant_role(&admin, &account, &role);
+                }
+            }
+            Op::RevokeRole => {
+                if let Some(admin) = client.get_admin() {
+                    let _ = client.try_revoke_role(&admin, &account, &role);
+                }
+            }
+            Op::RenounceRole => {
+                let _ = client.try_renounce_role(&account, &role);
+            }
+            Op::TransferAdmin => {
+                let live_until = ledger + rng.gen_range(1..15);
+                let _ = client.try_transfer_admin_role(&account, &live_until);
+            }
+            Op::CancelAdminTransfer => {
+                let _ = client.try_transfer_admin_role(&account, &0);
+            }
+            Op::AcceptAdminTransfer => {
+                let expected = state.admin(&contract).is_some()
+                    && state.pending_admin_transfer(&contract, ledger).is_some();
+                let accepted = client.try_accept_admin_transfer().is_ok();
+                assert_eq!(accepted, expected, "{context}: accept succeeded");
+            }
+            Op::TransferOwnership => {
+                let live_until = ledger + rng.gen_range(1..15);
+                let _ = client.try_transfer_ownership(&account, &live_until);
+            }
+            Op::CancelOwnershipTransfer => {
+                let _ = client.try_transfer_ownership(&account, &0);
+            }
+            Op::AcceptOwnership => {
+                let expected = state
+                    .pending_ownership_transfer(&contract, ledger)
+                    .is_some();
+                let accepted = client.try_accept_ownership().is_ok();
+                assert_eq!(accepted, expected, "{context}: accept succeeded");
+            }
+            Op::RenounceAdmin => {
+                let _ = client.try_renounce_admin();
+            }
+            Op::RenounceOwnership => {
+                let _ = client.try_renounce_ownership();
+            }
+            Op::Wait => {
+                let ledgers = rng.gen_range(1..10);
+                env.ledger()
+                    .with_mut(|info| info.sequence_number += ledgers);
+            }
+        }
+        chain.record();
+        for event in &chain.log.events[applied..] {
+            state.apply_with(ReplayOptions::EXTENDED, event);
+        }
+        applied = chain.log.events.len();
+
+        assert_state_matches(&chain, &state, &accounts, &context);
+    }
+}
+
+/// Compares the replayed state with `get_role_member`, `has_role`,
+/// `get_admin` and `get_owner`.
+fn assert_state_matches(chain: &Chain, state: &State, accounts: &[Address], context: &str) {
+    let client = chain.client();
+    let contract = chain.contract();
+
+    for role in ROLES {
+        let symbol = Symbol::new(&chain.env, role);
+        let count = client.get_role_member_count(&symbol);
+        let mut members: Vec<String> = (0..count)
+            .map(|index| strkey(&client.get_role_member(&symbol, &index)))
+            .collect();
+        members.sort();
+        assert_eq!(
+            state.role_members(&contract, role),
+            members,
+            "{context}: members of {role}"
+        );
+
+        for account in accounts {
+            assert_eq!(
+                state.has_role(&contract, role, &strkey(account)),
+                client.has_role(account, &symbol).is_some(),
+                "{context}: has_role({role})"
+            );
+        }
+    }
+    assert_eq!(
+        state.admin(&contract).map(str::to_string),
+        client.get_admin().map(|admin| strkey(&admin)),
+        "{context}: admin"
+    );
+    assert_eq!(
+        state.owner(&contract).map(str::to_string),
+        client.get_owner().map(|owner| strkey(&owner)),
+        "{context}: owner"
+    );
+}
@@ -0,0 +1,307 @@
+//! Replays playground runs from the sandbox and checks the result against the
+//! contract's views.
+
+mod common;
+
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+use common::{assert_matches_views, strkey, Chain};
+use rbac_event_decoder::EventType;
+use rbac_state_reconstructor::{read_events, ContractType, ReadError, State, MIN_TEMPORARY_TTL};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    vec, Address, Symbol,
+};
+
+#[test]
+fn replay_matches_views_through_role_changes() {
+    let env = Chain::env();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let mut chain = Chain::deploy(env.clone(), &[("minter", &alice), ("approver", &bob)]);
+    let client = chain.client();
+    let admin = chain.admin.clone();
+    let carol = Address::generate(&env);
+    let dave = Address::generate(&env);
+    assert_matches_views(&chain, &chain.replay());
+
+    client.grant_role(&admin, &carol, &Symbol::new(&env, "viewer"));
+    chain.record();
+    client.batch_grant_roles(
+        &vec![
+            &env,
+            (carol.clone(), Symbol::new(&env, "transfer")),
+            (dave.clone(), Symbol::new(&env, "pauser")),
+        ],
+        &admin,
+    );
+    chain.record();
+    client.offer_role(
+        &dave,
+        &Symbol::new(&env, "burner"),
+        &(chain.ledger() + 10),
+        &admin,
+    );
+    chain.record();
+    client.accept_role(&dave, &Symbol::new(&env, "burner"));
+    chain.record();
+    assert_matches_views(&chain, &chain.replay());
+
+    client.renounce_role(&alice, &Symbol::new(&env, "minter"));
+    chain.record();
+    client.revoke_role(&admin, &carol, &Symbol::new(&env, "viewer"));
+    chain.record();
+    client.revoke_all_roles(&dave);
+    chain.record();
+    let state = chain.replay();
+    assert_matches_views(&chain, &state);
+    assert!(state.has_role(&chain.contract(), "transfer", &strkey(&carol)));
+    assert!(!state.has_role(&chain.contract(), "pauser", &strkey(&dave)));
+}
+
+#[test]
+fn replay_tracks_admin_and_ownership_transfers() {
+    let env = Chain::env();
+    let mut chain = Chain::deploy(env.clone(), &[]);
+    let client = chain.client();
+    let contract = chain.contract();
+    let new_admin = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+
+    let initiated = chain.ledger();
+    let live_until = initiated + 10;
+    client.transfer_admin_role(&new_admin, &live_until);
+    chain.record();
+    let state = chain.replay();
+    let pending = state
+        .pending_admin_transfer(&contract, chain.ledger())
+        .unwrap();
+    assert_eq!(pending.from, strkey(&chain.admin));
+    assert_eq!(pending.to, strkey(&new_admin));
+    assert_eq!(pending.live_until_ledger, live_until);
+    // The pending admin is in temporary storage, which outlives a
+    // `live_until_ledger` this close.
+    let acceptable_until = initiated + MIN_TEMPORARY_TTL - 1;
+    assert_eq!(pending.acceptable_until_ledger, acceptable_until);
+    assert!(state
+        .pending_admin_transfer(&contract, acceptable_until + 1)
+        .is_none());
+    assert_matches_views(&chain, &state);
+
+    env.ledger().set_sequence_number(acceptable_until);
+    client.accept_admin_transfer();
+    chain.record();
+    let state = chain.replay();
+    assert_eq!(state.admin(&contract), Some(strkey(&new_admin).as_str()));
+    assert!(state
+        .pending_admin_transfer(&contract, chain.ledger())
+        .is_none());
+    assert_matches_views(&chain, &state);
+
+    // A transfer with `live_until_ledger` 0 cancels the pending one.
+    client.transfer_ownership(&new_owner, &(chain.ledger() + 3));
+    chain.record();
+    assert!(chain
+        .replay()
+        .pending_ownership_transfer(&contract, chain.ledger())
+        .is_some());
+    client.transfer_ownership(&new_owner, &0);
+    chain.record();
+    assert!(chain
+        .replay()
+        .pending_ownership_transfer(&contract, chain.ledger())
+        .is_none());
+
+    client.transfer_ownership(&new_owner, &(chain.ledger() + 10));
+    chain.record();
+    client.accept_ownership();
+    chain.record();
+    let state = chain.replay();
+    let ownership = &state.contract_ownerships[&contract];
+    assert_eq!(ownership.owner, strkey(&new_owner));
+    assert_eq!(ownership.previous_owner, Some(strkey(&chain.owner)));
+    assert!(state
+        .pending_ownership_transfer(&contract, chain.ledger())
+        .is_none());
+    assert_matches_views(&chain, &state);
+
+    client.renounce_admin();
+    chain.record();
+    client.renounce_ownership();
+    chain.record();
+    let state = chain.replay();
+    assert_eq!(state.admin(&contract), None);
+    assert_eq!(state.owner(&contract), None);
+    assert_matches_views(&chain, &state);
+}
+
+#[test]
+fn replay_builds_schema_entities() {
+    let env = Chain::env();
+    let alice = Address::generate(&env);
+    let mut chain = Chain::deploy(env, &[("minter", &alice)]);
+    let client = chain.client();
+    let admin = chain.admin.clone();
+    let new_owner = Address::generate(&chain.env);
+    let minter = Symbol::new(&chain.env, "minter");
+    let contract = chain.contract();
+    let deployment = chain.log.events[0].clone();
+
+    client.mint(&alice, &100, &alice);
+    chain.record();
+    client.revoke_role(&admin, &alice, &minter);
+    chain.record();
+    client.grant_role(&admin, &alice, &minter);
+    chain.record();
+    let granted = chain.log.events.last().unwrap().clone();
+    let live_until = chain.ledger() + 10;
+    client.transfer_ownership(&new_owner, &live_until);
+    chain.record();
+    let state = chain.replay();
+
+    let deployed = &state.contracts[&contract];
+    assert_eq!(deployed.contract_type, ContractType::AccessControlOwnable);
+    assert_eq!(deployed.deployed_at, Some(deployment.timestamp.clone()));
+    assert_eq!(deployed.deploy_tx_hash, Some(deployment.tx_hash.clone()));
+    // Starting a transfer is not activity in `updateContractMetadata`.
+    assert_eq!(deployed.last_activity_at, granted.timestamp);
+
+    let membership = &state.role_memberships[&format!("{contract}-minter-{}", strkey(&alice))];
+    assert_eq!(membership.granted_at, granted.timestamp);
+    assert_eq!(membership.granted_by, Some(strkey(&admin)));
+    assert_eq!(membership.tx_hash, granted.tx_hash);
+
+    // `minted` and `initialized` are not `AccessControlEvent`s.
+    let history: Vec<(EventType, &str)> = state
+        .access_control_events
+        .iter()
+        .map(|record| {
+            (
+                record.event.event_type,
+                record.event.role.as_deref().unwrap_or(""),
+            )
+        })
+        .collect();
+    let (constructor, after) = history.split_at(history.len() - 3);
+    assert!(constructor.iter().all(|(event_type, _)| matches!(
+        event_type,
+        EventType::RoleGranted | EventType::RoleAdminChanged
+    )));
+    assert_eq!(
+        after,
+        [
+            (EventType::RoleRevoked, "minter"),
+            (EventType::RoleGranted, "minter"),
+            (EventType::OwnershipTransferStarted, ""),
+        ]
+    );
+    let started = state.access_control_events.last().unwrap();
+    assert_eq!(
+        started.id,
+        format!("{}-ownership-start", chain.log.events.last().unwrap().id)
+    );
+    assert_eq!(started.event.live_until_ledger, Some(live_until));
+}
+
+/// Without options the replay stores what the handlers store: nothing from
+/// `initialized`, and no `previousOwner`.
+#[test]
+fn default_replay_matches_the_handlers() {
+    let env = Chain::env();
+    let alice = Address::generate(&env);
+    let mut chain = Chain::deploy(env.clone(), &[("minter", &alice)]);
+    let client = chain.client();
+    let contract = chain.contract();
+    let state = State::replay(&chain.log.events);
+
+    assert_eq!(state.owner(&contract), None);
+    assert_eq!(state.admin(&contract), None);
+    let deployed = &state.contracts[&contract];
+    assert_eq!(deployed.contract_type, ContractType::AccessControl);
+    assert_eq!(deployed.deployed_at, None);
+    assert_eq!(deployed.deploy_tx_hash, None);
+    assert!(state.has_role(&contract, "minter", &strkey(&alice)));
+
+    let new_owner = Address::generate(&env);
+    client.transfer_ownership(&new_owner, &(chain.ledger() + 10));
+    chain.record();
+    client.accept_ownership();
+    chain.record();
+    let state = State::replay(&chain.log.events);
+    let ownership = &state.contract_ownerships[&contract];
+    assert_eq!(ownership.owner, strkey(&new_owner));
+    assert_eq!(ownership.previous_owner, None);
+    assert_eq!(
+        state.contracts[&contract].contract_type,
+        ContractType::AccessControlOwnable
+    );
+    assert_eq!(
+        chain.replay().contract_ownerships[&contract].previous_owner,
+        Some(strkey(&chain.owner))
+    );
+}
+
+#[test]
+fn cli_replays_events_from_stdin_and_files() {
+    let env = Chain::env();
+    let alice = Address::generate(&env);
+    let mut chain = Chain::deploy(env, &[("viewer", &alice)]);
+    chain
+        .client()
+        .transfer_admin_role(&alice, &(chain.ledger() + 10));
+    chain.record();
+
+    let mut input = String::new();
+    for event in &chain.log.events {
+        input.push_str(&serde_json::to_string(event).unwrap());
+        input.push('\n');
+    }
+    assert_eq!(read_events(input.as_bytes()).unwrap(), chain.log.events);
+    let expected = State::replay(&chain.log.events);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rbac-state-reconstructor"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        serde_json::from_slice::<State>(&output.stdout).unwrap(),
+        expected
+    );
+
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("events.jsonl");
+    std::fs::write(&path, &input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rbac-state-reconstructor"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        serde_json::from_slice::<State>(&output.stdout).unwrap(),
+        expected
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rbac-state-reconstructor"))
+        .arg("--extended")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        serde_json::from_slice::<State>(&output.stdout).unwrap(),
+        chain.replay()
+    );
+
+    let error = read_events(format!("{input}\nnot json\n").as_bytes()).unwrap_err();
+    let line = chain.log.events.len() + 2;
+    assert!(matches!(error, ReadError::Json { line: l, .. } if l == line));
+}
@@ -0,0 +1,231 @@
+//! Helpers shared by the workspace's tests, as a dev-dependency only.
+//!
+//! [`wasm`] builds a contract of the workspace for `wasm32v1-none`, once per
+//! test binary, so tests can register the code that gets deployed. An
+//! [`EventLog`] records the events of sandbox invocations the way the indexer
+//! receives them: one `LedgerEvent` per contract event, with a ledger, a
+//! timestamp and a transaction hash. [`assert_last_events`] and
+//! [`assert_authorized_by`] check what the contracts' unit tests expect of
+//! their last invocation.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+use rbac_event_decoder::decode_contract_event;
+use rbac_state_reconstructor::LedgerEvent;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events as _, Ledger as _},
+    xdr::{
+        AccountId, ContractEvent, ContractEventType, ContractId, Hash, Limits, PublicKey, ReadXdr,
+        ScAddress, Uint256, WriteXdr,
+    },
+    Address, Env, Event, Symbol, TryFromVal, Val,
+};
+
+/// The release build of the workspace package `package` for
+/// `wasm32v1-none`, built on first use.
+///
+/// It builds into `tmp/wasm` next to the test binary's profile directory, a
+/// target directory of its own, so it doesn't wait on the lock of the one
+/// running the tests.
+pub fn wasm(package: &str) -> &'static [u8] {
+    static BUILT: Mutex<BTreeMap<String, &'static [u8]>> = Mutex::new(BTreeMap::new());
+    let mut built = BUILT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(wasm) = built.get(package) {
+        return wasm;
+    }
+
+    let target_dir = wasm_target_dir();
+    let output = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .args([
+            "build",
+            "-p",
+            package,
+            "--release",
+            "--target",
+            "wasm32v1-none",
+        ])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "building the {package} wasm failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let file = target_dir
+        .join("wasm32v1-none/release")
+        .join(format!("{}.wasm", package.replace('-', "_")));
+    let wasm: &'static [u8] = std::fs::read(file).unwrap().leak();
+    built.insert(package.to_string(), wasm);
+    wasm
+}
+
+/// `<target>/tmp/wasm`, from the path of the running test binary,
+/// `<target>/<profile>/deps/<name>`.
+fn wasm_target_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let target = exe.ancestors().nth(3).unwrap();
+    target.join("tmp/wasm")
+}
+
+/// A sandbox with every auth mocked, at ledger 100.
+pub fn sandbox() -> Env {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_sequence_number(100);
+    env.ledger().set_timestamp(1_700_000_000);
+    env
+}
+
+/// A new account (`G...`) address, where `Address::generate` gives a contract
+/// one. Its key is the bytes of a generated contract id, so it is as
+/// deterministic.
+pub fn account(env: &Env) -> Address {
+    let ScAddress::Contract(ContractId(Hash(key))) = ScAddress::from(Address::generate(env)) else {
+        unreachable!("Address::generate gives contract addresses");
+    };
+    let account = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key))));
+    Address::try_from_val(env, &account).unwrap()
+}
+
+/// Asserts that the last events published in `env` are `expected`, in order,
+/// all emitted by `contract_id`.
+pub fn assert_last_events(env: &Env, contract_id: &Address, expected: &[&dyn Event]) {
+    let events = env.events().all();
+    assert!(
+        events.len() as usize >= expected.len(),
+        "expected {} events, but only {} were published: {events:?}",
+        expected.len(),
+        events.len()
+    );
+    let mut want = soroban_sdk::Vec::new(env);
+    for event in expected {
+        want.push_back((contract_id.clone(), event.topics(env), event.data(env)));
+    }
+    assert_eq!(events.slice(events.len() - want.len()..), want);
+}
+
+/// Asserts that the last invocation was authorized by `account` alone, as a
+/// direct call of `function` on `contract_id` with `args`.
+pub fn assert_authorized_by(
+    env: &Env,
+    contract_id: &Address,
+    account: &Address,
+    function: &str,
+    args: soroban_sdk::Vec<Val>,
+) {
+    assert_eq!(
+        env.auths(),
+        std::vec![(
+            account.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    contract_id.clone(),
+                    Symbol::new(env, function),
+                    args,
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+}
+
+/// XDR of each contract event published by the last invocation in `env`.
+pub fn contract_events(env: &Env) -> Vec<Vec<u8>> {
+    env.host()
+        .get_events()
+        .unwrap()
+        .0
+        .into_iter()
+        .filter(|e| !e.failed_call && e.event.type_ == ContractEventType::Contract)
+        .map(|e| e.event.to_xdr(Limits::none()).unwrap())
+        .collect()
+}
+
+/// The strkey of a soroban `Address`, as the decoder renders it.
+pub fn strkey(address: &Address) -> String {
+    address.to_string().to_string()
+}
+
+/// The events of a sandbox run, as the indexer stores them.
+#[derive(Default)]
+pub struct EventLog {
+    pub events: Vec<LedgerEvent>,
+    transactions: u32,
+    ignored: Vec<String>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave out the events of `contract`, whose events the decoder doesn't
+    /// know.
+    pub fn ignoring(mut self, contract: &Address) -> Self {
+        self.ignored.push(strkey(contract));
+        self
+    }
+
+    /// Records the events of the last invocation in `env` as one
+    /// transaction, then closes the ledger. Call it after every
+    /// state-changing invocation, and before any view, which replaces the
+    /// events.
+    pub fn record(&mut self, env: &Env) {
+        self.transactions += 1;
+        let ledger = env.ledger().sequence();
+        let timestamp = env.ledger().timestamp();
+        for (index, bytes) in contract_events(env).iter().enumerate() {
+            if self.is_ignored(bytes) {
+                continue;
+            }
+            self.events.push(LedgerEvent {
+                id: format!("{ledger:010}-{index:04}"),
+                ledger,
+                timestamp: timestamp.to_string(),
+                tx_hash: format!("{:064x}", self.transactions),
+                decoded: decode_contract_event(bytes).unwrap(),
+            });
+        }
+
+        env.ledger().with_mut(|info| {
+            info.sequence_number += 1;
+            info.timestamp += 5;
+        });
+    }
+
+    fn is_ignored(&self, bytes: &[u8]) -> bool {
+        let event = ContractEvent::from_xdr(bytes, Limits::none()).unwrap();
+        event
+            .contract_id
+            .is_some_and(|id| self.ignored.contains(&id.to_string()))
+    }
+
+    /// The events as JSON lines, as `rbac-state-reconstructor` reads them.
+    pub fn json_lines(&self) -> String {
+        self.events
+            .iter()
+            .map(|event| serde_json::to_string(event).unwrap() + "\n")
+            .collect()
+    }
+}
+
+/// Compares `contents` with the checked-in fixture at `path`, or rewrites it
+/// with `UPDATE_FIXTURES=1`.
+pub fn assert_fixture_is_current(path: &str, contents: &str) {
+    if std::env::var_os("UPDATE_FIXTURES").is_some() {
+        std::fs::write(path, contents).unwrap();
+        return;
+    }
+    assert!(
+        std::fs::read_to_string(path).unwrap() == contents,
+        "{path} is out of date\nrerun with UPDATE_FIXTURES=1 if this is intended"
+    );
+}
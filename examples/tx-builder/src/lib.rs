@@ -0,0 +1,362 @@
+//! Builds RBAC playground invocations offline.
+//!
+//! [`Spec`] reads the functions of a built playground wasm from its contract
+//! spec, the `AccessControl` and `Ownable` defaults included. [`Spec::invoke`]
+//! checks named arguments against a function's parameters and converts them to
+//! `ScVal`s, and [`transaction`] wraps the call in an unsigned
+//! `InvokeHostFunction` transaction envelope.
+//!
+//! No network is involved, so the envelope carries no footprint, resource fee
+//! or authorizations. Simulate it before submitting it, for example with
+//! `stellar tx simulate`, then [`sign`] the simulated envelope: first with the
+//! keys of any authorizing accounts other than the source, such as a `caller`,
+//! then with the source account's.
+//!
+//! ```ignore
+//! let spec = Spec::from_wasm(&std::fs::read("rbac_playground.wasm")?)?;
+//! let call = spec.invoke(&contract, "grant_role", &[
+//!     ("account", "G..."),
+//!     ("role", "minter"),
+//!     ("caller", "G..."),
+//! ])?;
+//! let envelope = rbac_tx_builder::transaction(source, sequence, BASE_FEE, call)?;
+//! ```
+
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+use ed25519_dalek::{Signer as _, SigningKey};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    AccountId, DecoratedSignature, Hash, HashIdPreimage, HashIdPreimageSorobanAuthorization,
+    HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation,
+    OperationBody, Preconditions, PublicKey, ScAddress, ScMap, ScMapEntry, ScSpecEntry,
+    ScSpecFunctionV0, ScSpecTypeDef, ScSymbol, ScVal, ScVec, SequenceNumber, Signature,
+    SignatureHint, SorobanCredentials, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionV1Envelope, Uint256, WriteXdr,
+};
+
+mod value;
+
+pub use value::type_name;
+
+/// The inclusion fee, in stroops, of a built transaction. Simulation adds the
+/// resource fee.
+pub const BASE_FEE: u32 = 100;
+
+/// The passphrase of the network the Makefile deploys to.
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+/// The functions, types and events of a built contract.
+#[derive(Clone, Debug)]
+pub struct Spec {
+    entries: Vec<ScSpecEntry>,
+}
+
+impl Spec {
+    /// Reads the spec from the `contractspecv0` section of `wasm`.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Self, Error> {
+        let entries =
+            soroban_spec::read::from_wasm(wasm).map_err(|error| Error::Spec(error.to_string()))?;
+        Ok(Spec { entries })
+    }
+
+    pub fn entries(&self) -> &[ScSpecEntry] {
+        &self.entries
+    }
+
+    /// The functions a transaction can invoke. The constructor is left out,
+    /// as it only runs when the contract is deployed.
+    pub fn functions(&self) -> impl Iterator<Item = &ScSpecFunctionV0> {
+        self.entries.iter().filter_map(|entry| match entry {
+            ScSpecEntry::FunctionV0(function) if function.name.0.as_vec() != b"__constructor" => {
+                Some(function)
+            }
+            _ => None,
+        })
+    }
+
+    pub fn function(&self, name: &str) -> Result<&ScSpecFunctionV0, Error> {
+        self.functions()
+            .find(|function| function.name.0.as_vec() == name.as_bytes())
+            .ok_or_else(|| Error::UnknownFunction(name.to_string()))
+    }
+
+    /// The call of `function` on `contract` with `args`, given by parameter
+    /// name. `Option` parameters may be left out.
+    pub fn invoke(
+        &self,
+        contract: &ScAddress,
+        function: &str,
+        args: &[(&str, &str)],
+    ) -> Result<InvokeContractArgs, Error> {
+        let spec = self.function(function)?;
+        if let Some((name, _)) = args.iter().find(|(name, _)| {
+            !spec
+                .inputs
+                .iter()
+                .any(|input| input.name.as_vec() == name.as_bytes())
+        }) {
+            return Err(Error::UnknownArgument {
+                function: function.to_string(),
+                argument: name.to_string(),
+            });
+        }
+
+        let mut values = Vec::with_capacity(spec.inputs.len());
+        for input in spec.inputs.iter() {
+            let name = input.name.to_utf8_string_lossy();
+            let mut given = args.iter().filter(|(arg, _)| *arg == name);
+            let value = match (given.next(), given.next(), &input.type_) {
+                (Some(_), Some(_), _) => {
+                    return Err(invalid(function, &name, "given more than once".into()))
+                }
+                (Some((_, text)), None, type_) => value::parse(self, type_, text)
+                    .map_err(|message| invalid(function, &name, message))?,
+                (None, _, ScSpecTypeDef::Option(_)) => ScVal::Void,
+                (None, _, _) => {
+                    return Err(Error::MissingArgument {
+                        function: function.to_string(),
+                        argument: name,
+                    })
+                }
+            };
+            values.push(value);
+        }
+
+        Ok(InvokeContractArgs {
+            contract_address: contract.clone(),
+            function_name: ScSymbol(function.try_into()?),
+            args: values.try_into()?,
+        })
+    }
+}
+
+fn invalid(function: &str, argument: &str, message: String) -> Error {
+    Error::InvalidArgument {
+        function: function.to_string(),
+        argument: argument.to_string(),
+        message,
+    }
+}
+
+/// An unsigned transaction from `source`, with sequence number `sequence`,
+/// that makes `call`.
+pub fn transaction(
+    source: AccountId,
+    sequence: i64,
+    fee: u32,
+    call: InvokeContractArgs,
+) -> Result<TransactionEnvelope, Error> {
+    let AccountId(PublicKey::PublicKeyTypeEd25519(key)) = source;
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(call),
+            auth: Default::default(),
+        }),
+    };
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: Transaction {
+            source_account: MuxedAccount::Ed25519(key),
+            fee,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![operation].try_into()?,
+            ext: TransactionExt::V0,
+        },
+        signatures: Default::default(),
+    }))
+}
+
+/// Signs `envelope` with `key` for the network with `network_passphrase`.
+///
+/// A simulated envelope carries an authorization entry for each address whose
+/// `require_auth` the call reaches. Those of `key`'s account that are still
+/// unsigned, such as a `caller` that isn't the source account, are signed to
+/// stay valid up to `signature_expiration_ledger`. The envelope itself is signed
+/// only when `key` is the source account. Signing the entries changes the
+/// transaction, so every other key has to sign before the source account does.
+pub fn sign(
+    envelope: &mut TransactionEnvelope,
+    key: &SigningKey,
+    network_passphrase: &str,
+    signature_expiration_ledger: Option<u32>,
+) -> Result<(), Error> {
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        return Err(Error::Envelope(
+            "only v1 transaction envelopes can be signed".to_string(),
+        ));
+    };
+    let network_id = network_id(network_passphrase);
+    let account = ScAddress::Account(account_id(key));
+    let public_key = key.verifying_key().to_bytes();
+
+    let mut signed_auth = false;
+    for operation in envelope.tx.operations.iter_mut() {
+        let OperationBody::InvokeHostFunction(op) = &mut operation.body else {
+            continue;
+        };
+        let mut entries = op.auth.to_vec();
+        for entry in entries.iter_mut() {
+            let SorobanCredentials::Address(credentials) = &mut entry.credentials else {
+                continue;
+            };
+            if credentials.address != account || credentials.signature != ScVal::Void {
+                continue;
+            }
+            let Some(expiration) = signature_expiration_ledger else {
+                return Err(Error::Envelope(format!(
+                    "{account} authorizes the call; its signature needs an expiration ledger"
+                )));
+            };
+            if !envelope.signatures.is_empty() {
+                return Err(Error::Envelope(
+                    "the source account has signed the envelope already; sign the \
+                     authorizations before it"
+                        .to_string(),
+                ));
+            }
+            let preimage =
+                HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+                    network_id: network_id.clone(),
+                    nonce: credentials.nonce,
+                    signature_expiration_ledger: expiration,
+                    invocation: entry.root_invocation.clone(),
+                });
+            let payload = Sha256::digest(preimage.to_xdr(Limits::none())?);
+            let field = |name: &str, bytes: Vec<u8>| -> Result<ScMapEntry, Error> {
+                Ok(ScMapEntry {
+                    key: ScVal::Symbol(ScSymbol(name.try_into()?)),
+                    val: ScVal::Bytes(bytes.try_into()?),
+                })
+            };
+            let signature = ScMap(
+                vec![
+                    field("public_key", public_key.to_vec())?,
+                    field("signature", key.sign(&payload).to_bytes().to_vec())?,
+                ]
+                .try_into()?,
+            );
+            credentials.signature_expiration_ledger = expiration;
+            credentials.signature =
+                ScVal::Vec(Some(ScVec(vec![ScVal::Map(Some(signature))].try_into()?)));
+            signed_auth = true;
+        }
+        op.auth = entries.try_into()?;
+    }
+
+    let source = match &envelope.tx.source_account {
+        MuxedAccount::Ed25519(source) => source,
+        MuxedAccount::MuxedEd25519(source) => &source.ed25519,
+    };
+    if source.0 != public_key {
+        if signed_auth {
+            return Ok(());
+        }
+        return Err(Error::Envelope(format!(
+            "{account} is neither the source account nor the signer of an authorization in the \
+             envelope; simulate it first to add the authorizations"
+        )));
+    }
+    let hash = envelope.tx.hash(network_id.0)?;
+    let mut signatures = envelope.signatures.to_vec();
+    signatures.push(DecoratedSignature {
+        hint: SignatureHint(public_key[28..].try_into().unwrap()),
+        signature: Signature(key.sign(&hash).to_bytes().to_vec().try_into()?),
+    });
+    envelope.signatures = signatures
+        .try_into()
+        .map_err(|_| Error::Envelope("the envelope has 20 signatures already".to_string()))?;
+    Ok(())
+}
+
+/// Reads an ed25519 secret seed (`S...`) from the file at `path`.
+pub fn read_secret_key(path: impl AsRef<Path>) -> Result<SigningKey, Error> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    let seed = stellar_strkey::ed25519::PrivateKey::from_string(text.trim()).map_err(|_| {
+        Error::SecretKey(format!(
+            "{}: not an ed25519 secret seed (S...)",
+            path.display()
+        ))
+    })?;
+    Ok(SigningKey::from_bytes(&seed.0))
+}
+
+/// The account of `key`.
+pub fn account_id(key: &SigningKey) -> AccountId {
+    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+        key.verifying_key().to_bytes(),
+    )))
+}
+
+/// The network id transactions for `network_passphrase` are signed over.
+pub fn network_id(network_passphrase: &str) -> Hash {
+    Hash(Sha256::digest(network_passphrase).into())
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Xdr(stellar_xdr::curr::Error),
+    /// The wasm has no readable contract spec.
+    Spec(String),
+    UnknownFunction(String),
+    UnknownArgument {
+        function: String,
+        argument: String,
+    },
+    MissingArgument {
+        function: String,
+        argument: String,
+    },
+    /// An argument does not fit its parameter's type.
+    InvalidArgument {
+        function: String,
+        argument: String,
+        message: String,
+    },
+    SecretKey(String),
+    /// The envelope can't be signed.
+    Envelope(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Xdr(error) => write!(f, "xdr: {error}"),
+            Error::Spec(message) => write!(f, "contract spec: {message}"),
+            Error::UnknownFunction(name) => write!(f, "the contract has no function `{name}`"),
+            Error::UnknownArgument { function, argument } => {
+                write!(f, "`{function}` has no argument `{argument}`")
+            }
+            Error::MissingArgument { function, argument } => {
+                write!(f, "`{function}` needs the argument `{argument}`")
+            }
+            Error::InvalidArgument {
+                function,
+                argument,
+                message,
+            } => write!(f, "`{function}` argument `{argument}`: {message}"),
+            Error::SecretKey(message) | Error::Envelope(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<stellar_xdr::curr::Error> for Error {
+    fn from(error: stellar_xdr::curr::Error) -> Self {
+        Error::Xdr(error)
+    }
+}
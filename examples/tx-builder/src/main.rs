@@ -0,0 +1,220 @@
+//! Builds and signs playground transactions, offline.
+//!
+//! ```text
+//! rbac-tx-builder functions --wasm PATH
+//! rbac-tx-builder build [OPTIONS] FUNCTION [--ARGUMENT VALUE]...
+//! rbac-tx-builder sign --secret-key PATH [--network-passphrase PASSPHRASE]
+//!                       [--signature-expiration-ledger LEDGER] [ENVELOPE]
+//! ```
+//!
+//! Envelopes are base64 XDR, printed on stdout and read from a file or stdin.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use rbac_tx_builder::{
+    read_secret_key, sign, transaction, type_name, Spec, BASE_FEE, TESTNET_PASSPHRASE,
+};
+use stellar_xdr::curr::{
+    AccountId, Limits, ReadXdr, ScAddress, ScSpecTypeDef, TransactionEnvelope, WriteXdr,
+};
+
+const USAGE: &str = "usage: rbac-tx-builder functions --wasm PATH
+       rbac-tx-builder build [OPTIONS] FUNCTION [--ARGUMENT VALUE]...
+       rbac-tx-builder sign --secret-key PATH [--network-passphrase PASSPHRASE]
+                       [--signature-expiration-ledger LEDGER] [ENVELOPE]
+
+functions lists the contract functions of the wasm at PATH and their arguments.
+
+build prints an unsigned transaction envelope that calls FUNCTION. Simulate it
+before signing and submitting it, to add the footprint, resource fee and
+authorizations.
+
+  --wasm PATH      the playground wasm whose spec the arguments are checked against
+  --contract ID    the contract to call (C...)
+  --source ID      the account that submits the transaction (G...)
+  --sequence N     the transaction's sequence number, the account's plus one
+  --fee STROOPS    the inclusion fee (default: 100)
+
+Scalar arguments are given as they are, bytes in hex, and vectors, tuples and
+maps as JSON: --grants '[[\"G...\", \"minter\"]]'. Option arguments may be left
+out, or given as null.
+
+sign signs the envelope in the file ENVELOPE, or on stdin, with the secret seed
+(S...) in the file at PATH and prints it. The network defaults to testnet.
+
+The seed's account signs the simulated authorizations it has, such as those of
+a --caller that isn't the source account, valid up to the ledger given with
+--signature-expiration-ledger. It signs the envelope only when it is the
+source. Sign with every other account first; the source account signs last.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.as_slice(), [arg] if arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let result = match args.split_first() {
+        Some((command, args)) if command == "functions" => functions(args),
+        Some((command, args)) if command == "build" => build(args),
+        Some((command, args)) if command == "sign" => sign_envelope(args),
+        _ => None,
+    };
+    match result {
+        Some(Ok(output)) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Some(Err(error)) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+        None => {
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads `--flag value` pairs up to the first other argument, which is
+/// returned with those after it.
+fn options<'a>(
+    mut args: &'a [String],
+    flags: &[&str],
+) -> Option<(Vec<Option<&'a str>>, &'a [String])> {
+    let mut values = vec![None; flags.len()];
+    while let [flag, value, rest @ ..] = args {
+        let Some(index) = flags.iter().position(|known| known == flag) else {
+            break;
+        };
+        if values[index].replace(value.as_str()).is_some() {
+            return None;
+        }
+        args = rest;
+    }
+    if args
+        .first()
+        .is_some_and(|arg| flags.contains(&arg.as_str()))
+    {
+        return None;
+    }
+    Some((values, args))
+}
+
+fn functions(args: &[String]) -> Option<Result<String, Box<dyn Error>>> {
+    let (options, []) = options(args, &["--wasm"])? else {
+        return None;
+    };
+    let wasm = options[0]?;
+    Some(read_spec(wasm).map_err(Into::into).map(|spec| {
+        let lines: Vec<String> = spec
+            .functions()
+            .map(|function| {
+                let mut line = function.name.0.to_utf8_string_lossy();
+                for input in function.inputs.iter() {
+                    let argument = format!("--{} <{}>", input.name, type_name(&input.type_));
+                    if matches!(input.type_, ScSpecTypeDef::Option(_)) {
+                        line += &format!(" [{argument}]");
+                    } else {
+                        line += &format!(" {argument}");
+                    }
+                }
+                if let Some(output) = function.outputs.first() {
+                    line += &format!(" -> {}", type_name(output));
+                }
+                line
+            })
+            .collect();
+        lines.join("\n")
+    }))
+}
+
+fn build(args: &[String]) -> Option<Result<String, Box<dyn Error>>> {
+    let (options, rest) = options(
+        args,
+        &["--wasm", "--contract", "--source", "--sequence", "--fee"],
+    )?;
+    let [wasm, contract, source, sequence, fee] = options.as_slice() else {
+        unreachable!()
+    };
+    let (wasm, contract, source, sequence) = ((*wasm)?, (*contract)?, (*source)?, (*sequence)?);
+    let (function, pairs) = rest.split_first()?;
+    let mut call_args = Vec::new();
+    for pair in pairs.chunks(2) {
+        let [name, value] = pair else {
+            return None;
+        };
+        call_args.push((name.strip_prefix("--")?, value.as_str()));
+    }
+
+    Some((|| {
+        let contract = ScAddress::from_str(contract)
+            .map_err(|_| format!("--contract: not a contract address: `{contract}`"))?;
+        let source = AccountId::from_str(source)
+            .map_err(|_| format!("--source: not an account: `{source}`"))?;
+        let sequence: i64 = sequence
+            .parse()
+            .map_err(|_| format!("--sequence: not a sequence number: `{sequence}`"))?;
+        let fee: u32 = match fee {
+            Some(fee) => fee
+                .parse()
+                .map_err(|_| format!("--fee: not an amount of stroops: `{fee}`"))?,
+            None => BASE_FEE,
+        };
+        let spec = read_spec(wasm)?;
+        let call = spec.invoke(&contract, function, &call_args)?;
+        let envelope = transaction(source, sequence, fee, call)?;
+        Ok(envelope.to_xdr_base64(Limits::none())?)
+    })())
+}
+
+fn sign_envelope(args: &[String]) -> Option<Result<String, Box<dyn Error>>> {
+    let (options, rest) = options(
+        args,
+        &[
+            "--secret-key",
+            "--network-passphrase",
+            "--signature-expiration-ledger",
+        ],
+    )?;
+    let key_path = options[0]?;
+    let passphrase = options[1].unwrap_or(TESTNET_PASSPHRASE);
+    let expiration = options[2];
+    let path = match rest {
+        [] => None,
+        [path] if path == "-" => None,
+        [path] => Some(path),
+        _ => return None,
+    };
+
+    Some((|| {
+        let key = read_secret_key(key_path)?;
+        let expiration = expiration
+            .map(|ledger| {
+                ledger
+                    .parse::<u32>()
+                    .map_err(|_| format!("--signature-expiration-ledger: not a ledger: `{ledger}`"))
+            })
+            .transpose()?;
+        let text = match path {
+            Some(path) => fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?,
+            None => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text)?;
+                text
+            }
+        };
+        let mut envelope = TransactionEnvelope::from_xdr_base64(text.trim(), Limits::none())
+            .map_err(|error| format!("not a base64 transaction envelope: {error}"))?;
+        sign(&mut envelope, &key, passphrase, expiration)?;
+        Ok(envelope.to_xdr_base64(Limits::none())?)
+    })())
+}
+
+fn read_spec(path: &str) -> Result<Spec, String> {
+    let wasm = fs::read(path).map_err(|error| format!("{path}: {error}"))?;
+    Spec::from_wasm(&wasm).map_err(|error| format!("{path}: {error}"))
+}
@@ -0,0 +1,325 @@
+//! Converts command-line arguments to `ScVal`s of a spec type.
+//!
+//! Scalars are written as they are: `1000`, `true`, `minter`, `G...`, and
+//! bytes in hex. `Option`s are their value or `null`. Vectors, tuples, maps
+//! and user-defined types are JSON, with scalars inside as JSON strings, or
+//! numbers for integers: `[["G...", "minter"]]`.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+use stellar_xdr::curr::{
+    Int128Parts, ScAddress, ScBytes, ScMap, ScMapEntry, ScSpecEntry, ScSpecTypeDef,
+    ScSpecUdtUnionCaseV0, ScString, ScSymbol, ScVal, ScVec, UInt128Parts,
+};
+
+use crate::Spec;
+
+/// The Rust name of a spec type, such as `Vec<(Address, Symbol)>`.
+pub fn type_name(type_: &ScSpecTypeDef) -> String {
+    let name = match type_ {
+        ScSpecTypeDef::Val => "Val",
+        ScSpecTypeDef::Bool => "bool",
+        ScSpecTypeDef::Void => "()",
+        ScSpecTypeDef::Error => "Error",
+        ScSpecTypeDef::U32 => "u32",
+        ScSpecTypeDef::I32 => "i32",
+        ScSpecTypeDef::U64 => "u64",
+        ScSpecTypeDef::I64 => "i64",
+        ScSpecTypeDef::Timepoint => "Timepoint",
+        ScSpecTypeDef::Duration => "Duration",
+        ScSpecTypeDef::U128 => "u128",
+        ScSpecTypeDef::I128 => "i128",
+        ScSpecTypeDef::U256 => "U256",
+        ScSpecTypeDef::I256 => "I256",
+        ScSpecTypeDef::Bytes => "Bytes",
+        ScSpecTypeDef::String => "String",
+        ScSpecTypeDef::Symbol => "Symbol",
+        ScSpecTypeDef::Address => "Address",
+        ScSpecTypeDef::MuxedAddress => "MuxedAddress",
+        ScSpecTypeDef::Option(option) => {
+            return format!("Option<{}>", type_name(&option.value_type))
+        }
+        ScSpecTypeDef::Result(result) => {
+            return format!(
+                "Result<{}, {}>",
+                type_name(&result.ok_type),
+                type_name(&result.error_type)
+            )
+        }
+        ScSpecTypeDef::Vec(vec) => return format!("Vec<{}>", type_name(&vec.element_type)),
+        ScSpecTypeDef::Map(map) => {
+            return format!(
+                "Map<{}, {}>",
+                type_name(&map.key_type),
+                type_name(&map.value_type)
+            )
+        }
+        ScSpecTypeDef::Tuple(tuple) => {
+            let types: Vec<String> = tuple.value_types.iter().map(type_name).collect();
+            return format!("({})", types.join(", "));
+        }
+        ScSpecTypeDef::BytesN(bytes) => return format!("BytesN<{}>", bytes.n),
+        ScSpecTypeDef::Udt(udt) => return udt.name.to_utf8_string_lossy(),
+    };
+    name.to_string()
+}
+
+/// Parses the command-line argument `text` as a `type_`.
+pub(crate) fn parse(spec: &Spec, type_: &ScSpecTypeDef, text: &str) -> Result<ScVal, String> {
+    match type_ {
+        ScSpecTypeDef::Option(_) if text == "null" => Ok(ScVal::Void),
+        ScSpecTypeDef::Option(option) => parse(spec, &option.value_type, text),
+        ScSpecTypeDef::Vec(_)
+        | ScSpecTypeDef::Map(_)
+        | ScSpecTypeDef::Tuple(_)
+        | ScSpecTypeDef::Udt(_) => {
+            let json = serde_json::from_str(text)
+                .map_err(|_| format!("expected {} as JSON, got `{text}`", type_name(type_)))?;
+            from_json(spec, type_, &json)
+        }
+        _ => scalar(type_, text),
+    }
+}
+
+fn from_json(spec: &Spec, type_: &ScSpecTypeDef, json: &Value) -> Result<ScVal, String> {
+    let invalid = || format!("expected {}, got {json}", type_name(type_));
+    match (type_, json) {
+        (ScSpecTypeDef::Option(_), Value::Null) => Ok(ScVal::Void),
+        (ScSpecTypeDef::Option(option), _) => from_json(spec, &option.value_type, json),
+        (ScSpecTypeDef::Bool, Value::Bool(value)) => Ok(ScVal::Bool(*value)),
+        (ScSpecTypeDef::Vec(vec), Value::Array(items)) => vec_of(
+            items
+                .iter()
+                .map(|item| from_json(spec, &vec.element_type, item)),
+        ),
+        (ScSpecTypeDef::Tuple(tuple), Value::Array(items))
+            if items.len() == tuple.value_types.len() =>
+        {
+            vec_of(
+                tuple
+                    .value_types
+                    .iter()
+                    .zip(items)
+                    .map(|(type_, item)| from_json(spec, type_, item)),
+            )
+        }
+        (ScSpecTypeDef::Map(map), Value::Object(object)) => map_of(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        scalar(&map.key_type, key)?,
+                        from_json(spec, &map.value_type, value)?,
+                    ))
+                })
+                .collect::<Result<_, String>>()?,
+        ),
+        (ScSpecTypeDef::Udt(udt), _) => from_udt(spec, &udt.name.to_utf8_string_lossy(), json),
+        (
+            ScSpecTypeDef::U32
+            | ScSpecTypeDef::I32
+            | ScSpecTypeDef::U64
+            | ScSpecTypeDef::I64
+            | ScSpecTypeDef::Timepoint
+            | ScSpecTypeDef::Duration
+            | ScSpecTypeDef::U128
+            | ScSpecTypeDef::I128,
+            Value::Number(number),
+        ) => scalar(type_, &number.to_string()),
+        (_, Value::String(text)) => scalar(type_, text),
+        _ => Err(invalid()),
+    }
+}
+
+/// A struct as a map of its fields, or a vector for tuple structs; a union
+/// case as a vector of its name and values; an enum as its `u32` value.
+fn from_udt(spec: &Spec, name: &str, json: &Value) -> Result<ScVal, String> {
+    let invalid = || format!("expected {name}, got {json}");
+    let udt = spec.entries().iter().find(|entry| match entry {
+        ScSpecEntry::UdtStructV0(udt) => udt.name.to_utf8_string_lossy() == name,
+        ScSpecEntry::UdtUnionV0(udt) => udt.name.to_utf8_string_lossy() == name,
+        ScSpecEntry::UdtEnumV0(udt) => udt.name.to_utf8_string_lossy() == name,
+        _ => false,
+    });
+    match (udt, json) {
+        (Some(ScSpecEntry::UdtStructV0(udt)), Value::Array(items))
+            if udt.fields.len() == items.len()
+                && udt
+                    .fields
+                    .iter()
+                    .all(|field| field.name.as_vec()[0].is_ascii_digit()) =>
+        {
+            vec_of(
+                udt.fields
+                    .iter()
+                    .zip(items)
+                    .map(|(field, item)| from_json(spec, &field.type_, item)),
+            )
+        }
+        (Some(ScSpecEntry::UdtStructV0(udt)), Value::Object(object))
+            if udt.fields.len() == object.len() =>
+        {
+            let mut fields = Vec::with_capacity(udt.fields.len());
+            for field in udt.fields.iter() {
+                let field_name = field.name.to_utf8_string_lossy();
+                let value = object.get(&field_name).ok_or_else(invalid)?;
+                fields.push((
+                    ScVal::Symbol(ScSymbol(field.name.as_vec().clone().try_into().unwrap())),
+                    from_json(spec, &field.type_, value)?,
+                ));
+            }
+            map_of(fields)
+        }
+        (Some(ScSpecEntry::UdtUnionV0(udt)), Value::String(case)) => {
+            let known = udt.cases.iter().any(|known| {
+                matches!(known, ScSpecUdtUnionCaseV0::VoidV0(known) if known.name.to_utf8_string_lossy() == *case)
+            });
+            if !known {
+                return Err(invalid());
+            }
+            vec_of([scalar(&ScSpecTypeDef::Symbol, case)])
+        }
+        (Some(ScSpecEntry::UdtUnionV0(udt)), Value::Object(object)) if object.len() == 1 => {
+            let (case, values) = object.iter().next().unwrap();
+            let types = udt
+                .cases
+                .iter()
+                .find_map(|known| match known {
+                    ScSpecUdtUnionCaseV0::TupleV0(known)
+                        if known.name.to_utf8_string_lossy() == *case =>
+                    {
+                        Some(&known.type_)
+                    }
+                    _ => None,
+                })
+                .ok_or_else(invalid)?;
+            let values = match values {
+                Value::Array(values) if types.len() != 1 => values.as_slice(),
+                value => std::slice::from_ref(value),
+            };
+            if values.len() != types.len() {
+                return Err(invalid());
+            }
+            vec_of(
+                std::iter::once(scalar(&ScSpecTypeDef::Symbol, case)).chain(
+                    types
+                        .iter()
+                        .zip(values)
+                        .map(|(type_, value)| from_json(spec, type_, value)),
+                ),
+            )
+        }
+        (Some(ScSpecEntry::UdtEnumV0(udt)), Value::Number(number)) => {
+            match number.as_u64().and_then(|value| u32::try_from(value).ok()) {
+                Some(value) if udt.cases.iter().any(|case| case.value == value) => {
+                    Ok(ScVal::U32(value))
+                }
+                _ => Err(invalid()),
+            }
+        }
+        (None, _) => Err(format!("the spec has no type {name}")),
+        _ => Err(invalid()),
+    }
+}
+
+fn scalar(type_: &ScSpecTypeDef, text: &str) -> Result<ScVal, String> {
+    let invalid = || format!("expected {}, got `{text}`", type_name(type_));
+    Ok(match type_ {
+        ScSpecTypeDef::Bool => match text {
+            "true" => ScVal::Bool(true),
+            "false" => ScVal::Bool(false),
+            _ => return Err(invalid()),
+        },
+        ScSpecTypeDef::Void if text == "null" => ScVal::Void,
+        ScSpecTypeDef::U32 => ScVal::U32(text.parse().map_err(|_| invalid())?),
+        ScSpecTypeDef::I32 => ScVal::I32(text.parse().map_err(|_| invalid())?),
+        ScSpecTypeDef::U64 => ScVal::U64(text.parse().map_err(|_| invalid())?),
+        ScSpecTypeDef::I64 => ScVal::I64(text.parse().map_err(|_| invalid())?),
+        ScSpecTypeDef::Timepoint => {
+            ScVal::Timepoint(text.parse::<u64>().map_err(|_| invalid())?.into())
+        }
+        ScSpecTypeDef::Duration => {
+            ScVal::Duration(text.parse::<u64>().map_err(|_| invalid())?.into())
+        }
+        ScSpecTypeDef::U128 => {
+            let value: u128 = text.parse().map_err(|_| invalid())?;
+            ScVal::U128(UInt128Parts {
+                hi: (value >> 64) as u64,
+                lo: value as u64,
+            })
+        }
+        ScSpecTypeDef::I128 => {
+            let value: i128 = text.parse().map_err(|_| invalid())?;
+            ScVal::I128(Int128Parts {
+                hi: (value >> 64) as i64,
+                lo: value as u64,
+            })
+        }
+        ScSpecTypeDef::Bytes => ScVal::Bytes(ScBytes(
+            hex(text)
+                .ok_or_else(invalid)?
+                .try_into()
+                .map_err(|_| invalid())?,
+        )),
+        ScSpecTypeDef::BytesN(bytes) => match hex(text) {
+            Some(value) if value.len() == bytes.n as usize => {
+                ScVal::Bytes(ScBytes(value.try_into().map_err(|_| invalid())?))
+            }
+            _ => return Err(invalid()),
+        },
+        ScSpecTypeDef::String => ScVal::String(ScString(text.try_into().map_err(|_| invalid())?)),
+        ScSpecTypeDef::Symbol
+            if text
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_') =>
+        {
+            ScVal::Symbol(ScSymbol(text.try_into().map_err(|_| invalid())?))
+        }
+        ScSpecTypeDef::Address => match ScAddress::from_str(text) {
+            Ok(address @ (ScAddress::Account(_) | ScAddress::Contract(_))) => {
+                ScVal::Address(address)
+            }
+            _ => return Err(invalid()),
+        },
+        ScSpecTypeDef::Vec(_)
+        | ScSpecTypeDef::Map(_)
+        | ScSpecTypeDef::Tuple(_)
+        | ScSpecTypeDef::Option(_)
+        | ScSpecTypeDef::Udt(_)
+        | ScSpecTypeDef::Symbol
+        | ScSpecTypeDef::Void => return Err(invalid()),
+        _ => return Err(format!("{} arguments are not supported", type_name(type_))),
+    })
+}
+
+fn vec_of(items: impl IntoIterator<Item = Result<ScVal, String>>) -> Result<ScVal, String> {
+    let items = items.into_iter().collect::<Result<Vec<_>, _>>()?;
+    let items = items
+        .try_into()
+        .map_err(|_| "too many elements".to_string())?;
+    Ok(ScVal::Vec(Some(ScVec(items))))
+}
+
+/// A map with its entries in key order, as the host requires.
+fn map_of(mut entries: Vec<(ScVal, ScVal)>) -> Result<ScVal, String> {
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let entries: Vec<ScMapEntry> = entries
+        .into_iter()
+        .map(|(key, val)| ScMapEntry { key, val })
+        .collect();
+    let entries = entries
+        .try_into()
+        .map_err(|_| "too many entries".to_string())?;
+    Ok(ScVal::Map(Some(ScMap(entries))))
+}
+
+fn hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,525 @@
+//! Builds transactions from the spec of the playground wasm and runs their
+//! calls in the sandbox.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use ed25519_dalek::{Signature, SigningKey, Verifier};
+use rbac_tx_builder::{
+    account_id, network_id, read_secret_key, sign, transaction, Error, Spec, BASE_FEE,
+    TESTNET_PASSPHRASE,
+};
+use sha2::{Digest, Sha256};
+use soroban_sdk::{Address, Env, Symbol, TryFromVal, Val};
+use stellar_xdr::curr::{
+    ContractId, Hash, HashIdPreimage, HashIdPreimageSorobanAuthorization, HostFunction, Limits,
+    MuxedAccount, OperationBody, ReadXdr, ScAddress, ScVal, SorobanAddressCredentials,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    SorobanCredentials, TransactionEnvelope, TransactionExt, WriteXdr,
+};
+
+fn wasm() -> &'static [u8] {
+    rbac_test_support::wasm("rbac-playground")
+}
+
+fn spec() -> Spec {
+    Spec::from_wasm(wasm()).unwrap()
+}
+
+fn key(byte: u8) -> SigningKey {
+    SigningKey::from_bytes(&[byte; 32])
+}
+
+fn strkey(key: &SigningKey) -> String {
+    account_id(key).to_string()
+}
+
+/// A contract address for calls that aren't made.
+fn contract() -> ScAddress {
+    ScAddress::Contract(ContractId(Hash([1; 32])))
+}
+
+/// The address of the playground, registered from the wasm.
+fn deploy(env: &Env, admin: &SigningKey) -> ScAddress {
+    let admin = Address::from_str(env, &strkey(admin));
+    let roles: soroban_sdk::Vec<(Symbol, Address)> = soroban_sdk::Vec::new(env);
+    let balances: Option<soroban_sdk::Vec<(Address, i128)>> = None;
+    let address = env.register(wasm(), (admin.clone(), admin, roles, balances));
+    ScAddress::from_str(&address.to_string().to_string()).unwrap()
+}
+
+/// Makes the call in `envelope`, as the host would.
+fn execute(env: &Env, envelope: &TransactionEnvelope) -> Val {
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("not a v1 envelope");
+    };
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("not an InvokeHostFunction operation");
+    };
+    let HostFunction::InvokeContract(call) = &op.host_function else {
+        panic!("not a contract call");
+    };
+    let contract =
+        Address::try_from_val(env, &ScVal::Address(call.contract_address.clone())).unwrap();
+    let function = Symbol::try_from_val(env, &ScVal::Symbol(call.function_name.clone())).unwrap();
+    let mut args = soroban_sdk::Vec::<Val>::new(env);
+    for arg in call.args.iter() {
+        args.push_back(Val::try_from_val(env, arg).unwrap());
+    }
+    env.invoke_contract(&contract, &function, args)
+}
+
+#[test]
+fn spec_lists_every_entrypoint() {
+    let spec = spec();
+    let names: Vec<String> = spec
+        .functions()
+        .map(|function| function.name.0.to_utf8_string_lossy())
+        .collect();
+
+    for name in [
+        "mint",
+        "batch_grant_roles",
+        "propose_transfer",
+        "upgrade",
+        "list_minters",
+        // AccessControl
+        "grant_role",
+        "has_role",
+        "transfer_admin_role",
+        "accept_admin_transfer",
+        // Ownable
+        "get_owner",
+        "transfer_ownership",
+        "renounce_ownership",
+    ] {
+        assert!(names.iter().any(|known| known == name), "missing {name}");
+    }
+    assert!(!names.iter().any(|name| name == "__constructor"));
+}
+
+#[test]
+fn built_transactions_call_the_playground() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (admin, alice, bob) = (key(1), key(2), key(3));
+    let id = deploy(&env, &admin);
+    let spec = spec();
+    let build = |function: &str, args: &[(&str, &str)]| {
+        let call = spec.invoke(&id, function, args).unwrap();
+        transaction(account_id(&admin), 42, BASE_FEE, call).unwrap()
+    };
+    let (admin, alice, bob) = (strkey(&admin), strkey(&alice), strkey(&bob));
+
+    let grant = build(
+        "grant_role",
+        &[("account", &alice), ("role", "minter"), ("caller", &admin)],
+    );
+    let TransactionEnvelope::Tx(envelope) = &grant else {
+        panic!("not a v1 envelope");
+    };
+    assert_eq!(
+        envelope.tx.source_account,
+        MuxedAccount::Ed25519(key(1).verifying_key().to_bytes().into())
+    );
+    assert_eq!(envelope.tx.seq_num.0, 42);
+    assert_eq!(envelope.tx.fee, BASE_FEE);
+    assert_eq!(envelope.tx.ext, TransactionExt::V0);
+    assert!(envelope.signatures.is_empty());
+    execute(&env, &grant);
+
+    execute(
+        &env,
+        &build(
+            "mint",
+            &[("to", &bob), ("amount", "1000"), ("caller", &alice)],
+        ),
+    );
+    let balance = execute(&env, &build("get_balance", &[("account", &bob)]));
+    assert_eq!(i128::try_from_val(&env, &balance).unwrap(), 1000);
+
+    let grants = format!(r#"[["{alice}", "burner"], ["{bob}", "viewer"]]"#);
+    execute(
+        &env,
+        &build(
+            "batch_grant_roles",
+            &[("grants", &grants), ("caller", &admin)],
+        ),
+    );
+    let has_role = execute(
+        &env,
+        &build("has_role", &[("account", &bob), ("role", "viewer")]),
+    );
+    assert!(Option::<u32>::try_from_val(&env, &has_role)
+        .unwrap()
+        .is_some());
+
+    // Option arguments may be left out.
+    execute(
+        &env,
+        &build("set_role_cap", &[("role", "viewer"), ("max_members", "5")]),
+    );
+    let cap = execute(&env, &build("get_role_cap", &[("role", "viewer")]));
+    assert_eq!(Option::<u32>::try_from_val(&env, &cap).unwrap(), Some(5));
+    execute(&env, &build("set_role_cap", &[("role", "viewer")]));
+    let cap = execute(&env, &build("get_role_cap", &[("role", "viewer")]));
+    assert_eq!(Option::<u32>::try_from_val(&env, &cap).unwrap(), None);
+
+    execute(
+        &env,
+        &build(
+            "transfer_ownership",
+            &[("new_owner", &alice), ("live_until_ledger", "1000")],
+        ),
+    );
+    execute(&env, &build("accept_ownership", &[]));
+    let owner = execute(&env, &build("get_owner", &[]));
+    assert_eq!(
+        Option::<Address>::try_from_val(&env, &owner).unwrap(),
+        Some(Address::from_str(&env, &alice))
+    );
+}
+
+#[test]
+fn arguments_are_checked_against_the_spec() {
+    let spec = spec();
+    let contract = contract();
+    let account = strkey(&key(1));
+    let error = |function: &str, args: &[(&str, &str)]| {
+        spec.invoke(&contract, function, args)
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert_eq!(
+        error("__constructor", &[]),
+        "the contract has no function `__constructor`"
+    );
+    assert_eq!(
+        error("mint_all", &[]),
+        "the contract has no function `mint_all`"
+    );
+    assert_eq!(
+        error("mint", &[("to", &account), ("amount", "1")]),
+        "`mint` needs the argument `caller`"
+    );
+    assert_eq!(
+        error("get_balance", &[("account", &account), ("role", "minter")]),
+        "`get_balance` has no argument `role`"
+    );
+    assert_eq!(
+        error(
+            "get_balance",
+            &[("account", &account), ("account", &account)]
+        ),
+        "`get_balance` argument `account`: given more than once"
+    );
+    assert_eq!(
+        error(
+            "mint",
+            &[("to", &account), ("amount", "1.5"), ("caller", &account)]
+        ),
+        "`mint` argument `amount`: expected i128, got `1.5`"
+    );
+    assert_eq!(
+        error("get_balance", &[("account", "alice")]),
+        "`get_balance` argument `account`: expected Address, got `alice`"
+    );
+    assert_eq!(
+        error("get_role_cap", &[("role", "not a role")]),
+        "`get_role_cap` argument `role`: expected Symbol, got `not a role`"
+    );
+    assert_eq!(
+        error(
+            "get_role_member",
+            &[("role", "minter"), ("index", "4294967296")]
+        ),
+        "`get_role_member` argument `index`: expected u32, got `4294967296`"
+    );
+    assert_eq!(
+        error("upgrade", &[("new_wasm_hash", "abcd")]),
+        "`upgrade` argument `new_wasm_hash`: expected BytesN<32>, got `abcd`"
+    );
+    assert_eq!(
+        error("batch_grant_roles", &[("grants", "minter"), ("caller", &account)]),
+        "`batch_grant_roles` argument `grants`: expected Vec<(Address, Symbol)> as JSON, got `minter`"
+    );
+    assert_eq!(
+        error(
+            "batch_grant_roles",
+            &[
+                ("grants", &format!(r#"[["{account}"]]"#)),
+                ("caller", &account)
+            ]
+        ),
+        format!(
+            "`batch_grant_roles` argument `grants`: expected (Address, Symbol), got [\"{account}\"]"
+        )
+    );
+    assert!(matches!(
+        spec.invoke(
+            &contract,
+            "pause",
+            &[
+                ("caller", &account),
+                ("reason", "x"),
+                ("until_ledger", "-1")
+            ]
+        ),
+        Err(Error::InvalidArgument { .. })
+    ));
+}
+
+#[test]
+fn signatures_verify_for_the_network() {
+    let path = format!("{}/signer.key", env!("CARGO_TARGET_TMPDIR"));
+    let signer = key(9);
+    std::fs::write(
+        &path,
+        format!(
+            "{}\n",
+            stellar_strkey::ed25519::PrivateKey(signer.to_bytes())
+        ),
+    )
+    .unwrap();
+    let read = read_secret_key(&path).unwrap();
+    assert_eq!(read.to_bytes(), signer.to_bytes());
+
+    let spec = spec();
+    let contract = contract();
+    let call = spec.invoke(&contract, "accept_ownership", &[]).unwrap();
+    let mut envelope = transaction(account_id(&signer), 7, BASE_FEE, call).unwrap();
+    sign(&mut envelope, &read, TESTNET_PASSPHRASE, None).unwrap();
+
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("not a v1 envelope");
+    };
+    let [signature] = envelope.signatures.as_slice() else {
+        panic!("expected one signature");
+    };
+    let public_key = signer.verifying_key();
+    assert_eq!(signature.hint.0, public_key.to_bytes()[28..]);
+    let hash = envelope.tx.hash(network_id(TESTNET_PASSPHRASE).0).unwrap();
+    let signature = Signature::from_slice(&signature.signature.0).unwrap();
+    assert!(public_key.verify(&hash, &signature).is_ok());
+    let other = envelope
+        .tx
+        .hash(network_id("Public Global Stellar Network ; September 2015").0)
+        .unwrap();
+    assert!(public_key.verify(&other, &signature).is_err());
+
+    std::fs::write(&path, strkey(&signer)).unwrap();
+    assert!(matches!(read_secret_key(&path), Err(Error::SecretKey(_))));
+}
+
+/// A caller other than the source account signs its authorization entry,
+/// which simulation adds, before the source signs the envelope.
+#[test]
+fn callers_sign_their_authorizations() {
+    let (source, caller, outsider) = (key(6), key(7), key(8));
+    let spec = spec();
+    let call = spec
+        .invoke(
+            &contract(),
+            "grant_role",
+            &[
+                ("account", &strkey(&outsider)),
+                ("role", "minter"),
+                ("caller", &strkey(&caller)),
+            ],
+        )
+        .unwrap();
+    let mut envelope = transaction(account_id(&source), 3, BASE_FEE, call.clone()).unwrap();
+    assert!(matches!(
+        sign(&mut envelope, &caller, TESTNET_PASSPHRASE, Some(1000)),
+        Err(Error::Envelope(_))
+    ));
+
+    // What simulation adds for `caller.require_auth()`.
+    let TransactionEnvelope::Tx(v1) = &mut envelope else {
+        panic!("not a v1 envelope");
+    };
+    let OperationBody::InvokeHostFunction(op) =
+        &mut v1.tx.operations.iter_mut().next().unwrap().body
+    else {
+        panic!("not an InvokeHostFunction operation");
+    };
+    let invocation = SorobanAuthorizedInvocation {
+        function: SorobanAuthorizedFunction::ContractFn(call),
+        sub_invocations: Default::default(),
+    };
+    op.auth = vec![SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: ScAddress::Account(account_id(&caller)),
+            nonce: 42,
+            signature_expiration_ledger: 0,
+            signature: ScVal::Void,
+        }),
+        root_invocation: invocation.clone(),
+    }]
+    .try_into()
+    .unwrap();
+
+    let mut signed_first = envelope.clone();
+    sign(&mut signed_first, &source, TESTNET_PASSPHRASE, None).unwrap();
+    assert!(matches!(
+        sign(&mut signed_first, &caller, TESTNET_PASSPHRASE, Some(1000)),
+        Err(Error::Envelope(_))
+    ));
+    assert!(matches!(
+        sign(&mut envelope, &caller, TESTNET_PASSPHRASE, None),
+        Err(Error::Envelope(_))
+    ));
+    assert!(matches!(
+        sign(&mut envelope, &outsider, TESTNET_PASSPHRASE, Some(1000)),
+        Err(Error::Envelope(_))
+    ));
+
+    sign(&mut envelope, &caller, TESTNET_PASSPHRASE, Some(1000)).unwrap();
+    sign(&mut envelope, &source, TESTNET_PASSPHRASE, None).unwrap();
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("not a v1 envelope");
+    };
+    let [signature] = envelope.signatures.as_slice() else {
+        panic!("expected only the source's signature");
+    };
+    let hash = envelope.tx.hash(network_id(TESTNET_PASSPHRASE).0).unwrap();
+    let signature = Signature::from_slice(&signature.signature.0).unwrap();
+    assert!(source.verifying_key().verify(&hash, &signature).is_ok());
+
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("not an InvokeHostFunction operation");
+    };
+    let SorobanCredentials::Address(credentials) = &op.auth[0].credentials else {
+        panic!("not address credentials");
+    };
+    assert_eq!(credentials.signature_expiration_ledger, 1000);
+    let ScVal::Vec(Some(signatures)) = &credentials.signature else {
+        panic!("not a vector of signatures");
+    };
+    let [ScVal::Map(Some(fields))] = signatures.as_slice() else {
+        panic!("expected one signature");
+    };
+    let field = |name: &str| -> Vec<u8> {
+        let entry = fields
+            .iter()
+            .find(|entry| entry.key == ScVal::Symbol(name.try_into().unwrap()))
+            .unwrap();
+        let ScVal::Bytes(bytes) = &entry.val else {
+            panic!("`{name}` is not bytes");
+        };
+        bytes.to_vec()
+    };
+    let public_key = caller.verifying_key();
+    assert_eq!(field("public_key"), public_key.to_bytes());
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: network_id(TESTNET_PASSPHRASE),
+        nonce: 42,
+        signature_expiration_ledger: 1000,
+        invocation,
+    });
+    let payload = Sha256::digest(preimage.to_xdr(Limits::none()).unwrap());
+    let signature = Signature::from_slice(&field("signature")).unwrap();
+    assert!(public_key.verify(&payload, &signature).is_ok());
+}
+
+#[test]
+fn cli_builds_and_signs_like_the_library() {
+    let wasm_path = format!("{}/playground.wasm", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&wasm_path, wasm()).unwrap();
+    let key_path = format!("{}/cli.key", env!("CARGO_TARGET_TMPDIR"));
+    let signer = key(5);
+    std::fs::write(
+        &key_path,
+        stellar_strkey::ed25519::PrivateKey(signer.to_bytes()).to_string(),
+    )
+    .unwrap();
+    let contract = contract().to_string();
+    let account = strkey(&signer);
+    let cli = || Command::new(env!("CARGO_BIN_EXE_rbac-tx-builder"));
+
+    let output = cli()
+        .args(["build", "--wasm", &wasm_path, "--contract", &contract])
+        .args(["--source", &account, "--sequence", "11", "--fee", "500"])
+        .args(["grant_role", "--account", &account, "--role", "minter"])
+        .args(["--caller", &account])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let built = String::from_utf8(output.stdout).unwrap();
+    let call = spec()
+        .invoke(
+            &ScAddress::from_str(&contract).unwrap(),
+            "grant_role",
+            &[
+                ("account", &account),
+                ("role", "minter"),
+                ("caller", &account),
+            ],
+        )
+        .unwrap();
+    let mut expected = transaction(account_id(&signer), 11, 500, call).unwrap();
+    assert_eq!(
+        built.trim(),
+        expected.to_xdr_base64(Limits::none()).unwrap()
+    );
+
+    let mut child = cli()
+        .args(["sign", "--secret-key", &key_path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(built.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    sign(&mut expected, &signer, TESTNET_PASSPHRASE, None).unwrap();
+    let signed = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        TransactionEnvelope::from_xdr_base64(signed.trim(), Limits::none()).unwrap(),
+        expected
+    );
+
+    // Unsimulated, the envelope has nothing for another account to sign.
+    let envelope_path = format!("{}/built.xdr", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&envelope_path, &built).unwrap();
+    let other_path = format!("{}/other.key", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(
+        &other_path,
+        stellar_strkey::ed25519::PrivateKey(key(4).to_bytes()).to_string(),
+    )
+    .unwrap();
+    let output = cli()
+        .args(["sign", "--secret-key", &other_path])
+        .args(["--signature-expiration-ledger", "1000", &envelope_path])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("neither the source account nor"));
+
+    let output = cli()
+        .args(["build", "--wasm", &wasm_path, "--contract", &contract])
+        .args(["--source", &account, "--sequence", "11"])
+        .args([
+            "mint", "--to", &account, "--amount", "lots", "--caller", &account,
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "`mint` argument `amount`: expected i128, got `lots`"
+    );
+}